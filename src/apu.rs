@@ -0,0 +1,667 @@
+// APU (Audio Processing Unit)
+//
+// The Game Boy has 4 sound channels:
+//   Channel 1: Square wave with frequency sweep (NR10-NR14, 0xFF10-0xFF14)
+//   Channel 2: Square wave (NR21-NR24, 0xFF16-0xFF19)
+//   Channel 3: Programmable wave from wave RAM (NR30-NR34, 0xFF1A-0xFF1E)
+//   Channel 4: Noise (NR41-NR44, 0xFF20-0xFF23)
+//
+// Control registers:
+//   NR50 (0xFF24): Master volume / VIN panning
+//   NR51 (0xFF25): Channel panning
+//   NR52 (0xFF26): Bit 7 = APU power, Bits 0-3 = channel on flags (read-only)
+//
+// Wave RAM (0xFF30-0xFF3F): 32 4-bit samples for channel 3
+//
+// Frame sequencer:
+// Clocked at 512 Hz (every 8192 T-cycles), it drives the slower units:
+//   Step:   0  1  2  3  4  5  6  7
+//   Length: x     x     x     x        (256 Hz)
+//   Sweep:        x           x        (128 Hz)
+//   Volume:                      x     (64 Hz)
+//
+// Length counter:
+// Loaded from NRx1 (64 - value, or 256 - value for channel 3). While the
+// length-enable bit (NRx4 bit 6) is set, each length clock decrements it and
+// the channel turns off when it reaches zero. Triggering (NRx4 bit 7) turns
+// the channel on if its DAC is on, reloading the length if it was zero.
+//
+// Volume envelope (channels 1, 2 and 4, NRx2):
+//   Bits 7-4: initial volume, Bit 3: direction (1 = up), Bits 2-0: period
+// Triggering loads the volume and period. Each 64 Hz clock counts the period
+// down; at zero the volume steps by one until it reaches 0 or 15, where the
+// envelope stops. A period of 0 disables stepping.
+//
+// Zombie mode (DMG): writing NRx2 while the channel is on changes the volume
+// without a trigger:
+//   - If the old period was 0 and the envelope hadn't stopped, volume += 1;
+//     otherwise, if the old direction was down, volume += 2
+//   - If the direction changed, volume = 16 - volume
+//   - Only the low 4 bits of the volume are kept
+//
+// Trigger (NRx4 bit 7), the same for every channel:
+//   - The channel turns on if its DAC is on
+//   - A zero length counter is reloaded with the full length
+//   - The frequency timer is reloaded from the current period
+//   - The envelope (channels 1, 2 and 4) reloads its volume and period
+//
+// Frequency timer periods in T-cycles:
+//   Channels 1-2: (2048 - frequency) * 4, Channel 3: (2048 - frequency) * 2
+//   Channel 4: divisor << shift (NR43; divisor code 0 = 8, n = n * 16)
+//
+// Power (NR52 bit 7):
+// Powering off zeroes NR10-NR51 and turns every channel off; while off, all
+// register writes except NR52 are ignored. Wave RAM is not affected. On the
+// DMG the length counters are kept, and NRx1 writes still load them while
+// powered off. Powering back on restarts the frame sequencer at step 0.
+//
+// Only channel on/off state, frequency timers and volume are modeled so far;
+// no samples are generated.
+
+/// T-cycles per frame sequencer step (512 Hz)
+pub const CYCLES_PER_FRAME_SEQUENCER_STEP: u32 = 8192;
+
+/// Bits that always read as 1, for 0xFF10-0xFF2F (write-only and unused bits)
+const READ_MASKS: [u8; 0x20] = [
+    0x80, 0x3F, 0x00, 0xFF, 0xBF, // NR10-NR14
+    0xFF, 0x3F, 0x00, 0xFF, 0xBF, // (unused), NR21-NR24
+    0x7F, 0xFF, 0x9F, 0xFF, 0xBF, // NR30-NR34
+    0xFF, 0xFF, 0x00, 0x00, 0xBF, // (unused), NR41-NR44
+    0x00, 0x00, 0x70,             // NR50-NR52
+    0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, // unused
+];
+
+/// Volume envelope unit (channels 1, 2 and 4)
+#[derive(Debug, Clone, Copy, Default)]
+struct Envelope {
+    /// Current volume (0-15)
+    volume: u8,
+    /// Step direction (NRx2 bit 3)
+    increase: bool,
+    /// Clocks between volume steps (0 = no stepping)
+    period: u8,
+    /// Clocks left until the next step
+    timer: u8,
+    /// Volume reached 0 or 15; no more automatic steps until retriggered
+    stopped: bool,
+}
+
+impl Envelope {
+    /// Reload from NRx2 on trigger
+    fn trigger(&mut self, nrx2: u8) {
+        self.volume = nrx2 >> 4;
+        self.increase = nrx2 & 0x08 != 0;
+        self.period = nrx2 & 0x07;
+        self.timer = self.period;
+        self.stopped = false;
+    }
+
+    /// NRx2 write while the channel is on (zombie mode)
+    fn zombie_write(&mut self, nrx2: u8) {
+        if self.period == 0 && !self.stopped {
+            self.volume += 1;
+        } else if !self.increase {
+            self.volume += 2;
+        }
+        let increase = nrx2 & 0x08 != 0;
+        if increase != self.increase {
+            self.volume = 16u8.wrapping_sub(self.volume);
+        }
+        self.volume &= 0x0F;
+        self.increase = increase;
+        self.period = nrx2 & 0x07;
+    }
+
+    /// 64 Hz envelope clock
+    fn clock(&mut self) {
+        if self.period == 0 || self.stopped {
+            return;
+        }
+        self.timer = self.timer.saturating_sub(1);
+        if self.timer > 0 {
+            return;
+        }
+        self.timer = self.period;
+        if self.increase && self.volume < 15 {
+            self.volume += 1;
+        } else if !self.increase && self.volume > 0 {
+            self.volume -= 1;
+        } else {
+            self.stopped = true;
+        }
+    }
+}
+
+/// State shared by all channels: on/off, DAC and length counter
+#[derive(Debug, Clone, Copy)]
+struct Channel {
+    /// Channel is producing output (NR52 status bit)
+    enabled: bool,
+    /// DAC power (channel 3: NR30 bit 7, others: NRx2 bits 3-7 non-zero)
+    dac_enabled: bool,
+    /// Remaining length clocks
+    length_counter: u16,
+    /// NRx4 bit 6
+    length_enabled: bool,
+    /// Full length (64, or 256 for channel 3)
+    max_length: u16,
+    /// T-cycles until the waveform advances a step
+    frequency_timer: u32,
+    /// Volume envelope (unused by channel 3)
+    envelope: Envelope,
+}
+
+impl Channel {
+    fn new(max_length: u16) -> Self {
+        Self {
+            enabled: false,
+            dac_enabled: false,
+            length_counter: 0,
+            length_enabled: false,
+            max_length,
+            frequency_timer: 0,
+            envelope: Envelope::default(),
+        }
+    }
+
+    /// Load the length counter from the NRx1 length field
+    fn load_length(&mut self, value: u8) {
+        let mask = self.max_length - 1;
+        self.length_counter = self.max_length - (value as u16 & mask);
+    }
+
+    /// Power-off: everything but the length counter is cleared
+    fn power_off(&mut self) {
+        self.enabled = false;
+        self.dac_enabled = false;
+        self.length_enabled = false;
+        self.envelope = Envelope::default();
+    }
+
+    /// Set DAC power; turning the DAC off also turns the channel off
+    fn set_dac(&mut self, on: bool) {
+        self.dac_enabled = on;
+        if !on {
+            self.enabled = false;
+        }
+    }
+
+    /// Trigger: restart the channel with a fresh frequency timer
+    /// (the envelope is reloaded separately, it needs NRx2)
+    fn trigger(&mut self, period: u32) {
+        self.enabled = self.dac_enabled;
+        if self.length_counter == 0 {
+            self.length_counter = self.max_length;
+        }
+        self.frequency_timer = period;
+    }
+
+    /// Advance the frequency timer, reloading it from `period` on expiry
+    fn clock_frequency(&mut self, cycles: u32, period: u32) {
+        let mut cycles = cycles;
+        while cycles >= self.frequency_timer {
+            cycles -= self.frequency_timer;
+            self.frequency_timer = period;
+        }
+        self.frequency_timer -= cycles;
+    }
+
+    /// 256 Hz length clock
+    fn clock_length(&mut self) {
+        if self.length_enabled && self.length_counter > 0 {
+            self.length_counter -= 1;
+            if self.length_counter == 0 {
+                self.enabled = false;
+            }
+        }
+    }
+}
+
+/// NRx2 register index (from 0xFF10) of the channels with an envelope
+const ENVELOPE_REGS: [(usize, usize); 3] = [(0, 0x02), (1, 0x07), (3, 0x11)];
+
+/// The APU state
+pub struct Apu {
+    /// Raw register values for 0xFF10-0xFF2F
+    regs: [u8; 0x20],
+    /// Wave RAM (0xFF30-0xFF3F)
+    pub wave_ram: [u8; 16],
+    /// Channels 1-4
+    channels: [Channel; 4],
+    /// NR52 bit 7
+    powered: bool,
+    /// Cycles accumulated toward the next frame sequencer step
+    sequencer_cycles: u32,
+    /// Current frame sequencer step (0-7)
+    sequencer_step: u8,
+}
+
+impl Apu {
+    pub fn new() -> Self {
+        Self {
+            regs: [0; 0x20],
+            wave_ram: [0; 16],
+            channels: [Channel::new(64), Channel::new(64), Channel::new(256), Channel::new(64)],
+            powered: true,
+            sequencer_cycles: 0,
+            sequencer_step: 0,
+        }
+    }
+
+    /// Check if a channel (1-4) is currently on
+    pub fn channel_enabled(&self, channel: usize) -> bool {
+        self.channels[channel - 1].enabled
+    }
+
+    /// Current envelope volume (0-15) of channel 1, 2 or 4
+    pub fn channel_volume(&self, channel: usize) -> u8 {
+        self.channels[channel - 1].envelope.volume
+    }
+
+    /// T-cycles until the waveform of a channel (1-4) advances
+    pub fn frequency_timer(&self, channel: usize) -> u32 {
+        self.channels[channel - 1].frequency_timer
+    }
+
+    /// Frequency timer period of a channel (0-3) from its current registers
+    fn frequency_period(&self, channel: usize) -> u32 {
+        if channel == 3 {
+            let nr43 = self.regs[0x12];
+            let divisor = match nr43 & 0x07 {
+                0 => 8,
+                code => code as u32 * 16,
+            };
+            return divisor << (nr43 >> 4);
+        }
+        let base = 0x03 + 5 * channel;
+        let frequency = self.regs[base] as u32 | (self.regs[base + 1] as u32 & 0x07) << 8;
+        let multiplier = if channel == 2 { 2 } else { 4 };
+        (2048 - frequency) * multiplier
+    }
+
+    /// Trigger a channel (0-3)
+    fn trigger(&mut self, channel: usize) {
+        let period = self.frequency_period(channel);
+        self.channels[channel].trigger(period);
+        if let Some(&(_, reg)) = ENVELOPE_REGS.iter().find(|&&(ch, _)| ch == channel) {
+            self.channels[channel].envelope.trigger(self.regs[reg]);
+        }
+    }
+
+    /// Check if the APU is powered on (NR52 bit 7)
+    pub fn powered(&self) -> bool {
+        self.powered
+    }
+
+    /// Advance the frame sequencer by the given number of T-cycles
+    pub fn tick(&mut self, cycles: u32) {
+        if !self.powered {
+            return;
+        }
+
+        for channel in 0..4 {
+            if self.channels[channel].enabled {
+                let period = self.frequency_period(channel);
+                self.channels[channel].clock_frequency(cycles, period);
+            }
+        }
+
+        self.sequencer_cycles += cycles;
+        while self.sequencer_cycles >= CYCLES_PER_FRAME_SEQUENCER_STEP {
+            self.sequencer_cycles -= CYCLES_PER_FRAME_SEQUENCER_STEP;
+            self.step_frame_sequencer();
+        }
+    }
+
+    /// Run one frame sequencer step
+    fn step_frame_sequencer(&mut self) {
+        if matches!(self.sequencer_step, 0 | 2 | 4 | 6) {
+            for channel in self.channels.iter_mut() {
+                channel.clock_length();
+            }
+        }
+        if self.sequencer_step == 7 {
+            for (channel, _) in ENVELOPE_REGS {
+                self.channels[channel].envelope.clock();
+            }
+        }
+        self.sequencer_step = (self.sequencer_step + 1) % 8;
+    }
+
+    /// Clear NR10-NR51 and turn all channels off (wave RAM is kept)
+    fn power_off(&mut self) {
+        self.regs[..=0x15].fill(0);
+        for channel in self.channels.iter_mut() {
+            channel.power_off();
+        }
+    }
+
+    /// Read an APU register or wave RAM (0xFF10-0xFF3F)
+    pub fn read(&self, addr: u16) -> u8 {
+        match addr {
+            0xFF26 => {
+                let status = self
+                    .channels
+                    .iter()
+                    .enumerate()
+                    .fold(0, |acc, (i, ch)| acc | (ch.enabled as u8) << i);
+                0x70 | (self.powered as u8) << 7 | status
+            }
+            0xFF10..=0xFF2F => {
+                let index = (addr - 0xFF10) as usize;
+                self.regs[index] | READ_MASKS[index]
+            }
+            0xFF30..=0xFF3F => self.wave_ram[(addr - 0xFF30) as usize],
+            _ => 0xFF,
+        }
+    }
+
+    /// Last value written to an APU register (0xFF10-0xFF3F), without read masks
+    /// NR52 is reported as read, since its status bits aren't written.
+    pub fn raw_register(&self, addr: u16) -> u8 {
+        match addr {
+            0xFF10..=0xFF25 | 0xFF27..=0xFF2F => self.regs[(addr - 0xFF10) as usize],
+            _ => self.read(addr),
+        }
+    }
+
+    /// Write an APU register or wave RAM (0xFF10-0xFF3F)
+    pub fn write(&mut self, addr: u16, value: u8) {
+        match addr {
+            0xFF26 => {
+                let power = value & 0x80 != 0;
+                if self.powered && !power {
+                    self.power_off();
+                } else if !self.powered && power {
+                    self.sequencer_cycles = 0;
+                    self.sequencer_step = 0;
+                }
+                self.powered = power;
+            }
+            // While powered off, only the DMG length counters can be written
+            0xFF11 | 0xFF16 | 0xFF1B | 0xFF20 if !self.powered => {
+                let channel = match addr {
+                    0xFF11 => 0,
+                    0xFF16 => 1,
+                    0xFF1B => 2,
+                    _ => 3,
+                };
+                self.channels[channel].load_length(value);
+            }
+            0xFF10..=0xFF2F if !self.powered => {}
+            0xFF10..=0xFF2F => {
+                let index = (addr - 0xFF10) as usize;
+                self.regs[index] = value;
+                if let Some(&(channel, _)) = ENVELOPE_REGS.iter().find(|&&(_, reg)| reg == index) {
+                    if self.channels[channel].enabled {
+                        self.channels[channel].envelope.zombie_write(value);
+                    }
+                }
+                match addr {
+                    // Length loads
+                    0xFF11 => self.channels[0].load_length(value),
+                    0xFF16 => self.channels[1].load_length(value),
+                    0xFF1B => self.channels[2].load_length(value),
+                    0xFF20 => self.channels[3].load_length(value),
+
+                    // DAC power
+                    0xFF12 => self.channels[0].set_dac(value & 0xF8 != 0),
+                    0xFF17 => self.channels[1].set_dac(value & 0xF8 != 0),
+                    0xFF1A => self.channels[2].set_dac(value & 0x80 != 0),
+                    0xFF21 => self.channels[3].set_dac(value & 0xF8 != 0),
+
+                    // Length enable / trigger
+                    0xFF14 | 0xFF19 | 0xFF1E | 0xFF23 => {
+                        let channel = (index - 0x04) / 5;
+                        self.channels[channel].length_enabled = value & 0x40 != 0;
+                        if value & 0x80 != 0 {
+                            self.trigger(channel);
+                        }
+                    }
+
+                    _ => {}
+                }
+            }
+            0xFF30..=0xFF3F => self.wave_ram[(addr - 0xFF30) as usize] = value,
+            _ => {}
+        }
+    }
+}
+
+impl Default for Apu {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// (NRx1, NRx2/NR30, NRx4) addresses and the DAC-on value for each channel
+    const CHANNEL_REGS: [(u16, u16, u8, u16); 4] = [
+        (0xFF11, 0xFF12, 0xF0, 0xFF14),
+        (0xFF16, 0xFF17, 0xF0, 0xFF19),
+        (0xFF1B, 0xFF1A, 0x80, 0xFF1E),
+        (0xFF20, 0xFF21, 0xF0, 0xFF23),
+    ];
+
+    #[test]
+    fn test_length_counter_disables_channels() {
+        for (index, &(length_reg, dac_reg, dac_on, control_reg)) in CHANNEL_REGS.iter().enumerate() {
+            let channel = index + 1;
+            let mut apu = Apu::new();
+
+            // Channel under test: length 2 with length enabled
+            let length_value = if channel == 3 { 254 } else { 62 };
+            apu.write(dac_reg, dac_on);
+            apu.write(length_reg, length_value);
+            apu.write(control_reg, 0xC0);
+
+            // Another channel plays without length enabled
+            let (other_length, other_dac, other_on, other_control) = CHANNEL_REGS[(index + 1) % 4];
+            let other = (index + 1) % 4 + 1;
+            apu.write(other_dac, other_on);
+            apu.write(other_length, 0xFF);
+            apu.write(other_control, 0x80);
+
+            assert!(apu.channel_enabled(channel));
+            assert_eq!(apu.read(0xFF26) & (1 << index), 1 << index);
+
+            // Length clocks happen on steps 0, 2, 4, 6 -> 2 clocks after 3 steps
+            apu.tick(CYCLES_PER_FRAME_SEQUENCER_STEP);
+            assert!(apu.channel_enabled(channel), "channel {} off too early", channel);
+            apu.tick(2 * CYCLES_PER_FRAME_SEQUENCER_STEP);
+            assert!(!apu.channel_enabled(channel), "channel {} still on", channel);
+            assert_eq!(apu.read(0xFF26) & (1 << index), 0);
+
+            // The length-disabled channel keeps playing
+            apu.tick(1000 * CYCLES_PER_FRAME_SEQUENCER_STEP);
+            assert!(apu.channel_enabled(other), "channel {} stopped", other);
+        }
+    }
+
+    #[test]
+    fn test_trigger_requires_dac() {
+        let mut apu = Apu::new();
+
+        // DAC off: trigger doesn't turn the channel on
+        apu.write(0xFF17, 0x00);
+        apu.write(0xFF19, 0x80);
+        assert!(!apu.channel_enabled(2));
+
+        apu.write(0xFF17, 0xF0);
+        apu.write(0xFF19, 0x80);
+        assert!(apu.channel_enabled(2));
+
+        // Turning the DAC off stops the channel
+        apu.write(0xFF17, 0x00);
+        assert!(!apu.channel_enabled(2));
+    }
+
+    #[test]
+    fn test_register_read_masks() {
+        let mut apu = Apu::new();
+        apu.write(0xFF11, 0x00);
+        assert_eq!(apu.read(0xFF11), 0x3F); // Length bits are write-only
+        assert_eq!(apu.read(0xFF13), 0xFF); // Frequency low is write-only
+        assert_eq!(apu.read(0xFF26), 0xF0); // Powered, no channels on
+    }
+
+    #[test]
+    fn test_power_off_clears_registers() {
+        let mut apu = Apu::new();
+        for addr in 0xFF10..=0xFF25u16 {
+            apu.write(addr, 0xFF);
+        }
+        for i in 0..16 {
+            apu.write(0xFF30 + i, i as u8);
+        }
+        apu.write(0xFF12, 0xF0);
+        apu.write(0xFF14, 0x80);
+        assert!(apu.channel_enabled(1));
+
+        apu.write(0xFF26, 0x00);
+        assert_eq!(apu.read(0xFF26), 0x70);
+        assert!(!apu.channel_enabled(1));
+
+        // NR10-NR51: all writable bits read back as zero
+        for addr in 0xFF10..=0xFF25u16 {
+            let index = (addr - 0xFF10) as usize;
+            assert_eq!(apu.read(addr), READ_MASKS[index], "register {:04X}", addr);
+        }
+        // Wave RAM is preserved
+        for i in 0..16 {
+            assert_eq!(apu.read(0xFF30 + i), i as u8);
+        }
+
+        // Writes are ignored while powered off
+        apu.write(0xFF24, 0x77);
+        apu.write(0xFF12, 0xF0);
+        apu.write(0xFF14, 0x80);
+        assert_eq!(apu.read(0xFF24), 0x00);
+        assert!(!apu.channel_enabled(1));
+
+        // ...until power is restored
+        apu.write(0xFF26, 0x80);
+        apu.write(0xFF24, 0x77);
+        assert_eq!(apu.read(0xFF24), 0x77);
+    }
+
+    #[test]
+    fn test_length_writable_while_powered_off() {
+        let mut apu = Apu::new();
+        apu.write(0xFF26, 0x00);
+        apu.write(0xFF16, 0x3E); // Length 2 (DMG: accepted while off)
+        apu.write(0xFF26, 0x80);
+
+        apu.write(0xFF17, 0xF0);
+        apu.write(0xFF19, 0x40 | 0x80); // Trigger with length enabled, no reload
+        apu.tick(CYCLES_PER_FRAME_SEQUENCER_STEP);
+        assert!(apu.channel_enabled(2));
+        apu.tick(2 * CYCLES_PER_FRAME_SEQUENCER_STEP);
+        assert!(!apu.channel_enabled(2));
+    }
+
+    #[test]
+    fn test_envelope_steps_at_64hz() {
+        let mut apu = Apu::new();
+        let envelope_clock = 8 * CYCLES_PER_FRAME_SEQUENCER_STEP;
+
+        // Volume 10, decreasing every 3 envelope clocks
+        apu.write(0xFF12, 0xA3);
+        apu.write(0xFF14, 0x80);
+        assert_eq!(apu.channel_volume(1), 10);
+
+        apu.tick(3 * envelope_clock - 1);
+        assert_eq!(apu.channel_volume(1), 10);
+        apu.tick(1);
+        assert_eq!(apu.channel_volume(1), 9);
+
+        // Bottoms out at 0 and stays there
+        apu.tick(100 * envelope_clock);
+        assert_eq!(apu.channel_volume(1), 0);
+
+        // Volume 13, increasing every clock: stops at 15
+        apu.write(0xFF21, 0xD9);
+        apu.write(0xFF23, 0x80);
+        let progression: Vec<u8> = (0..4)
+            .map(|_| {
+                apu.tick(envelope_clock);
+                apu.channel_volume(4)
+            })
+            .collect();
+        assert_eq!(progression, vec![14, 15, 15, 15]);
+    }
+
+    #[test]
+    fn test_envelope_zombie_mode() {
+        let mut apu = Apu::new();
+
+        // Period 0, envelope running: +1
+        apu.write(0xFF17, 0x80);
+        apu.write(0xFF19, 0x80);
+        apu.write(0xFF17, 0x80);
+        assert_eq!(apu.channel_volume(2), 9);
+
+        // Direction change: +1 (old period 0), then 16 - volume
+        apu.write(0xFF17, 0x88);
+        assert_eq!(apu.channel_volume(2), 6);
+
+        // Decreasing with a non-zero period: +2
+        apu.write(0xFF17, 0x81);
+        apu.write(0xFF19, 0x80);
+        apu.write(0xFF17, 0x81);
+        assert_eq!(apu.channel_volume(2), 10);
+
+        // Only the low 4 bits are kept
+        apu.write(0xFF17, 0xF1);
+        apu.write(0xFF19, 0x80);
+        apu.write(0xFF17, 0xF1);
+        assert_eq!(apu.channel_volume(2), 1);
+        apu.write(0xFF17, 0xF1);
+        apu.write(0xFF19, 0x80);
+        apu.write(0xFF17, 0xF9);
+        assert_eq!(apu.channel_volume(2), 15);
+
+        // No effect while the channel is off
+        apu.write(0xFF26, 0x00);
+        apu.write(0xFF26, 0x80);
+        apu.write(0xFF17, 0x81);
+        assert_eq!(apu.channel_volume(2), 0);
+    }
+
+    #[test]
+    fn test_trigger_restarts_length_expired_channel() {
+        let mut apu = Apu::new();
+
+        // Channel 2: volume 12, length 1, frequency 0x700 (period 1024)
+        apu.write(0xFF17, 0xC0);
+        apu.write(0xFF16, 63);
+        apu.write(0xFF18, 0x00);
+        apu.write(0xFF19, 0xC7);
+        assert!(apu.channel_enabled(2));
+        assert_eq!(apu.frequency_timer(2), 1024);
+
+        apu.tick(CYCLES_PER_FRAME_SEQUENCER_STEP);
+        assert!(!apu.channel_enabled(2));
+
+        // Zombie-mode writes while off are plain register writes
+        apu.write(0xFF17, 0x70);
+        apu.tick(300);
+
+        // Retrigger: on again, length reloaded to 64, timer and envelope reset
+        apu.write(0xFF19, 0xC7);
+        assert!(apu.channel_enabled(2));
+        assert_eq!(apu.channel_volume(2), 7);
+        assert_eq!(apu.frequency_timer(2), 1024);
+        apu.tick(300);
+        assert_eq!(apu.frequency_timer(2), 724);
+
+        // Length 64: still on after 63 length clocks, off after the 64th
+        apu.tick(CYCLES_PER_FRAME_SEQUENCER_STEP * 2 * 63 - 300);
+        assert!(apu.channel_enabled(2));
+        apu.tick(CYCLES_PER_FRAME_SEQUENCER_STEP * 2);
+        assert!(!apu.channel_enabled(2));
+    }
+}
@@ -0,0 +1,154 @@
+// BESS (Best Effort Save State)
+//
+// A save state layout shared by several emulators (SameBoy, BGB, ...) so
+// states can be moved between them. The file is:
+//
+//   [raw memory regions][blocks...][footer]
+//
+// Memory regions (WRAM, VRAM, cartridge RAM, OAM, HRAM, CGB palettes) are
+// stored as plain bytes; the CORE block points at them by size and offset.
+//
+// Each block starts with a 4-character ASCII ID and a u32 LE length,
+// followed by `length` bytes of content. All multi-byte values are LE.
+//   NAME: emulator name and version (optional, first if present)
+//   INFO: cartridge title (0x0134-0x0143) and global checksum (0x014E-0x014F)
+//   CORE: CPU registers, IME, IE, run state, I/O registers 0xFF00-0xFF7F
+//         and the (size, offset) pairs of the memory regions (0xD0 bytes)
+//   XRNG: this emulator's RNG state (u64 LE); other readers skip it as an
+//         unknown block
+//   END : terminates the block list (length 0)
+//
+// Footer: u32 LE offset of the first block, then the magic "BESS".
+//
+// Only this core subset is written; MBC and RTC blocks are not.
+
+/// Footer magic at the very end of a BESS file
+pub const BESS_MAGIC: &[u8; 4] = b"BESS";
+
+/// ID of the emulator-specific block holding the RNG state
+pub const RNG_BLOCK_ID: &[u8; 4] = b"XRNG";
+
+/// BESS version written in the CORE block
+const VERSION_MAJOR: u16 = 1;
+const VERSION_MINOR: u16 = 1;
+
+/// CPU run state as recorded in the CORE block
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExecutionState {
+    Running = 0,
+    Halted = 1,
+    Stopped = 2,
+}
+
+/// Snapshot of the state stored in the CORE block
+pub struct CoreState<'a> {
+    /// Model string, e.g. "GDB " (DMG-B) or "CCE " (CGB-E)
+    pub model: [u8; 4],
+    pub pc: u16,
+    pub af: u16,
+    pub bc: u16,
+    pub de: u16,
+    pub hl: u16,
+    pub sp: u16,
+    pub ime: bool,
+    pub ie: u8,
+    pub execution_state: ExecutionState,
+    /// I/O registers 0xFF00-0xFF7F
+    pub io: [u8; 0x80],
+    /// Memory regions in CORE order: WRAM, VRAM, cartridge RAM, OAM, HRAM,
+    /// BG palettes, OBJ palettes (empty slices for regions that don't exist)
+    pub regions: [&'a [u8]; 7],
+}
+
+/// Builds a BESS file
+pub struct BessWriter {
+    data: Vec<u8>,
+    first_block: Option<u32>,
+}
+
+impl BessWriter {
+    pub fn new() -> Self {
+        Self {
+            data: Vec::new(),
+            first_block: None,
+        }
+    }
+
+    /// Append a block with the given 4-character ID
+    fn block(&mut self, id: &[u8; 4], contents: &[u8]) {
+        if self.first_block.is_none() {
+            self.first_block = Some(self.data.len() as u32);
+        }
+        self.data.extend_from_slice(id);
+        self.data.extend_from_slice(&(contents.len() as u32).to_le_bytes());
+        self.data.extend_from_slice(contents);
+    }
+
+    /// Write the memory regions, then the NAME, INFO, CORE, XRNG and END blocks
+    pub fn write(mut self, name: &str, info: &[u8; 0x12], core: &CoreState, rng_state: u64) -> Vec<u8> {
+        // Raw memory regions first, remembering where each one landed
+        let mut descriptors = Vec::with_capacity(core.regions.len() * 8);
+        for region in core.regions {
+            let offset = if region.is_empty() { 0 } else { self.data.len() as u32 };
+            descriptors.extend_from_slice(&(region.len() as u32).to_le_bytes());
+            descriptors.extend_from_slice(&offset.to_le_bytes());
+            self.data.extend_from_slice(region);
+        }
+
+        self.block(b"NAME", name.as_bytes());
+        self.block(b"INFO", info);
+
+        let mut contents = Vec::with_capacity(0xD0);
+        contents.extend_from_slice(&VERSION_MAJOR.to_le_bytes());
+        contents.extend_from_slice(&VERSION_MINOR.to_le_bytes());
+        contents.extend_from_slice(&core.model);
+        for register in [core.pc, core.af, core.bc, core.de, core.hl, core.sp] {
+            contents.extend_from_slice(&register.to_le_bytes());
+        }
+        contents.push(core.ime as u8);
+        contents.push(core.ie);
+        contents.push(core.execution_state as u8);
+        contents.push(0); // Reserved
+        contents.extend_from_slice(&core.io);
+        contents.extend_from_slice(&descriptors);
+        self.block(b"CORE", &contents);
+        self.block(RNG_BLOCK_ID, &rng_state.to_le_bytes());
+
+        self.block(b"END ", &[]);
+
+        let first_block = self.first_block.unwrap_or(0);
+        self.data.extend_from_slice(&first_block.to_le_bytes());
+        self.data.extend_from_slice(BESS_MAGIC);
+        self.data
+    }
+}
+
+/// Find a block's contents in a BESS file by walking the block list
+/// Returns None if the file is malformed or has no such block.
+pub fn find_block<'a>(data: &'a [u8], id: &[u8; 4]) -> Option<&'a [u8]> {
+    let u32_at = |at: usize| -> Option<usize> {
+        Some(u32::from_le_bytes(data.get(at..at + 4)?.try_into().ok()?) as usize)
+    };
+    if data.len() < 8 || &data[data.len() - 4..] != BESS_MAGIC {
+        return None;
+    }
+    let mut at = u32_at(data.len() - 8)?;
+    loop {
+        let block_id = data.get(at..at + 4)?;
+        let len = u32_at(at + 4)?;
+        let contents = data.get(at + 8..(at + 8).checked_add(len)?)?;
+        if block_id == id {
+            return Some(contents);
+        }
+        if block_id == b"END " {
+            return None;
+        }
+        at += 8 + len;
+    }
+}
+
+impl Default for BessWriter {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -8,53 +8,159 @@
 // 0x4000-0x7FFF: ROM Bank N (16KB) - Switchable cartridge ROM
 // 0x8000-0x9FFF: VRAM (8KB) - Video RAM for tiles and maps
 // 0xA000-0xBFFF: External RAM (8KB) - Cartridge RAM (battery-backed for saves)
-// 0xC000-0xDFFF: WRAM (8KB) - Work RAM
+// 0xC000-0xDFFF: WRAM (8KB) - Work RAM (CGB: 0xD000-0xDFFF is banked, SVBK)
 // 0xE000-0xFDFF: Echo RAM - Mirror of C000-DDFF (not recommended to use)
 // 0xFE00-0xFE9F: OAM (160B) - Object Attribute Memory (sprite data)
 // 0xFEA0-0xFEFF: Unusable - Returns 0xFF on read
 // 0xFF00-0xFF7F: I/O Registers - Hardware control registers
 // 0xFF80-0xFFFE: HRAM (127B) - High RAM (fast access)
 // 0xFFFF: IE Register - Interrupt Enable register
+//
+// During OAM DMA the DMA unit owns the external bus: the CPU can only reach
+// I/O, HRAM and IE (0xFF00-0xFFFF). Other reads return 0xFF and writes are
+// ignored, which is why games run their DMA wait loop from HRAM. Only the
+// CPU's accesses (`cpu_read` / `cpu_write`) are restricted; `read`, `write`
+// and `peek` see the whole map for debuggers, tools and HDMA.
 
+use crate::apu::Apu;
 use crate::joypad::Joypad;
 use crate::mbc::{self, Mbc};
+use crate::model::Model;
 use crate::ppu::Ppu;
+use crate::rng::Rng;
+use crate::serial::{Serial, SerialControl};
 use crate::timer::Timer;
 
+/// Region of the memory map an address belongs to (for memory viewers)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MemoryRegion {
+    /// 0x0000-0x3FFF
+    RomBank0,
+    /// 0x4000-0x7FFF
+    RomBankN,
+    /// 0x8000-0x9FFF
+    Vram,
+    /// 0xA000-0xBFFF
+    ExtRam,
+    /// 0xC000-0xDFFF
+    Wram,
+    /// 0xE000-0xFDFF
+    EchoRam,
+    /// 0xFE00-0xFE9F
+    Oam,
+    /// 0xFEA0-0xFEFF
+    Unusable,
+    /// 0xFF00-0xFF7F
+    Io,
+    /// 0xFF80-0xFFFE
+    Hram,
+    /// 0xFFFF
+    Ie,
+}
+
+impl MemoryRegion {
+    /// Short label for display
+    pub fn name(&self) -> &'static str {
+        match self {
+            MemoryRegion::RomBank0 => "ROM0",
+            MemoryRegion::RomBankN => "ROMX",
+            MemoryRegion::Vram => "VRAM",
+            MemoryRegion::ExtRam => "SRAM",
+            MemoryRegion::Wram => "WRAM",
+            MemoryRegion::EchoRam => "ECHO",
+            MemoryRegion::Oam => "OAM",
+            MemoryRegion::Unusable => "----",
+            MemoryRegion::Io => "I/O",
+            MemoryRegion::Hram => "HRAM",
+            MemoryRegion::Ie => "IE",
+        }
+    }
+}
+
 /// Memory Bus - handles all memory read/write operations
 pub struct Bus {
     /// Memory Bank Controller (handles ROM and cartridge RAM)
     mbc: Box<dyn Mbc>,
-    /// Work RAM (8KB)
-    wram: [u8; 0x2000],
+    /// Work RAM (8 banks of 4KB; DMG only uses banks 0 and 1)
+    wram: [u8; 0x8000],
+    /// SVBK - WRAM bank at 0xD000-0xDFFF as written (0 selects bank 1)
+    svbk: u8,
     /// High RAM (127 bytes)
     hram: [u8; 0x7F],
     /// I/O Registers (128 bytes, 0xFF00-0xFF7F)
     io: [u8; 0x80],
     /// Interrupt Enable register (0xFFFF)
     ie: u8,
-    /// Serial output buffer (for test ROMs)
-    pub serial_output: Vec<u8>,
+    /// Serial port (link cable)
+    pub serial: Serial,
     /// Timer
     pub timer: Timer,
     /// PPU (Pixel Processing Unit)
     pub ppu: Ppu,
     /// Joypad input
     pub joypad: Joypad,
+    /// APU (sound registers and wave RAM)
+    pub apu: Apu,
+    /// Hardware model (gates the CGB-only registers)
+    model: Model,
+    /// CGB double-speed mode (CPU runs at 2x, PPU stays at normal rate)
+    double_speed: bool,
+    /// Odd CPU cycle left over in double-speed mode (half a dot), carried to the next tick
+    half_dot: u32,
+    /// KEY1 bit 0 - speed switch armed (performed by the next STOP)
+    speed_switch_armed: bool,
+    /// HDMA1-4 - VRAM DMA source and destination
+    hdma_source: u16,
+    hdma_dest: u16,
+    /// OAM DMA (0xFF46): last written value, source base and bytes copied so far
+    dma_register: u8,
+    dma_source: u16,
+    dma_progress: Option<u8>,
+    /// Cycles accumulated toward the next OAM DMA byte
+    dma_cycles: u32,
+    /// Restrict CPU accesses to 0xFF00-0xFFFF during OAM DMA
+    dma_block: bool,
+    /// Address watched for writes, and the last value written to it
+    write_watch: Option<u16>,
+    watch_hit: Option<u8>,
+    /// IF bits requested by hardware since the last take_raised_interrupts
+    raised_interrupts: u8,
+    /// Called with each byte the program sends over serial (byte, cycles)
+    serial_byte_callback: Option<Box<dyn FnMut(u8, u64)>>,
+    /// Cycle count reported to the serial byte callback (kept by the emulator)
+    cycles: u64,
 }
 
 impl Bus {
     pub fn new() -> Self {
         Self {
             mbc: Box::new(mbc::NoMbc::new(vec![0; 0x8000])),
-            wram: [0; 0x2000],
+            wram: [0; 0x8000],
+            svbk: 0,
             hram: [0; 0x7F],
             io: [0; 0x80],
             ie: 0,
-            serial_output: Vec::new(),
+            serial: Serial::new(),
             timer: Timer::new(),
             ppu: Ppu::new(),
             joypad: Joypad::new(),
+            apu: Apu::new(),
+            model: Model::Dmg,
+            double_speed: false,
+            half_dot: 0,
+            speed_switch_armed: false,
+            hdma_source: 0,
+            hdma_dest: 0,
+            dma_register: 0xFF,
+            dma_source: 0,
+            dma_progress: None,
+            dma_cycles: 0,
+            dma_block: true,
+            write_watch: None,
+            watch_hit: None,
+            raised_interrupts: 0,
+            serial_byte_callback: None,
+            cycles: 0,
         }
     }
 
@@ -62,50 +168,271 @@ impl Bus {
     pub fn with_cartridge(cartridge_type: u8, rom: Vec<u8>, ram_size: usize) -> Self {
         Self {
             mbc: mbc::create_mbc(cartridge_type, rom, ram_size),
-            wram: [0; 0x2000],
+            wram: [0; 0x8000],
+            svbk: 0,
             hram: [0; 0x7F],
             io: [0; 0x80],
             ie: 0,
-            serial_output: Vec::new(),
+            serial: Serial::new(),
             timer: Timer::new(),
             ppu: Ppu::new(),
             joypad: Joypad::new(),
+            apu: Apu::new(),
+            model: Model::Dmg,
+            double_speed: false,
+            half_dot: 0,
+            speed_switch_armed: false,
+            hdma_source: 0,
+            hdma_dest: 0,
+            dma_register: 0xFF,
+            dma_source: 0,
+            dma_progress: None,
+            dma_cycles: 0,
+            dma_block: true,
+            write_watch: None,
+            watch_hit: None,
+            raised_interrupts: 0,
+            serial_byte_callback: None,
+            cycles: 0,
         }
     }
 
+    /// Keep at most `bytes` bytes of serial output, dropping the oldest
+    /// (the buffer is unbounded by default)
+    pub fn set_serial_buffer_cap(&mut self, bytes: usize) {
+        self.serial.set_output_cap(bytes);
+    }
+
+    /// Make the serial output buffer unbounded again
+    pub fn clear_serial_buffer_cap(&mut self) {
+        self.serial.clear_output_cap();
+    }
+
+    /// Also pass every byte sent over serial to `callback`, with the cycle
+    /// count at the time (as set by `set_cycles`); the buffer is still filled
+    pub fn set_serial_byte_callback(&mut self, callback: Box<dyn FnMut(u8, u64)>) {
+        self.serial_byte_callback = Some(callback);
+    }
+
+    /// Remove the serial byte callback
+    pub fn clear_serial_byte_callback(&mut self) {
+        self.serial_byte_callback = None;
+    }
+
+    /// Set the cycle count used to timestamp serial bytes
+    pub fn set_cycles(&mut self, cycles: u64) {
+        self.cycles = cycles;
+    }
+
     /// Get serial output as string
     pub fn get_serial_output(&self) -> String {
-        String::from_utf8_lossy(&self.serial_output).to_string()
+        String::from_utf8_lossy(&self.serial.output).to_string()
+    }
+
+    /// Get the cartridge's memory bank controller
+    pub fn mbc(&self) -> &dyn Mbc {
+        self.mbc.as_ref()
+    }
+
+    /// Get the cartridge's memory bank controller mutably
+    pub fn mbc_mut(&mut self) -> &mut dyn Mbc {
+        self.mbc.as_mut()
+    }
+
+    /// ROM bank currently mapped at 0x4000-0x7FFF
+    pub fn current_rom_bank(&self) -> usize {
+        self.mbc.current_rom_bank()
+    }
+
+    /// External RAM bank currently mapped at 0xA000-0xBFFF
+    pub fn current_ram_bank(&self) -> usize {
+        self.mbc.current_ram_bank()
+    }
+
+    /// Get the current serial clock configuration (SC)
+    /// The fast clock bit only exists on CGB: `fast_clock` is always false on DMG
+    pub fn serial_control(&self) -> SerialControl {
+        let mut control = self.serial.control();
+        control.fast_clock &= self.model.is_cgb();
+        control
+    }
+
+    /// Drive one external serial clock pulse (link partner acting as master)
+    /// Returns the received byte when the 8th bit completes the transfer,
+    /// which also requests the Serial interrupt
+    pub fn serial_clock_pulse(&mut self, incoming_bit: u8) -> Option<u8> {
+        let received = self.serial.external_clock_pulse(incoming_bit);
+        if self.serial.take_interrupt() {
+            self.raise_interrupts(0x08);
+        }
+        received
+    }
+
+    /// Get the hardware model
+    pub fn model(&self) -> Model {
+        self.model
+    }
+
+    /// Fill WRAM and HRAM with a random power-on pattern
+    pub fn randomize_ram(&mut self, rng: &mut Rng) {
+        rng.fill(&mut self.wram);
+        rng.fill(&mut self.hram);
+    }
+
+    /// Set the hardware model (DMG by default)
+    /// Call before running: the timer is reset to the model's post-boot phase
+    pub fn set_model(&mut self, model: Model) {
+        self.model = model;
+        self.timer = Timer::with_model(model, false);
+        self.ppu.set_cgb_mode(model.is_cgb());
+    }
+
+    /// Enable or disable VRAM access blocking during PPU mode 3 (default: on)
+    /// Turning it off is inaccurate, but some test ROMs assume open VRAM
+    pub fn set_vram_block(&mut self, enabled: bool) {
+        self.ppu.set_vram_block(enabled);
+    }
+
+    /// Enable or disable restricting CPU accesses to I/O and HRAM during
+    /// OAM DMA (default: on)
+    pub fn set_dma_block(&mut self, enabled: bool) {
+        self.dma_block = enabled;
+    }
+
+    /// Check if an access to `addr` is cut off by a running OAM DMA
+    fn dma_blocks(&self, addr: u16) -> bool {
+        self.dma_block && self.dma_progress.is_some() && addr < 0xFF00
+    }
+
+    /// Enable or disable OAM access blocking during PPU modes 2 and 3 (default: on)
+    pub fn set_oam_block(&mut self, enabled: bool) {
+        self.ppu.set_oam_block(enabled);
+    }
+
+    /// Raw VRAM (0x8000-0x9FFF) of the bank selected by VBK, for external renderers
+    /// Unlike read(), this ignores PPU mode access blocking.
+    pub fn vram_slice(&self) -> &[u8] {
+        self.vram_bank_slice(self.ppu.selected_vram_bank())
+    }
+
+    /// Raw VRAM of a specific bank (0 or 1; bank 1 only exists on CGB)
+    pub fn vram_bank_slice(&self, bank: u8) -> &[u8] {
+        if bank == 1 && self.model.is_cgb() {
+            &self.ppu.vram1
+        } else {
+            &self.ppu.vram
+        }
+    }
+
+    /// Raw OAM (0xFE00-0xFE9F), for external renderers
+    pub fn oam_slice(&self) -> &[u8] {
+        &self.ppu.oam
+    }
+
+    /// Raw WRAM, all banks present on this model (8KB on DMG, 32KB on CGB)
+    pub fn wram_slice(&self) -> &[u8] {
+        if self.model.is_cgb() {
+            &self.wram
+        } else {
+            &self.wram[..0x2000]
+        }
+    }
+
+    /// Raw HRAM (0xFF80-0xFFFE)
+    pub fn hram_slice(&self) -> &[u8] {
+        &self.hram
+    }
+
+    /// Check if the CPU is running in (CGB) double-speed mode
+    pub fn double_speed(&self) -> bool {
+        self.double_speed
+    }
+
+    /// Enable or disable (CGB) double-speed mode
+    pub fn set_double_speed(&mut self, enabled: bool) {
+        self.double_speed = enabled;
+        self.half_dot = 0;
+    }
+
+    /// Called by STOP: switch speeds if KEY1 armed a switch (CGB only)
+    /// Returns true if the speed changed
+    pub fn perform_speed_switch(&mut self) -> bool {
+        if !self.model.is_cgb() || !self.speed_switch_armed {
+            return false;
+        }
+        self.speed_switch_armed = false;
+        self.double_speed = !self.double_speed;
+        self.half_dot = 0;
+        true
     }
 
     /// Update timer, PPU, and check for interrupts
+    ///
+    /// `cycles` are CPU T-cycles. Components are clocked from two domains:
+    ///   - CPU domain (timer, serial): advance by `cycles`
+    ///   - Video domain (PPU): fixed 4.19 MHz dot clock, so in double-speed
+    ///     mode it only advances by half the CPU cycles (an odd cycle is
+    ///     carried over, so no dots are lost)
     pub fn tick(&mut self, cycles: u32) {
+        let dots = if self.double_speed {
+            let cycles = cycles + self.half_dot;
+            self.half_dot = cycles % 2;
+            cycles / 2
+        } else {
+            cycles
+        };
+
         self.timer.tick(cycles);
-        self.ppu.tick(cycles);
+        self.serial.tick(cycles);
+        self.tick_dma(cycles);
+        self.ppu.tick(dots);
+        self.apu.tick(dots);
+
+        let mut raised = 0;
 
         // Check for timer interrupt
         if self.timer.take_interrupt() {
             // Set Timer interrupt flag (bit 2 of IF)
-            self.io[0x0F] |= 0x04;
+            raised |= 0x04;
         }
 
         // Check for VBlank interrupt
         if self.ppu.vblank_interrupt {
             // Set VBlank interrupt flag (bit 0 of IF)
-            self.io[0x0F] |= 0x01;
+            raised |= 0x01;
         }
 
         // Check for STAT interrupt
         if self.ppu.stat_interrupt {
             // Set LCD STAT interrupt flag (bit 1 of IF)
-            self.io[0x0F] |= 0x02;
+            raised |= 0x02;
+        }
+
+        // Check for Serial interrupt
+        if self.serial.take_interrupt() {
+            // Set Serial interrupt flag (bit 3 of IF)
+            raised |= 0x08;
         }
 
         // Check for Joypad interrupt
         if self.joypad.take_interrupt() {
             // Set Joypad interrupt flag (bit 4 of IF)
-            self.io[0x0F] |= 0x10;
+            raised |= 0x10;
         }
+
+        self.raise_interrupts(raised);
+    }
+
+    /// Set hardware-requested bits in IF, recording them for take_raised_interrupts
+    fn raise_interrupts(&mut self, raised: u8) {
+        self.io[0x0F] |= raised;
+        self.raised_interrupts |= raised;
+    }
+
+    /// Take the IF bits hardware requested since the last call
+    /// (CPU writes to IF are not included)
+    pub fn take_raised_interrupts(&mut self) -> u8 {
+        std::mem::take(&mut self.raised_interrupts)
     }
 
     /// Load ROM data into memory (for simple ROM-only cartridges)
@@ -113,6 +440,45 @@ impl Bus {
         self.mbc = Box::new(mbc::NoMbc::new(data.to_vec()));
     }
 
+    /// Classify an address by memory map region
+    pub fn region_of(addr: u16) -> MemoryRegion {
+        match addr {
+            0x0000..=0x3FFF => MemoryRegion::RomBank0,
+            0x4000..=0x7FFF => MemoryRegion::RomBankN,
+            0x8000..=0x9FFF => MemoryRegion::Vram,
+            0xA000..=0xBFFF => MemoryRegion::ExtRam,
+            0xC000..=0xDFFF => MemoryRegion::Wram,
+            0xE000..=0xFDFF => MemoryRegion::EchoRam,
+            0xFE00..=0xFE9F => MemoryRegion::Oam,
+            0xFEA0..=0xFEFF => MemoryRegion::Unusable,
+            0xFF00..=0xFF7F => MemoryRegion::Io,
+            0xFF80..=0xFFFE => MemoryRegion::Hram,
+            0xFFFF => MemoryRegion::Ie,
+        }
+    }
+
+    /// Read a byte as the CPU sees it (0xFF outside HRAM and I/O during OAM DMA)
+    pub fn cpu_read(&self, addr: u16) -> u8 {
+        if self.dma_blocks(addr) {
+            return 0xFF;
+        }
+        self.read(addr)
+    }
+
+    /// Write a byte as the CPU does (ignored outside HRAM and I/O during OAM DMA)
+    pub fn cpu_write(&mut self, addr: u16, value: u8) {
+        if self.dma_blocks(addr) {
+            return;
+        }
+        self.write(addr, value);
+    }
+
+    /// Read a byte without side effects, ignoring OAM DMA
+    /// For debuggers and disassemblers: never changes emulator state.
+    pub fn peek(&self, addr: u16) -> u8 {
+        self.read(addr)
+    }
+
     /// Read a byte from the given address
     pub fn read(&self, addr: u16) -> u8 {
         match addr {
@@ -126,10 +492,10 @@ impl Bus {
             0xA000..=0xBFFF => self.mbc.read(addr),
 
             // Work RAM
-            0xC000..=0xDFFF => self.wram[(addr - 0xC000) as usize],
+            0xC000..=0xDFFF => self.wram[self.wram_index(addr)],
 
             // Echo RAM (mirror of C000-DDFF)
-            0xE000..=0xFDFF => self.wram[(addr - 0xE000) as usize],
+            0xE000..=0xFDFF => self.wram[self.wram_index(addr - 0x2000)],
 
             // OAM (Object Attribute Memory, through PPU)
             0xFE00..=0xFE9F => self.ppu.read_oam(addr - 0xFE00),
@@ -148,8 +514,23 @@ impl Bus {
         }
     }
 
+    /// Watch an address for writes (None to stop watching)
+    pub fn set_write_watch(&mut self, addr: Option<u16>) {
+        self.write_watch = addr;
+        self.watch_hit = None;
+    }
+
+    /// Take the value last written to the watched address, if any
+    pub fn take_watch_hit(&mut self) -> Option<u8> {
+        self.watch_hit.take()
+    }
+
     /// Write a byte to the given address
     pub fn write(&mut self, addr: u16, value: u8) {
+        if self.write_watch == Some(addr) {
+            self.watch_hit = Some(value);
+        }
+
         match addr {
             // ROM area (MBC register writes)
             0x0000..=0x7FFF => self.mbc.write(addr, value),
@@ -161,10 +542,16 @@ impl Bus {
             0xA000..=0xBFFF => self.mbc.write(addr, value),
 
             // Work RAM
-            0xC000..=0xDFFF => self.wram[(addr - 0xC000) as usize] = value,
+            0xC000..=0xDFFF => {
+                let index = self.wram_index(addr);
+                self.wram[index] = value;
+            }
 
             // Echo RAM (writes also go to WRAM)
-            0xE000..=0xFDFF => self.wram[(addr - 0xE000) as usize] = value,
+            0xE000..=0xFDFF => {
+                let index = self.wram_index(addr - 0x2000);
+                self.wram[index] = value;
+            }
 
             // OAM (through PPU)
             0xFE00..=0xFE9F => self.ppu.write_oam(addr - 0xFE00, value),
@@ -183,15 +570,31 @@ impl Bus {
         }
     }
 
+    /// Map a 0xC000-0xDFFF address to an index into the banked WRAM
+    fn wram_index(&self, addr: u16) -> usize {
+        let offset = (addr & 0x0FFF) as usize;
+        if addr < 0xD000 {
+            offset
+        } else {
+            // Bank 0 can't be selected for the upper half; it maps to bank 1
+            let bank = if self.model.is_cgb() { (self.svbk & 0x07).max(1) } else { 1 };
+            bank as usize * 0x1000 + offset
+        }
+    }
+
     /// Read from I/O registers
     fn read_io(&self, addr: u16) -> u8 {
         let offset = (addr - 0xFF00) as usize;
         match addr {
+            // Unmapped registers read as open bus
+            _ if self.is_unmapped_io(addr) => 0xFF,
+
             // Joypad
             0xFF00 => self.joypad.read(),
 
-            // Serial transfer - stub
-            0xFF01..=0xFF02 => self.io[offset],
+            // Serial transfer
+            0xFF01 => self.serial.read_sb(),
+            0xFF02 => self.serial.read_sc(self.model.is_cgb()),
 
             // Timer registers
             0xFF04 => self.timer.div(),           // DIV
@@ -202,33 +605,92 @@ impl Bus {
             // Interrupt Flag (IF)
             0xFF0F => self.io[offset] | 0xE0,     // Upper bits always 1
 
-            // Sound registers - stub for now
-            0xFF10..=0xFF3F => self.io[offset],
+            // Sound registers and wave RAM
+            0xFF10..=0xFF3F => self.apu.read(addr),
+
+            // DMA register reads back the last written value
+            0xFF46 => self.dma_register,
 
             // PPU registers
             0xFF40..=0xFF4B => self.ppu.read_register(addr),
 
+            // CGB registers (only reachable in CGB mode)
+            0xFF4D => 0x7E | (self.double_speed as u8) << 7 | self.speed_switch_armed as u8,
+            0xFF4F | 0xFF68..=0xFF6B => self.ppu.read_cgb_register(addr),
+            0xFF51..=0xFF55 => 0xFF,              // HDMA (HDMA5 = no transfer active)
+            0xFF56 => self.io[offset] | 0x3E,     // RP (bit 1 set = no IR light received)
+            0xFF6C => self.io[offset] | 0xFE,     // OPRI
+            0xFF70 => 0xF8 | self.svbk,
+            0xFF75 => self.io[offset] | 0x8F,
+
             // Other I/O
             _ => self.io[offset],
         }
     }
 
+    /// Check if an I/O address has no register behind it on the current model
+    /// (reads return 0xFF, writes are ignored)
+    fn is_unmapped_io(&self, addr: u16) -> bool {
+        if self.model.is_cgb() && Self::is_cgb_io(addr) {
+            return false;
+        }
+        matches!(
+            addr,
+            0xFF03
+                | 0xFF08..=0xFF0E
+                | 0xFF15
+                | 0xFF1F
+                | 0xFF27..=0xFF2F
+                | 0xFF4C..=0xFF7F
+        )
+    }
+
+    /// Check if an I/O address is a CGB-only register
+    fn is_cgb_io(addr: u16) -> bool {
+        matches!(
+            addr,
+            0xFF4D | 0xFF4F | 0xFF51..=0xFF56 | 0xFF68..=0xFF6C | 0xFF70 | 0xFF72..=0xFF77
+        )
+    }
+
+    /// Raw I/O register value for state exports
+    /// Write-only and partly readable registers give the value last written
+    /// instead of what a CPU read returns (open-bus bits set to 1).
+    pub fn read_io_raw(&self, addr: u16) -> u8 {
+        match addr {
+            0xFF07 => self.timer.tac,
+            0xFF0F => self.io[0x0F],
+            0xFF10..=0xFF3F => self.apu.raw_register(addr),
+            0xFF51 => (self.hdma_source >> 8) as u8,
+            0xFF52 => self.hdma_source as u8,
+            0xFF53 => (self.hdma_dest >> 8) as u8,
+            0xFF54 => self.hdma_dest as u8,
+            _ => self.read(addr),
+        }
+    }
+
     /// Write to I/O registers
     fn write_io(&mut self, addr: u16, value: u8) {
         let offset = (addr - 0xFF00) as usize;
         match addr {
+            // Unmapped registers - writes ignored
+            _ if self.is_unmapped_io(addr) => {}
+
             // Joypad
             0xFF00 => self.joypad.write(value),
 
-            // Serial Control (SC) - 0xFF02
-            // When bit 7 is set (0x81), a transfer is initiated
-            // For test ROMs, we capture the data byte (SB at 0xFF01)
+            // Serial transfer
+            // Writing SC with bits 7 and 0 set starts a transfer; for test ROMs the
+            // outgoing byte is captured into the serial output buffer
+            0xFF01 => self.serial.write_sb(value),
             0xFF02 => {
-                self.io[offset] = value;
-                if value == 0x81 {
-                    // Transfer requested - capture the byte from SB
-                    let sb = self.io[0x01]; // 0xFF01 - SB register
-                    self.serial_output.push(sb);
+                // The byte in SB is sent (and captured) when a transfer starts
+                let (byte, sent) = (self.serial.read_sb(), self.serial.output_total());
+                self.serial.write_sc(value);
+                if self.serial.output_total() != sent {
+                    if let Some(callback) = self.serial_byte_callback.as_mut() {
+                        callback(byte, self.cycles);
+                    }
                 }
             }
 
@@ -241,25 +703,93 @@ impl Bus {
             // Interrupt Flag (IF)
             0xFF0F => self.io[offset] = value & 0x1F,  // Only lower 5 bits
 
+            // Sound registers and wave RAM
+            0xFF10..=0xFF3F => self.apu.write(addr, value),
+
             // DMA Transfer (0xFF46) - must be before PPU registers
-            0xFF46 => self.dma_transfer(value),
+            0xFF46 => self.start_dma(value),
 
             // PPU registers
             0xFF40..=0xFF4B => self.ppu.write_register(addr, value),
 
+            // CGB registers (only reachable in CGB mode)
+            0xFF4D => self.speed_switch_armed = value & 0x01 != 0,
+            0xFF4F | 0xFF68..=0xFF6B => self.ppu.write_cgb_register(addr, value),
+            0xFF51 => self.hdma_source = (self.hdma_source & 0x00FF) | (value as u16) << 8,
+            0xFF52 => self.hdma_source = (self.hdma_source & 0xFF00) | (value & 0xF0) as u16,
+            0xFF53 => self.hdma_dest = (self.hdma_dest & 0x00FF) | ((value & 0x1F) as u16) << 8,
+            0xFF54 => self.hdma_dest = (self.hdma_dest & 0xFF00) | (value & 0xF0) as u16,
+            0xFF55 => self.hdma_transfer(value),
+            0xFF56 => self.io[offset] = value & 0xC1,
+            0xFF6C => self.io[offset] = value & 0x01,
+            0xFF70 => self.svbk = value & 0x07,
+            0xFF75 => self.io[offset] = value & 0x70,
+            0xFF76..=0xFF77 => {}                  // PCM12/PCM34 are read-only
+
             // Normal I/O write
             _ => self.io[offset] = value,
         }
     }
 
-    /// Perform OAM DMA transfer
-    /// Copies 160 bytes from source (value * 0x100) to OAM (0xFE00-0xFE9F)
-    fn dma_transfer(&mut self, value: u8) {
-        let source = (value as u16) << 8;
-        for i in 0..160 {
-            let byte = self.read(source + i);
-            self.ppu.oam[i as usize] = byte;
+    /// Start an OAM DMA transfer
+    /// Copies 160 bytes from source (value * 0x100) to OAM (0xFE00-0xFE9F),
+    /// one byte per M-cycle (640 T-cycles in total). Restarting mid-transfer
+    /// begins again from the first byte.
+    fn start_dma(&mut self, value: u8) {
+        self.dma_register = value;
+        self.dma_source = (value as u16) << 8;
+        self.dma_progress = Some(0);
+        self.dma_cycles = 0;
+    }
+
+    /// Read a DMA source byte: the DMA unit isn't locked out of VRAM by the
+    /// PPU, and sources from 0xE000 up (including 0xFE/0xFF) read the WRAM
+    /// echo of 0xC000-0xDFFF rather than OAM or I/O
+    fn dma_read(&self, addr: u16) -> u8 {
+        match addr {
+            0x8000..=0x9FFF => {
+                self.vram_bank_slice(self.ppu.selected_vram_bank())[(addr - 0x8000) as usize]
+            }
+            0xE000..=0xFFFF => self.wram[self.wram_index(addr - 0x2000)],
+            _ => self.read(addr),
+        }
+    }
+
+    /// Advance an active OAM DMA transfer
+    fn tick_dma(&mut self, cycles: u32) {
+        let Some(mut progress) = self.dma_progress else {
+            return;
+        };
+
+        self.dma_cycles += cycles;
+        while self.dma_cycles >= 4 && progress < 160 {
+            self.dma_cycles -= 4;
+            let byte = self.dma_read(self.dma_source + progress as u16);
+            self.ppu.oam[progress as usize] = byte;
+            progress += 1;
         }
+
+        self.dma_progress = if progress < 160 { Some(progress) } else { None };
+    }
+
+    /// Active OAM DMA transfer: (source base address, bytes copied so far)
+    /// None when no transfer is running
+    pub fn oam_dma_status(&self) -> Option<(u16, u8)> {
+        self.dma_progress.map(|progress| (self.dma_source, progress))
+    }
+
+    /// Perform a CGB VRAM DMA transfer (HDMA5 write)
+    /// Copies (value & 0x7F) + 1 blocks of 16 bytes from the HDMA source to VRAM.
+    /// H-Blank mode (bit 7) is approximated by copying everything at once.
+    fn hdma_transfer(&mut self, value: u8) {
+        let length = ((value & 0x7F) as u16 + 1) * 0x10;
+        for i in 0..length {
+            let byte = self.read(self.hdma_source.wrapping_add(i));
+            let dest = (self.hdma_dest.wrapping_add(i)) & 0x1FFF;
+            self.ppu.write_vram(dest, byte);
+        }
+        self.hdma_source = self.hdma_source.wrapping_add(length);
+        self.hdma_dest = self.hdma_dest.wrapping_add(length) & 0x1FFF;
     }
 
     /// Read a 16-bit value (little-endian)
@@ -358,6 +888,198 @@ mod tests {
         assert_eq!(bus.read(0xFEFF), 0xFF);
     }
 
+    #[test]
+    fn test_unmapped_io_reads_ff() {
+        let mut bus = Bus::new();
+
+        assert_eq!(bus.read(0xFF03), 0xFF);
+        assert_eq!(bus.read(0xFF08), 0xFF);
+        assert_eq!(bus.read(0xFF15), 0xFF);
+        assert_eq!(bus.read(0xFF4C), 0xFF);
+        assert_eq!(bus.read(0xFF7F), 0xFF);
+
+        // Writes don't stick
+        bus.write(0xFF03, 0x12);
+        assert_eq!(bus.read(0xFF03), 0xFF);
+    }
+
+    #[test]
+    fn test_cgb_registers_on_dmg() {
+        let mut bus = Bus::new();
+        assert_eq!(bus.model(), Model::Dmg);
+
+        let cgb_regs = [
+            0xFF4D, 0xFF4F, 0xFF51, 0xFF55, 0xFF56, 0xFF68, 0xFF69, 0xFF6C, 0xFF70, 0xFF72, 0xFF75,
+        ];
+        for addr in cgb_regs {
+            assert_eq!(bus.read(addr), 0xFF, "0x{:04X}", addr);
+        }
+        bus.write(0xFF72, 0x12);
+        assert_eq!(bus.read(0xFF72), 0xFF);
+
+        // Selecting VRAM bank 1 has no effect
+        bus.write(0xFF4F, 0x01);
+        assert_eq!(bus.read(0xFF4F), 0xFF);
+        bus.write(0x8000, 0x42);
+        assert_eq!(bus.ppu.vram[0], 0x42);
+        assert_eq!(bus.ppu.vram1[0], 0x00);
+
+        // Neither does selecting a WRAM bank
+        bus.write(0xD000, 0x11);
+        bus.write(0xFF70, 0x03);
+        assert_eq!(bus.read(0xD000), 0x11);
+    }
+
+    #[test]
+    fn test_cgb_registers() {
+        let mut bus = Bus::new();
+        bus.set_model(Model::Cgb);
+
+        // VRAM banking
+        bus.write(0x8000, 0x11);
+        bus.write(0xFF4F, 0x01);
+        assert_eq!(bus.read(0xFF4F), 0xFF);
+        bus.write(0x8000, 0x22);
+        assert_eq!(bus.read(0x8000), 0x22);
+        bus.write(0xFF4F, 0x00);
+        assert_eq!(bus.read(0xFF4F), 0xFE);
+        assert_eq!(bus.read(0x8000), 0x11);
+
+        // WRAM banking (bank 0 selects bank 1), echo RAM follows
+        bus.write(0xD000, 0x33);
+        bus.write(0xFF70, 0x02);
+        assert_eq!(bus.read(0xFF70), 0xFA);
+        assert_eq!(bus.read(0xD000), 0x00);
+        bus.write(0xD000, 0x44);
+        assert_eq!(bus.read(0xF000), 0x44);
+        bus.write(0xFF70, 0x00);
+        assert_eq!(bus.read(0xD000), 0x33);
+
+        // Undocumented and infrared registers keep their writable bits
+        bus.write(0xFF72, 0x12);
+        bus.write(0xFF75, 0xFF);
+        bus.write(0xFF6C, 0xFF);
+        bus.write(0xFF56, 0xFF);
+        assert_eq!(bus.read(0xFF72), 0x12);
+        assert_eq!(bus.read(0xFF75), 0xFF);
+        bus.write(0xFF75, 0x00);
+        assert_eq!(bus.read(0xFF75), 0x8F);
+        assert_eq!(bus.read(0xFF6C), 0xFF);
+        assert_eq!(bus.read(0xFF56), 0xFF);
+        bus.write(0xFF56, 0x00);
+        assert_eq!(bus.read(0xFF56), 0x3E);
+
+        // KEY1: arm a speed switch
+        assert_eq!(bus.read(0xFF4D), 0x7E);
+        bus.write(0xFF4D, 0x01);
+        assert_eq!(bus.read(0xFF4D), 0x7F);
+
+        // Palette RAM with auto-increment
+        bus.write(0xFF68, 0x80 | 0x3F);
+        bus.write(0xFF69, 0xAA);
+        bus.write(0xFF69, 0xBB);
+        assert_eq!(bus.ppu.bg_palette_ram[0x3F], 0xAA);
+        assert_eq!(bus.ppu.bg_palette_ram[0x00], 0xBB);
+        assert_eq!(bus.read(0xFF68), 0xC1);
+
+        // General-purpose VRAM DMA: 2 blocks from WRAM to 0x8100
+        for i in 0..0x20 {
+            bus.write(0xC000 + i, i as u8);
+        }
+        bus.write(0xFF51, 0xC0);
+        bus.write(0xFF52, 0x00);
+        bus.write(0xFF53, 0x01);
+        bus.write(0xFF54, 0x00);
+        bus.write(0xFF55, 0x01);
+        assert_eq!(bus.ppu.vram[0x0100], 0x00);
+        assert_eq!(bus.ppu.vram[0x011F], 0x1F);
+        assert_eq!(bus.read(0xFF55), 0xFF);
+    }
+
+    #[test]
+    fn test_serial_control() {
+        let mut bus = Bus::new();
+        bus.set_model(Model::Cgb);
+        bus.serial.set_mode(crate::serial::SerialMode::Timed);
+
+        // Internal clock, fast mode, transfer started
+        bus.write(0xFF02, 0x83);
+        assert_eq!(
+            bus.serial_control(),
+            SerialControl {
+                transfer_start: true,
+                internal_clock: true,
+                fast_clock: true,
+            }
+        );
+
+        assert_eq!(bus.read(0xFF02), 0xFF);
+        bus.write(0xFF02, 0x81);
+        assert_eq!(bus.read(0xFF02) & 0x02, 0x00); // Normal clock reads back 0 on CGB
+
+        // External clock, no transfer
+        bus.write(0xFF02, 0x00);
+        assert_eq!(
+            bus.serial_control(),
+            SerialControl {
+                transfer_start: false,
+                internal_clock: false,
+                fast_clock: false,
+            }
+        );
+
+        // No fast clock on DMG
+        bus.set_model(Model::Dmg);
+        bus.write(0xFF02, 0x03);
+        assert!(bus.serial_control().internal_clock);
+        assert!(!bus.serial_control().fast_clock);
+        bus.write(0xFF02, 0x00);
+        assert_eq!(bus.read(0xFF02), 0x7E);
+    }
+
+    #[test]
+    fn test_serial_clock_pulse() {
+        let mut bus = Bus::new();
+
+        // No transfer armed: pulses are ignored
+        assert_eq!(bus.serial_clock_pulse(1), None);
+
+        // Arm a slave transfer with the external clock
+        bus.write(0xFF01, 0x00);
+        bus.write(0xFF02, 0x80);
+
+        // Time alone doesn't move an externally clocked transfer
+        bus.tick(10_000);
+        assert_ne!(bus.read(0xFF02) & 0x80, 0);
+
+        let bits = [1, 0, 1, 0, 0, 1, 0, 1]; // 0xA5, MSB first
+        for &bit in &bits[..7] {
+            assert_eq!(bus.serial_clock_pulse(bit), None);
+        }
+        assert_eq!(bus.read(0xFF0F) & 0x08, 0);
+
+        assert_eq!(bus.serial_clock_pulse(bits[7]), Some(0xA5));
+        assert_eq!(bus.read(0xFF01), 0xA5);
+        assert_eq!(bus.read(0xFF02) & 0x80, 0);
+        assert_ne!(bus.read(0xFF0F) & 0x08, 0);
+        assert_eq!(bus.take_raised_interrupts(), 0x08);
+    }
+
+    #[test]
+    fn test_serial_loopback_sb_readback() {
+        let mut bus = Bus::new();
+        bus.serial.set_mode(crate::serial::SerialMode::Timed);
+        bus.serial.set_loopback(true);
+
+        bus.write(0xFF01, 0x5A);
+        bus.write(0xFF02, 0x81);
+        bus.tick(8 * crate::serial::CYCLES_PER_BIT);
+
+        assert_eq!(bus.read(0xFF02) & 0x80, 0);
+        assert_eq!(bus.read(0xFF01), 0x5A);
+        assert_ne!(bus.read(0xFF0F) & 0x08, 0);
+    }
+
     #[test]
     fn test_16bit_read_write() {
         let mut bus = Bus::new();
@@ -408,12 +1130,255 @@ mod tests {
             bus.write(0xC000 + i as u16, i);
         }
 
-        // Trigger DMA from 0xC000 (value 0xC0)
+        // Trigger DMA from 0xC000 (value 0xC0); it takes 160 M-cycles
         bus.write(0xFF46, 0xC0);
+        bus.tick(640);
 
         // Verify OAM contains the copied data
         for i in 0..160u8 {
             assert_eq!(bus.ppu.oam[i as usize], i);
         }
     }
+
+    #[test]
+    fn test_access_block_toggles() {
+        let mut bus = Bus::new();
+        bus.ppu.vram[0x10] = 0x42;
+        bus.ppu.oam[0x04] = 0x24;
+        bus.ppu.force_mode(crate::ppu::PpuMode::Drawing, 10, 100);
+
+        // Accurate default: both blocked during mode 3
+        assert_eq!(bus.read(0x8010), 0xFF);
+        assert_eq!(bus.read(0xFE04), 0xFF);
+
+        // Only OAM opened up
+        bus.set_oam_block(false);
+        assert_eq!(bus.read(0xFE04), 0x24);
+        assert_eq!(bus.read(0x8010), 0xFF);
+        bus.write(0xFE05, 0x99);
+        assert_eq!(bus.ppu.oam[0x05], 0x99);
+
+        // Both open
+        bus.set_vram_block(false);
+        assert_eq!(bus.read(0x8010), 0x42);
+    }
+
+    #[test]
+    fn test_stat_read_reflects_mode() {
+        let mut bus = Bus::new();
+        bus.write(0xFF45, 42); // LYC
+        bus.write(0xFF41, 0xFF); // All select bits; read-only bits must survive
+
+        bus.ppu.force_mode(crate::ppu::PpuMode::Drawing, 42, 100);
+        bus.ppu.stat.set_coincidence(bus.ppu.ly == bus.ppu.lyc);
+        assert_eq!(bus.read(0xFF41), 0x80 | 0x78 | 0x04 | 0x03);
+
+        bus.write(0xFF41, 0x28); // Mode 2 and HBlank selects only
+        bus.ppu.force_mode(crate::ppu::PpuMode::HBlank, 43, 300);
+        bus.ppu.stat.set_coincidence(bus.ppu.ly == bus.ppu.lyc);
+        assert_eq!(bus.read(0xFF41), 0x80 | 0x28);
+
+        // Ticking into the next line moves the live mode to OAM scan
+        bus.tick(456 - 300);
+        assert_eq!(bus.read(0xFF41) & 0x03, 2);
+    }
+
+    #[test]
+    fn test_region_of() {
+        assert_eq!(Bus::region_of(0x0000), MemoryRegion::RomBank0);
+        assert_eq!(Bus::region_of(0x3FFF), MemoryRegion::RomBank0);
+        assert_eq!(Bus::region_of(0x4000), MemoryRegion::RomBankN);
+        assert_eq!(Bus::region_of(0x9FFF), MemoryRegion::Vram);
+        assert_eq!(Bus::region_of(0xA000), MemoryRegion::ExtRam);
+        assert_eq!(Bus::region_of(0xD000), MemoryRegion::Wram);
+        assert_eq!(Bus::region_of(0xE000), MemoryRegion::EchoRam);
+        assert_eq!(Bus::region_of(0xFE00), MemoryRegion::Oam);
+        assert_eq!(Bus::region_of(0xFEA0), MemoryRegion::Unusable);
+        assert_eq!(Bus::region_of(0xFF40), MemoryRegion::Io);
+        assert_eq!(Bus::region_of(0xFFFE), MemoryRegion::Hram);
+        assert_eq!(Bus::region_of(0xFFFF), MemoryRegion::Ie);
+        assert_eq!(MemoryRegion::Oam.name(), "OAM");
+    }
+
+    #[test]
+    fn test_wave_ram_while_apu_off() {
+        let mut bus = Bus::new();
+        bus.write(0xFF26, 0x00); // APU off
+        assert!(!bus.apu.powered());
+
+        for i in 0..16u16 {
+            bus.write(0xFF30 + i, (i as u8) * 0x11);
+        }
+        for i in 0..16u16 {
+            assert_eq!(bus.read(0xFF30 + i), (i as u8) * 0x11);
+        }
+
+        // Power cycling keeps the pattern
+        bus.write(0xFF26, 0x80);
+        assert_eq!(bus.read(0xFF3F), 0xFF);
+        assert_eq!(bus.read(0xFF31), 0x11);
+    }
+
+    #[test]
+    fn test_vram_oam_slices() {
+        let mut bus = Bus::new();
+        bus.write(0xFF40, 0x00); // LCD off: VRAM and OAM freely writable
+        bus.write(0x8000, 0x12);
+        bus.write(0x9FFF, 0x34);
+        bus.write(0xFE9F, 0x56);
+
+        assert_eq!(bus.vram_slice().len(), 0x2000);
+        assert_eq!(bus.vram_slice()[0], 0x12);
+        assert_eq!(bus.vram_slice()[0x1FFF], 0x34);
+        assert_eq!(bus.oam_slice().len(), 160);
+        assert_eq!(bus.oam_slice()[159], 0x56);
+
+        // CGB: the selected bank follows VBK
+        bus.set_model(Model::Cgb);
+        bus.write(0xFF4F, 0x01);
+        bus.write(0x8000, 0x78);
+        assert_eq!(bus.vram_slice()[0], 0x78);
+        assert_eq!(bus.vram_bank_slice(0)[0], 0x12);
+        assert_eq!(bus.vram_bank_slice(1)[0], 0x78);
+    }
+
+    #[test]
+    fn test_oam_dma_status() {
+        let mut bus = Bus::new();
+        for i in 0..160u16 {
+            bus.write(0xC100 + i, 0x80 | i as u8);
+        }
+        assert_eq!(bus.oam_dma_status(), None);
+
+        bus.write(0xFF46, 0xC1);
+        assert_eq!(bus.read(0xFF46), 0xC1);
+        assert_eq!(bus.oam_dma_status(), Some((0xC100, 0)));
+
+        // 40 M-cycles in: 40 bytes copied
+        bus.tick(160);
+        assert_eq!(bus.oam_dma_status(), Some((0xC100, 40)));
+        assert_eq!(bus.ppu.oam[39], 0x80 | 39);
+        assert_eq!(bus.ppu.oam[40], 0x00);
+
+        bus.tick(480);
+        assert_eq!(bus.oam_dma_status(), None);
+        assert_eq!(bus.ppu.oam[159], 0x80 | 159);
+    }
+
+    #[test]
+    fn test_serial_registers_mid_transfer() {
+        let mut bus = Bus::new();
+        bus.serial.set_mode(crate::serial::SerialMode::Timed);
+        bus.write(0xFF01, 0x00);
+        bus.write(0xFF02, 0x81);
+
+        // 3 of 8 bits shifted: three 1s (no partner) came in at the bottom
+        bus.tick(3 * crate::serial::CYCLES_PER_BIT);
+        assert_eq!(bus.read(0xFF01), 0b0000_0111);
+        assert_eq!(bus.read(0xFF02) & 0x80, 0x80);
+
+        bus.tick(5 * crate::serial::CYCLES_PER_BIT);
+        assert_eq!(bus.read(0xFF01), 0xFF);
+        assert_eq!(bus.read(0xFF02) & 0x80, 0x00);
+        assert_eq!(bus.read(0xFF0F) & 0x08, 0x08);
+    }
+
+    #[test]
+    fn test_ly_and_stat_track_a_full_frame() {
+        let mut bus = Bus::new();
+        bus.tick(154 * 456);
+
+        // Sample LY and the STAT mode at the start of every line
+        let mut lines = Vec::new();
+        for _ in 0..155 {
+            lines.push((bus.read(0xFF44), bus.read(0xFF41) & 0x03));
+            bus.tick(456);
+        }
+
+        let ly: Vec<u8> = lines.iter().map(|&(ly, _)| ly).collect();
+        let expected: Vec<u8> = (0..=153).chain(0..1).collect();
+        assert_eq!(ly, expected);
+        assert!(lines[..144].iter().all(|&(_, mode)| mode == 2));
+        assert!(lines[144..154].iter().all(|&(_, mode)| mode == 1));
+        assert_eq!(lines[154].1, 2);
+
+        // LY == LYC sets the coincidence flag
+        let mut bus = Bus::new();
+        bus.write(0xFF45, 2);
+        bus.tick(456);
+        assert_eq!(bus.read(0xFF41) & 0x04, 0);
+        bus.tick(456);
+        assert_eq!(bus.read(0xFF41) & 0x04, 0x04);
+    }
+
+    #[test]
+    fn test_double_speed_keeps_odd_cycles() {
+        let mut bus = Bus::new();
+        bus.set_double_speed(true);
+        let start = bus.ppu.dot();
+
+        // Two 1-cycle ticks make one dot
+        bus.tick(1);
+        assert_eq!(bus.ppu.dot(), start);
+        bus.tick(1);
+        assert_eq!(bus.ppu.dot(), start + 1);
+
+        // Odd ticks add up exactly
+        for _ in 0..10 {
+            bus.tick(3);
+        }
+        assert_eq!(bus.ppu.dot(), start + 16);
+    }
+
+    #[test]
+    fn test_dma_source_mapping() {
+        // VRAM is copied even while the PPU locks it out (mode 3)
+        let mut bus = Bus::new();
+        bus.ppu.vram[0x0000] = 0x12;
+        bus.ppu.force_mode(crate::ppu::PpuMode::Drawing, 10, 100);
+        assert_eq!(bus.read(0x8000), 0xFF);
+        bus.write(0xFF46, 0x80);
+        bus.tick(4);
+        assert_eq!(bus.ppu.oam[0], 0x12);
+
+        // 0xFE and 0xFF read the echo of 0xDE00 / 0xDF00
+        for (source, wram) in [(0xFE, 0xDE00), (0xFF, 0xDF00)] {
+            let mut bus = Bus::new();
+            bus.write(wram, 0x34);
+            bus.write(0xFF46, source);
+            bus.tick(4);
+            assert_eq!(bus.ppu.oam[0], 0x34);
+        }
+    }
+
+    #[test]
+    fn test_dma_restricts_cpu_to_hram() {
+        let mut bus = Bus::new();
+        bus.write(0xC000, 0x11);
+        bus.write(0xFF80, 0x22);
+        bus.write(0xFF46, 0xC0);
+
+        // Only I/O, HRAM and IE are reachable by the CPU while the DMA runs
+        assert_eq!(bus.cpu_read(0xC000), 0xFF);
+        assert_eq!(bus.cpu_read(0x0100), 0xFF);
+        assert_eq!(bus.cpu_read(0xFF80), 0x22);
+        assert_eq!(bus.cpu_read(0xFF46), 0xC0);
+        bus.cpu_write(0xC001, 0x33);
+        bus.cpu_write(0xFF81, 0x44);
+
+        // Tools still see the whole map
+        assert_eq!(bus.read(0xC000), 0x11);
+        assert_eq!(bus.peek(0xC000), 0x11);
+
+        bus.tick(640);
+        assert_eq!(bus.read(0xC000), 0x11);
+        assert_eq!(bus.read(0xC001), 0x00);
+        assert_eq!(bus.read(0xFF81), 0x44);
+        assert_eq!(bus.ppu.oam[0], 0x11);
+
+        // Can be turned off
+        bus.set_dma_block(false);
+        bus.write(0xFF46, 0xC0);
+        assert_eq!(bus.cpu_read(0xC000), 0x11);
+    }
 }
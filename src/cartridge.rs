@@ -5,8 +5,10 @@
 //
 // 0x0100-0x0103: Entry point (usually NOP + JP)
 // 0x0104-0x0133: Nintendo logo (must match for boot)
-// 0x0134-0x0143: Title (uppercase ASCII)
-// 0x0143: CGB flag
+// 0x0134-0x0143: Title (uppercase ASCII, padded with 0x00)
+// 0x0143: CGB flag (0x80 = CGB enhanced, 0xC0 = CGB only); on CGB-aware
+//         carts the title is cut to 15 bytes (newer ones also reuse
+//         0x013F-0x0142 for a manufacturer code)
 // 0x0144-0x0145: New licensee code
 // 0x0146: SGB flag
 // 0x0147: Cartridge type (MBC type)
@@ -64,6 +66,38 @@ impl From<u8> for CartridgeType {
     }
 }
 
+impl CartridgeType {
+    /// Check if the cartridge type includes RAM (external or MBC2 built-in)
+    pub fn has_ram(&self) -> bool {
+        matches!(
+            self,
+            CartridgeType::Mbc1Ram
+                | CartridgeType::Mbc1RamBattery
+                | CartridgeType::Mbc2
+                | CartridgeType::Mbc2Battery
+                | CartridgeType::Mbc3Ram
+                | CartridgeType::Mbc3RamBattery
+                | CartridgeType::Mbc3TimerRamBattery
+                | CartridgeType::Mbc5Ram
+                | CartridgeType::Mbc5RamBattery
+        )
+    }
+
+    /// Check if this is an MBC2 cartridge (512x4-bit RAM built into the MBC)
+    pub fn is_mbc2(&self) -> bool {
+        matches!(self, CartridgeType::Mbc2 | CartridgeType::Mbc2Battery)
+    }
+}
+
+/// Result of cross-checking the declared cartridge type against the ROM
+#[derive(Debug, Clone, PartialEq)]
+pub struct MbcDetection {
+    /// Cartridge type the ROM most likely needs
+    pub cartridge_type: CartridgeType,
+    /// Why the header looks wrong (None = consistent)
+    pub warning: Option<String>,
+}
+
 /// Cartridge information parsed from header
 #[derive(Debug)]
 pub struct CartridgeInfo {
@@ -76,6 +110,15 @@ pub struct CartridgeInfo {
     pub checksum_valid: bool,
 }
 
+/// Compute the header checksum stored at 0x014D:
+/// x = 0; for each byte in 0x0134..=0x014C: x = x - byte - 1
+/// `rom` must contain at least the header (0x0150 bytes).
+pub fn compute_header_checksum(rom: &[u8]) -> u8 {
+    rom[0x0134..=0x014C]
+        .iter()
+        .fold(0u8, |checksum, &byte| checksum.wrapping_sub(byte).wrapping_sub(1))
+}
+
 /// Cartridge data and metadata
 pub struct Cartridge {
     pub rom: Vec<u8>,
@@ -107,13 +150,9 @@ impl Cartridge {
 
     /// Parse cartridge header
     fn parse_header(rom: &[u8]) -> Result<CartridgeInfo, String> {
-        // Extract title (0x0134-0x0143)
-        let title_bytes = &rom[0x0134..=0x0143];
-        let title = title_bytes
-            .iter()
-            .take_while(|&&b| b != 0)
-            .map(|&b| b as char)
-            .collect::<String>();
+        // Extract title (0x0134-0x0143, or up to 0x0142 when 0x0143 is a CGB flag)
+        let title_end = if rom[0x0143] & 0x80 != 0 { 0x0143 } else { 0x0144 };
+        let title = Self::clean_title(&rom[0x0134..title_end]);
 
         // Cartridge type (0x0147)
         let cartridge_type_byte = rom[0x0147];
@@ -148,13 +187,7 @@ impl Cartridge {
         let header_checksum = rom[0x014D];
 
         // Verify header checksum
-        // x = 0
-        // for i in 0x0134..=0x014C: x = x - rom[i] - 1
-        let mut checksum: u8 = 0;
-        for i in 0x0134..=0x014C {
-            checksum = checksum.wrapping_sub(rom[i]).wrapping_sub(1);
-        }
-        let checksum_valid = checksum == header_checksum;
+        let checksum_valid = compute_header_checksum(rom) == header_checksum;
 
         Ok(CartridgeInfo {
             title,
@@ -167,6 +200,91 @@ impl Cartridge {
         })
     }
 
+    /// Turn raw title bytes into a displayable title: stop at the first
+    /// zero, drop non-printable bytes and trim trailing spaces
+    /// Only printable ASCII is kept; bytes >= 0x80 would otherwise become
+    /// unrelated Latin-1 characters.
+    fn clean_title(bytes: &[u8]) -> String {
+        let title: String = bytes
+            .iter()
+            .take_while(|&&b| b != 0)
+            .filter(|&&b| (0x20..=0x7E).contains(&b))
+            .map(|&b| b as char)
+            .collect();
+        title.trim_end().to_string()
+    }
+
+    /// The game title from the header
+    pub fn title(&self) -> &str {
+        &self.info.title
+    }
+
+    /// Check if the cartridge has RAM
+    /// MBC2 always has its built-in RAM, even though the header RAM size is 0
+    pub fn has_ram(&self) -> bool {
+        match self.info.cartridge_type {
+            CartridgeType::Unknown(_) => self.info.ram_size > 0,
+            t if t.is_mbc2() => true,
+            t => t.has_ram() && self.info.ram_size > 0,
+        }
+    }
+
+    /// Number of 8KB RAM banks (0 for RAM-less carts)
+    /// Carts with less than 8KB (2KB RAM, MBC2 built-in RAM) report a single bank
+    pub fn ram_banks(&self) -> usize {
+        if !self.has_ram() {
+            0
+        } else if self.info.cartridge_type.is_mbc2() {
+            1
+        } else {
+            self.info.ram_size.div_ceil(0x2000)
+        }
+    }
+
+    /// Sanity-check the header's cartridge type against the ROM and RAM sizes
+    /// Bad dumps and homebrew sometimes declare a type that can't address
+    /// their ROM; the suggested type is then the closest one that can.
+    pub fn detect_mbc(&self) -> MbcDetection {
+        let declared = self.info.cartridge_type;
+        let rom_kb = self.rom.len().max(self.info.rom_size) / 1024;
+        let fits_mbc1 = rom_kb <= 2048;
+        let larger_mbc = if fits_mbc1 { CartridgeType::Mbc1 } else { CartridgeType::Mbc5 };
+
+        let (cartridge_type, warning) = match declared {
+            CartridgeType::RomOnly if rom_kb > 32 => (
+                larger_mbc,
+                Some(format!("ROM-only header but {}KB of ROM (needs banking)", rom_kb)),
+            ),
+            CartridgeType::Mbc1 | CartridgeType::Mbc1Ram | CartridgeType::Mbc1RamBattery
+                if !fits_mbc1 =>
+            {
+                (
+                    CartridgeType::Mbc5,
+                    Some(format!("MBC1 header but {}KB of ROM (MBC1 maxes out at 2MB)", rom_kb)),
+                )
+            }
+            CartridgeType::Mbc2 | CartridgeType::Mbc2Battery if self.info.ram_size > 0 => (
+                declared,
+                Some("MBC2 header declares external RAM (MBC2 RAM is built in)".to_string()),
+            ),
+            CartridgeType::Unknown(byte) => {
+                let guess = if rom_kb > 32 { larger_mbc } else { CartridgeType::RomOnly };
+                (guess, Some(format!("Unknown cartridge type 0x{:02X}", byte)))
+            }
+            _ if self.rom.len() != self.info.rom_size => (
+                declared,
+                Some(format!(
+                    "Header declares {}KB of ROM but the file is {}KB",
+                    self.info.rom_size / 1024,
+                    self.rom.len() / 1024
+                )),
+            ),
+            _ => (declared, None),
+        };
+
+        MbcDetection { cartridge_type, warning }
+    }
+
     /// Read a byte from ROM
     pub fn read(&self, addr: u16) -> u8 {
         if (addr as usize) < self.rom.len() {
@@ -203,12 +321,7 @@ mod tests {
         // RAM size: None
         rom[0x0149] = 0x00;
 
-        // Calculate header checksum
-        let mut checksum: u8 = 0;
-        for i in 0x0134..=0x014C {
-            checksum = checksum.wrapping_sub(rom[i]).wrapping_sub(1);
-        }
-        rom[0x014D] = checksum;
+        rom[0x014D] = compute_header_checksum(&rom);
 
         rom
     }
@@ -225,6 +338,31 @@ mod tests {
         assert!(cart.info.checksum_valid);
     }
 
+    #[test]
+    fn test_title_cleanup() {
+        // 15-character title followed by the CGB flag
+        let mut rom = create_minimal_rom();
+        rom[0x0134..0x0143].copy_from_slice(b"POKEMON CRYSTAL");
+        rom[0x0143] = 0x80;
+        let cart = Cartridge::from_bytes(rom).unwrap();
+        assert_eq!(cart.title(), "POKEMON CRYSTAL");
+
+        // Non-printable bytes and trailing spaces are dropped
+        let mut rom = create_minimal_rom();
+        rom[0x0134..0x0141].copy_from_slice(b"GAME\x01\xFFX   \x7F  ");
+        let cart = Cartridge::from_bytes(rom).unwrap();
+        assert_eq!(cart.title(), "GAMEX");
+    }
+
+    #[test]
+    fn test_title_drops_non_ascii() {
+        let mut rom = create_minimal_rom();
+        rom[0x0134..0x013A].copy_from_slice(b"ZEL\x99DA");
+        let cart = Cartridge::from_bytes(rom).unwrap();
+        assert_eq!(cart.title(), "ZELDA");
+        assert!(cart.title().is_ascii());
+    }
+
     #[test]
     fn test_cartridge_type_parsing() {
         assert_eq!(CartridgeType::from(0x00), CartridgeType::RomOnly);
@@ -234,6 +372,82 @@ mod tests {
         assert_eq!(CartridgeType::from(0x1B), CartridgeType::Mbc5RamBattery);
     }
 
+    #[test]
+    fn test_ram_presence() {
+        let with_header = |cart_type: u8, ram_size: u8| {
+            let mut rom = create_minimal_rom();
+            rom[0x0147] = cart_type;
+            rom[0x0149] = ram_size;
+            Cartridge::from_bytes(rom).unwrap()
+        };
+
+        // ROM only
+        let cart = with_header(0x00, 0x00);
+        assert!(!cart.has_ram());
+        assert_eq!(cart.ram_banks(), 0);
+
+        // MBC1+RAM+BATTERY, 32KB
+        let cart = with_header(0x03, 0x03);
+        assert!(cart.has_ram());
+        assert_eq!(cart.ram_banks(), 4);
+
+        // MBC3+RAM with 2KB
+        let cart = with_header(0x12, 0x01);
+        assert!(cart.has_ram());
+        assert_eq!(cart.ram_banks(), 1);
+
+        // MBC2: built-in RAM despite a RAM size byte of 0
+        let cart = with_header(0x05, 0x00);
+        assert!(cart.has_ram());
+        assert_eq!(cart.ram_banks(), 1);
+
+        // MBC1 without RAM in its type ignores a bogus RAM size
+        let cart = with_header(0x01, 0x02);
+        assert!(!cart.has_ram());
+        assert_eq!(cart.ram_banks(), 0);
+    }
+
+    #[test]
+    fn test_detect_mbc() {
+        let cart = Cartridge::from_bytes(create_minimal_rom()).unwrap();
+        let detection = cart.detect_mbc();
+        assert_eq!(detection.cartridge_type, CartridgeType::RomOnly);
+        assert_eq!(detection.warning, None);
+
+        // ROM-only header on a 256KB ROM
+        let mut rom = create_minimal_rom();
+        rom[0x0148] = 0x03;
+        rom.resize(256 * 1024, 0);
+        let cart = Cartridge::from_bytes(rom).unwrap();
+        let detection = cart.detect_mbc();
+        assert_eq!(detection.cartridge_type, CartridgeType::Mbc1);
+        assert!(detection.warning.unwrap().contains("256KB"));
+
+        // Consistent type, but the file is shorter than the header claims
+        let mut rom = create_minimal_rom();
+        rom[0x0147] = 0x01;
+        rom[0x0148] = 0x02;
+        let cart = Cartridge::from_bytes(rom).unwrap();
+        let detection = cart.detect_mbc();
+        assert_eq!(detection.cartridge_type, CartridgeType::Mbc1);
+        assert!(detection.warning.is_some());
+    }
+
+    #[test]
+    fn test_compute_header_checksum() {
+        let mut rom = create_minimal_rom();
+        assert!(Cartridge::from_bytes(rom.clone()).unwrap().info.checksum_valid);
+
+        // Editing the title invalidates the checksum until it is recomputed
+        rom[0x0134..0x0138].copy_from_slice(b"EDIT");
+        assert!(!Cartridge::from_bytes(rom.clone()).unwrap().info.checksum_valid);
+
+        rom[0x014D] = compute_header_checksum(&rom);
+        let cart = Cartridge::from_bytes(rom).unwrap();
+        assert!(cart.info.checksum_valid);
+        assert_eq!(cart.info.header_checksum, compute_header_checksum(&cart.rom));
+    }
+
     #[test]
     fn test_rom_too_small() {
         let rom = vec![0u8; 100];
@@ -110,7 +110,7 @@ impl Cpu {
             3 => self.regs.e,
             4 => self.regs.h,
             5 => self.regs.l,
-            6 => bus.read(self.regs.hl()),  // (HL)
+            6 => bus.cpu_read(self.regs.hl()),  // (HL)
             7 => self.regs.a,
             _ => unreachable!(),
         }
@@ -125,7 +125,7 @@ impl Cpu {
             3 => self.regs.e = value,
             4 => self.regs.h = value,
             5 => self.regs.l = value,
-            6 => bus.write(self.regs.hl(), value),  // (HL)
+            6 => bus.cpu_write(self.regs.hl(), value),  // (HL)
             7 => self.regs.a = value,
             _ => unreachable!(),
         }
@@ -260,7 +260,7 @@ mod tests {
         bus.write(0xC000, 0xCB);  // CB prefix
         bus.write(0xC001, 0x00);  // RLC B
 
-        cpu.step(&mut bus);
+        cpu.step(&mut bus).unwrap();
 
         assert_eq!(cpu.regs.b, 0x0B);  // 0000_1011
         assert!(cpu.regs.f.c);  // bit 7 was set
@@ -274,7 +274,7 @@ mod tests {
         bus.write(0xC000, 0xCB);
         bus.write(0xC001, 0x08);  // RRC B
 
-        cpu.step(&mut bus);
+        cpu.step(&mut bus).unwrap();
 
         assert_eq!(cpu.regs.b, 0x80);  // 1000_0000
         assert!(cpu.regs.f.c);
@@ -287,7 +287,7 @@ mod tests {
         bus.write(0xC000, 0xCB);
         bus.write(0xC001, 0x20);  // SLA B
 
-        cpu.step(&mut bus);
+        cpu.step(&mut bus).unwrap();
 
         assert_eq!(cpu.regs.b, 0x00);
         assert!(cpu.regs.f.c);  // bit 7 went to carry
@@ -301,7 +301,7 @@ mod tests {
         bus.write(0xC000, 0xCB);
         bus.write(0xC001, 0x28);  // SRA B
 
-        cpu.step(&mut bus);
+        cpu.step(&mut bus).unwrap();
 
         assert_eq!(cpu.regs.b, 0xC0);  // 1100_0000 (bit 7 preserved)
         assert!(cpu.regs.f.c);
@@ -314,7 +314,7 @@ mod tests {
         bus.write(0xC000, 0xCB);
         bus.write(0xC001, 0x30);  // SWAP B
 
-        cpu.step(&mut bus);
+        cpu.step(&mut bus).unwrap();
 
         assert_eq!(cpu.regs.b, 0x0F);
         assert!(!cpu.regs.f.z);
@@ -328,7 +328,7 @@ mod tests {
         bus.write(0xC000, 0xCB);
         bus.write(0xC001, 0x78);  // BIT 7, B
 
-        cpu.step(&mut bus);
+        cpu.step(&mut bus).unwrap();
 
         assert!(!cpu.regs.f.z);  // bit 7 is set
         assert!(!cpu.regs.f.n);
@@ -337,7 +337,7 @@ mod tests {
         // Test bit 0 (not set)
         cpu.regs.pc = 0xC000;
         bus.write(0xC001, 0x40);  // BIT 0, B
-        cpu.step(&mut bus);
+        cpu.step(&mut bus).unwrap();
         assert!(cpu.regs.f.z);  // bit 0 is not set
     }
 
@@ -348,7 +348,7 @@ mod tests {
         bus.write(0xC000, 0xCB);
         bus.write(0xC001, 0x80);  // RES 0, B
 
-        cpu.step(&mut bus);
+        cpu.step(&mut bus).unwrap();
 
         assert_eq!(cpu.regs.b, 0xFE);
     }
@@ -360,7 +360,7 @@ mod tests {
         bus.write(0xC000, 0xCB);
         bus.write(0xC001, 0xF8);  // SET 7, B
 
-        cpu.step(&mut bus);
+        cpu.step(&mut bus).unwrap();
 
         assert_eq!(cpu.regs.b, 0x80);
     }
@@ -373,7 +373,7 @@ mod tests {
         bus.write(0xC000, 0xCB);
         bus.write(0xC001, 0x10);  // RL B
 
-        cpu.step(&mut bus);
+        cpu.step(&mut bus).unwrap();
 
         assert_eq!(cpu.regs.b, 0x01);  // Carry rotated in
         assert!(cpu.regs.f.c);  // bit 7 went to carry
@@ -386,9 +386,30 @@ mod tests {
         bus.write(0xC000, 0xCB);
         bus.write(0xC001, 0x38);  // SRL B
 
-        cpu.step(&mut bus);
+        cpu.step(&mut bus).unwrap();
 
         assert_eq!(cpu.regs.b, 0x40);  // 0100_0000 (logical shift, 0 into bit 7)
         assert!(cpu.regs.f.c);  // bit 0 went to carry
     }
+
+    #[test]
+    fn test_cb_cycle_counts() {
+        // Including the 0xCB prefix: registers 8, BIT b,(HL) 12 (read only),
+        // every other (HL) operation 16 (read-modify-write)
+        for opcode in 0..=0xFFu8 {
+            let (mut cpu, mut bus) = setup();
+            cpu.regs.set_hl(0xC100);
+            bus.write(0xC000, 0xCB);
+            bus.write(0xC001, opcode);
+
+            let expected = match (opcode >> 6, opcode & 0x07) {
+                (_, r) if r != 6 => 8,
+                (1, _) => 12,
+                _ => 16,
+            };
+            let cycles = cpu.step(&mut bus).unwrap();
+            assert_eq!(cycles, expected, "CB {:02X}", opcode);
+            assert_eq!(cpu.regs.pc, 0xC002, "CB {:02X}", opcode);
+        }
+    }
 }
@@ -9,30 +9,49 @@
 // - Memory access takes 1 M-cycle per byte
 // - Conditional branches may take different times depending on condition
 
-use super::Cpu;
+use super::{Cpu, CpuError};
 use crate::bus::Bus;
 
 impl Cpu {
     /// Fetch, decode, and execute one instruction
-    /// Returns the number of T-cycles (clock cycles) consumed
-    pub fn step(&mut self, bus: &mut Bus) -> u32 {
+    /// Returns the number of T-cycles (clock cycles) consumed, or an error for
+    /// an opcode the CPU can't execute (PC is left just past it)
+    pub fn step(&mut self, bus: &mut Bus) -> Result<u32, CpuError> {
+        self.last_interrupt = None;
+        self.ticked_cycles = 0;
+
+        if self.stopped {
+            // STOP mode: only a pressed button on a selected joypad line wakes
+            // the CPU (interrupts don't)
+            if bus.read(0xFF00) & 0x0F == 0x0F {
+                return Ok(4);
+            }
+            self.stopped = false;
+        }
+
         // Handle pending interrupts first
         let interrupt_cycles = self.handle_interrupts(bus);
         if interrupt_cycles > 0 {
-            return interrupt_cycles;
+            return Ok(interrupt_cycles);
         }
 
         if self.halted {
             // HALT mode: CPU waits for interrupt
-            // Still consume cycles
-            return 4;
+            // Still consume cycles, one M-cycle at a time. Every instruction
+            // takes a multiple of 4 cycles, so interrupts can only become
+            // pending on 4-cycle boundaries and this never overshoots the wake-up.
+            return Ok(4);
         }
 
         // Remember if EI was scheduled before this instruction
         let ei_pending = self.ime_scheduled;
 
         let opcode = self.fetch(bus);
-        let cycles = self.execute(bus, opcode);
+        let cycles = self.execute(bus, opcode)?;
+
+        if self.nesting_warning.is_some() {
+            self.update_interrupt_depth();
+        }
 
         // Apply scheduled IME enable AFTER the instruction executes
         // (EI has 1 instruction delay)
@@ -41,12 +60,40 @@ impl Cpu {
             self.ime_scheduled = false;
         }
 
-        cycles
+        Ok(cycles)
+    }
+
+    /// Like `step`, but panics on an undefined opcode
+    pub fn step_unchecked(&mut self, bus: &mut Bus) -> u32 {
+        self.step(bus).unwrap_or_else(|e| panic!("{}", e))
+    }
+
+    /// Write one instruction's bytes at PC and execute exactly that instruction
+    /// Interrupts and HALT are not considered. PC must point at writable memory
+    /// (e.g. WRAM). Returns the cycles taken.
+    pub fn exec_one(&mut self, bus: &mut Bus, bytes: &[u8]) -> Result<u32, CpuError> {
+        for (i, &byte) in bytes.iter().enumerate() {
+            bus.write(self.regs.pc.wrapping_add(i as u16), byte);
+        }
+
+        let ei_pending = self.ime_scheduled;
+        let opcode = self.fetch(bus);
+        let cycles = self.execute(bus, opcode)?;
+        if ei_pending {
+            self.ime = true;
+            self.ime_scheduled = false;
+        }
+        Ok(cycles)
+    }
+
+    /// Leave STOP mode as if a button had been pressed
+    pub fn wake_from_stop(&mut self) {
+        self.stopped = false;
     }
 
     /// Fetch the next byte from PC and increment PC
     fn fetch(&mut self, bus: &Bus) -> u8 {
-        let byte = bus.read(self.regs.pc);
+        let byte = bus.cpu_read(self.regs.pc);
         self.regs.pc = self.regs.pc.wrapping_add(1);
         byte
     }
@@ -59,18 +106,27 @@ impl Cpu {
     }
 
     /// Execute an instruction and return cycles consumed
-    fn execute(&mut self, bus: &mut Bus, opcode: u8) -> u32 {
-        match opcode {
+    fn execute(&mut self, bus: &mut Bus, opcode: u8) -> Result<u32, CpuError> {
+        let cycles = match opcode {
             // ========== NOP ==========
             // 0x00: NOP - No operation
             0x00 => 4,
 
             // ========== STOP ==========
             // 0x10: STOP - Halt CPU & LCD until button pressed
-            // In practice, often used as a 2-byte NOP (0x10 0x00)
+            // Encoded as 0x10 0x00. If the second byte isn't 0x00 the STOP is
+            // "corrupted": only the opcode is consumed and the following byte
+            // runs as the next instruction after waking up.
+            // On CGB with a speed switch armed (KEY1), STOP switches speed
+            // instead of stopping. Entering STOP resets DIV.
             0x10 => {
-                self.fetch(bus); // Consume the next byte (usually 0x00)
-                // For now, treat as NOP. Real STOP would halt until joypad input.
+                if bus.cpu_read(self.regs.pc) == 0x00 {
+                    self.fetch(bus);
+                }
+                bus.cpu_write(0xFF04, 0);
+                if !bus.perform_speed_switch() {
+                    self.stopped = true;
+                }
                 4
             }
 
@@ -92,7 +148,7 @@ impl Cpu {
             0x43 => { self.regs.b = self.regs.e; 4 }
             0x44 => { self.regs.b = self.regs.h; 4 }
             0x45 => { self.regs.b = self.regs.l; 4 }
-            0x46 => { self.regs.b = bus.read(self.regs.hl()); 8 }  // LD B, (HL)
+            0x46 => { self.regs.b = bus.cpu_read(self.regs.hl()); 8 }  // LD B, (HL)
             0x47 => { self.regs.b = self.regs.a; 4 }
 
             // LD C, r
@@ -102,7 +158,7 @@ impl Cpu {
             0x4B => { self.regs.c = self.regs.e; 4 }
             0x4C => { self.regs.c = self.regs.h; 4 }
             0x4D => { self.regs.c = self.regs.l; 4 }
-            0x4E => { self.regs.c = bus.read(self.regs.hl()); 8 }
+            0x4E => { self.regs.c = bus.cpu_read(self.regs.hl()); 8 }
             0x4F => { self.regs.c = self.regs.a; 4 }
 
             // LD D, r
@@ -112,7 +168,7 @@ impl Cpu {
             0x53 => { self.regs.d = self.regs.e; 4 }
             0x54 => { self.regs.d = self.regs.h; 4 }
             0x55 => { self.regs.d = self.regs.l; 4 }
-            0x56 => { self.regs.d = bus.read(self.regs.hl()); 8 }
+            0x56 => { self.regs.d = bus.cpu_read(self.regs.hl()); 8 }
             0x57 => { self.regs.d = self.regs.a; 4 }
 
             // LD E, r
@@ -122,7 +178,7 @@ impl Cpu {
             0x5B => 4,  // LD E, E
             0x5C => { self.regs.e = self.regs.h; 4 }
             0x5D => { self.regs.e = self.regs.l; 4 }
-            0x5E => { self.regs.e = bus.read(self.regs.hl()); 8 }
+            0x5E => { self.regs.e = bus.cpu_read(self.regs.hl()); 8 }
             0x5F => { self.regs.e = self.regs.a; 4 }
 
             // LD H, r
@@ -132,7 +188,7 @@ impl Cpu {
             0x63 => { self.regs.h = self.regs.e; 4 }
             0x64 => 4,  // LD H, H
             0x65 => { self.regs.h = self.regs.l; 4 }
-            0x66 => { self.regs.h = bus.read(self.regs.hl()); 8 }
+            0x66 => { self.regs.h = bus.cpu_read(self.regs.hl()); 8 }
             0x67 => { self.regs.h = self.regs.a; 4 }
 
             // LD L, r
@@ -142,18 +198,18 @@ impl Cpu {
             0x6B => { self.regs.l = self.regs.e; 4 }
             0x6C => { self.regs.l = self.regs.h; 4 }
             0x6D => 4,  // LD L, L
-            0x6E => { self.regs.l = bus.read(self.regs.hl()); 8 }
+            0x6E => { self.regs.l = bus.cpu_read(self.regs.hl()); 8 }
             0x6F => { self.regs.l = self.regs.a; 4 }
 
             // LD (HL), r
-            0x70 => { bus.write(self.regs.hl(), self.regs.b); 8 }
-            0x71 => { bus.write(self.regs.hl(), self.regs.c); 8 }
-            0x72 => { bus.write(self.regs.hl(), self.regs.d); 8 }
-            0x73 => { bus.write(self.regs.hl(), self.regs.e); 8 }
-            0x74 => { bus.write(self.regs.hl(), self.regs.h); 8 }
-            0x75 => { bus.write(self.regs.hl(), self.regs.l); 8 }
+            0x70 => { bus.cpu_write(self.regs.hl(), self.regs.b); 8 }
+            0x71 => { bus.cpu_write(self.regs.hl(), self.regs.c); 8 }
+            0x72 => { bus.cpu_write(self.regs.hl(), self.regs.d); 8 }
+            0x73 => { bus.cpu_write(self.regs.hl(), self.regs.e); 8 }
+            0x74 => { bus.cpu_write(self.regs.hl(), self.regs.h); 8 }
+            0x75 => { bus.cpu_write(self.regs.hl(), self.regs.l); 8 }
             // 0x76 is HALT
-            0x77 => { bus.write(self.regs.hl(), self.regs.a); 8 }
+            0x77 => { bus.cpu_write(self.regs.hl(), self.regs.a); 8 }
 
             // LD A, r
             0x78 => { self.regs.a = self.regs.b; 4 }
@@ -162,7 +218,7 @@ impl Cpu {
             0x7B => { self.regs.a = self.regs.e; 4 }
             0x7C => { self.regs.a = self.regs.h; 4 }
             0x7D => { self.regs.a = self.regs.l; 4 }
-            0x7E => { self.regs.a = bus.read(self.regs.hl()); 8 }
+            0x7E => { self.regs.a = bus.cpu_read(self.regs.hl()); 8 }
             0x7F => 4,  // LD A, A
 
             // ========== LD rr, nn (16-bit immediate) ==========
@@ -172,29 +228,29 @@ impl Cpu {
             0x31 => { self.regs.sp = self.fetch16(bus); 12 }                 // LD SP, nn
 
             // ========== LD A, (rr) / LD (rr), A ==========
-            0x02 => { bus.write(self.regs.bc(), self.regs.a); 8 }  // LD (BC), A
-            0x12 => { bus.write(self.regs.de(), self.regs.a); 8 }  // LD (DE), A
-            0x0A => { self.regs.a = bus.read(self.regs.bc()); 8 }  // LD A, (BC)
-            0x1A => { self.regs.a = bus.read(self.regs.de()); 8 }  // LD A, (DE)
+            0x02 => { bus.cpu_write(self.regs.bc(), self.regs.a); 8 }  // LD (BC), A
+            0x12 => { bus.cpu_write(self.regs.de(), self.regs.a); 8 }  // LD (DE), A
+            0x0A => { self.regs.a = bus.cpu_read(self.regs.bc()); 8 }  // LD A, (BC)
+            0x1A => { self.regs.a = bus.cpu_read(self.regs.de()); 8 }  // LD A, (DE)
 
             // LD A, (HL+) / LD A, (HL-) / LD (HL+), A / LD (HL-), A
             0x22 => {  // LD (HL+), A
-                bus.write(self.regs.hl(), self.regs.a);
+                bus.cpu_write(self.regs.hl(), self.regs.a);
                 self.regs.set_hl(self.regs.hl().wrapping_add(1));
                 8
             }
             0x32 => {  // LD (HL-), A
-                bus.write(self.regs.hl(), self.regs.a);
+                bus.cpu_write(self.regs.hl(), self.regs.a);
                 self.regs.set_hl(self.regs.hl().wrapping_sub(1));
                 8
             }
             0x2A => {  // LD A, (HL+)
-                self.regs.a = bus.read(self.regs.hl());
+                self.regs.a = bus.cpu_read(self.regs.hl());
                 self.regs.set_hl(self.regs.hl().wrapping_add(1));
                 8
             }
             0x3A => {  // LD A, (HL-)
-                self.regs.a = bus.read(self.regs.hl());
+                self.regs.a = bus.cpu_read(self.regs.hl());
                 self.regs.set_hl(self.regs.hl().wrapping_sub(1));
                 8
             }
@@ -202,41 +258,41 @@ impl Cpu {
             // LD (nn), A / LD A, (nn)
             0xEA => {  // LD (nn), A
                 let addr = self.fetch16(bus);
-                bus.write(addr, self.regs.a);
+                bus.cpu_write(addr, self.regs.a);
                 16
             }
             0xFA => {  // LD A, (nn)
                 let addr = self.fetch16(bus);
-                self.regs.a = bus.read(addr);
+                self.regs.a = bus.cpu_read(addr);
                 16
             }
 
             // LDH (n), A / LDH A, (n) - High RAM access
             0xE0 => {  // LDH (n), A - LD (0xFF00+n), A
                 let offset = self.fetch(bus) as u16;
-                bus.write(0xFF00 + offset, self.regs.a);
+                bus.cpu_write(0xFF00 + offset, self.regs.a);
                 12
             }
             0xF0 => {  // LDH A, (n) - LD A, (0xFF00+n)
                 let offset = self.fetch(bus) as u16;
-                self.regs.a = bus.read(0xFF00 + offset);
+                self.regs.a = bus.cpu_read(0xFF00 + offset);
                 12
             }
 
             // LDH (C), A / LDH A, (C)
             0xE2 => {  // LD (0xFF00+C), A
-                bus.write(0xFF00 + self.regs.c as u16, self.regs.a);
+                bus.cpu_write(0xFF00 + self.regs.c as u16, self.regs.a);
                 8
             }
             0xF2 => {  // LD A, (0xFF00+C)
-                self.regs.a = bus.read(0xFF00 + self.regs.c as u16);
+                self.regs.a = bus.cpu_read(0xFF00 + self.regs.c as u16);
                 8
             }
 
             // LD (HL), n
             0x36 => {
                 let n = self.fetch(bus);
-                bus.write(self.regs.hl(), n);
+                bus.cpu_write(self.regs.hl(), n);
                 12
             }
 
@@ -246,7 +302,8 @@ impl Cpu {
             // LD (nn), SP
             0x08 => {
                 let addr = self.fetch16(bus);
-                bus.write16(addr, self.regs.sp);
+                bus.cpu_write(addr, (self.regs.sp & 0xFF) as u8);
+                bus.cpu_write(addr.wrapping_add(1), (self.regs.sp >> 8) as u8);
                 20
             }
 
@@ -258,8 +315,8 @@ impl Cpu {
             0x24 => { self.regs.h = self.inc(self.regs.h); 4 }  // INC H
             0x2C => { self.regs.l = self.inc(self.regs.l); 4 }  // INC L
             0x34 => {  // INC (HL)
-                let v = self.inc(bus.read(self.regs.hl()));
-                bus.write(self.regs.hl(), v);
+                let v = self.inc(bus.cpu_read(self.regs.hl()));
+                bus.cpu_write(self.regs.hl(), v);
                 12
             }
             0x3C => { self.regs.a = self.inc(self.regs.a); 4 }  // INC A
@@ -271,8 +328,8 @@ impl Cpu {
             0x25 => { self.regs.h = self.dec(self.regs.h); 4 }  // DEC H
             0x2D => { self.regs.l = self.dec(self.regs.l); 4 }  // DEC L
             0x35 => {  // DEC (HL)
-                let v = self.dec(bus.read(self.regs.hl()));
-                bus.write(self.regs.hl(), v);
+                let v = self.dec(bus.cpu_read(self.regs.hl()));
+                bus.cpu_write(self.regs.hl(), v);
                 12
             }
             0x3D => { self.regs.a = self.dec(self.regs.a); 4 }  // DEC A
@@ -295,7 +352,7 @@ impl Cpu {
             0x83 => { self.add(self.regs.e); 4 }
             0x84 => { self.add(self.regs.h); 4 }
             0x85 => { self.add(self.regs.l); 4 }
-            0x86 => { self.add(bus.read(self.regs.hl())); 8 }
+            0x86 => { self.add(bus.cpu_read(self.regs.hl())); 8 }
             0x87 => { self.add(self.regs.a); 4 }
             0xC6 => { let n = self.fetch(bus); self.add(n); 8 }  // ADD A, n
 
@@ -306,7 +363,7 @@ impl Cpu {
             0x8B => { self.adc(self.regs.e); 4 }
             0x8C => { self.adc(self.regs.h); 4 }
             0x8D => { self.adc(self.regs.l); 4 }
-            0x8E => { self.adc(bus.read(self.regs.hl())); 8 }
+            0x8E => { self.adc(bus.cpu_read(self.regs.hl())); 8 }
             0x8F => { self.adc(self.regs.a); 4 }
             0xCE => { let n = self.fetch(bus); self.adc(n); 8 }  // ADC A, n
 
@@ -317,7 +374,7 @@ impl Cpu {
             0x93 => { self.sub(self.regs.e); 4 }
             0x94 => { self.sub(self.regs.h); 4 }
             0x95 => { self.sub(self.regs.l); 4 }
-            0x96 => { self.sub(bus.read(self.regs.hl())); 8 }
+            0x96 => { self.sub(bus.cpu_read(self.regs.hl())); 8 }
             0x97 => { self.sub(self.regs.a); 4 }
             0xD6 => { let n = self.fetch(bus); self.sub(n); 8 }  // SUB n
 
@@ -328,7 +385,7 @@ impl Cpu {
             0x9B => { self.sbc(self.regs.e); 4 }
             0x9C => { self.sbc(self.regs.h); 4 }
             0x9D => { self.sbc(self.regs.l); 4 }
-            0x9E => { self.sbc(bus.read(self.regs.hl())); 8 }
+            0x9E => { self.sbc(bus.cpu_read(self.regs.hl())); 8 }
             0x9F => { self.sbc(self.regs.a); 4 }
             0xDE => { let n = self.fetch(bus); self.sbc(n); 8 }  // SBC A, n
 
@@ -339,7 +396,7 @@ impl Cpu {
             0xA3 => { self.and(self.regs.e); 4 }
             0xA4 => { self.and(self.regs.h); 4 }
             0xA5 => { self.and(self.regs.l); 4 }
-            0xA6 => { self.and(bus.read(self.regs.hl())); 8 }
+            0xA6 => { self.and(bus.cpu_read(self.regs.hl())); 8 }
             0xA7 => { self.and(self.regs.a); 4 }
             0xE6 => { let n = self.fetch(bus); self.and(n); 8 }  // AND n
 
@@ -350,7 +407,7 @@ impl Cpu {
             0xAB => { self.xor(self.regs.e); 4 }
             0xAC => { self.xor(self.regs.h); 4 }
             0xAD => { self.xor(self.regs.l); 4 }
-            0xAE => { self.xor(bus.read(self.regs.hl())); 8 }
+            0xAE => { self.xor(bus.cpu_read(self.regs.hl())); 8 }
             0xAF => { self.xor(self.regs.a); 4 }
             0xEE => { let n = self.fetch(bus); self.xor(n); 8 }  // XOR n
 
@@ -361,7 +418,7 @@ impl Cpu {
             0xB3 => { self.or(self.regs.e); 4 }
             0xB4 => { self.or(self.regs.h); 4 }
             0xB5 => { self.or(self.regs.l); 4 }
-            0xB6 => { self.or(bus.read(self.regs.hl())); 8 }
+            0xB6 => { self.or(bus.cpu_read(self.regs.hl())); 8 }
             0xB7 => { self.or(self.regs.a); 4 }
             0xF6 => { let n = self.fetch(bus); self.or(n); 8 }  // OR n
 
@@ -372,7 +429,7 @@ impl Cpu {
             0xBB => { self.cp(self.regs.e); 4 }
             0xBC => { self.cp(self.regs.h); 4 }
             0xBD => { self.cp(self.regs.l); 4 }
-            0xBE => { self.cp(bus.read(self.regs.hl())); 8 }
+            0xBE => { self.cp(bus.cpu_read(self.regs.hl())); 8 }
             0xBF => { self.cp(self.regs.a); 4 }
             0xFE => { let n = self.fetch(bus); self.cp(n); 8 }  // CP n
 
@@ -554,15 +611,11 @@ impl Cpu {
             // ========== Undefined opcodes ==========
             0xD3 | 0xDB | 0xDD | 0xE3 | 0xE4 | 0xEB | 0xEC | 0xED | 0xF4 | 0xFC | 0xFD => {
                 // These opcodes are undefined on the Game Boy
-                // Real hardware behavior varies, often acts like NOP or crashes
-                panic!("Undefined opcode: 0x{:02X} at 0x{:04X}", opcode, self.regs.pc.wrapping_sub(1));
-            }
-
-            // For debugging: halt on unimplemented
-            _ => {
-                panic!("Unimplemented opcode: 0x{:02X} at 0x{:04X}", opcode, self.regs.pc.wrapping_sub(1));
+                // Real hardware locks up; let the caller decide what to do
+                return Err(CpuError::UndefinedOpcode(opcode, self.regs.pc.wrapping_sub(1)));
             }
-        }
+        };
+        Ok(cycles)
     }
 
     // ========== ALU Helper Functions ==========
@@ -666,6 +719,9 @@ impl Cpu {
     }
 
     /// ADD HL, rr (16-bit add)
+    /// On hardware the add is split over the 2 M-cycles (L + low byte, then
+    /// H + high byte + carry). Neither touches the bus, so computing it at once
+    /// and ticking the bus for all 8 cycles afterwards is indistinguishable.
     fn add_hl(&mut self, value: u16) {
         let hl = self.regs.hl();
         let (result, carry) = hl.overflowing_add(value);
@@ -679,18 +735,20 @@ impl Cpu {
     // ========== Stack operations ==========
 
     /// Push 16-bit value onto stack
-    fn push(&mut self, bus: &mut Bus, value: u16) {
+    /// SP is decremented before each write: high byte first, then low byte.
+    /// Shared by PUSH, CALL and RST.
+    pub(super) fn push(&mut self, bus: &mut Bus, value: u16) {
         self.regs.sp = self.regs.sp.wrapping_sub(1);
-        bus.write(self.regs.sp, (value >> 8) as u8);
+        bus.cpu_write(self.regs.sp, (value >> 8) as u8);
         self.regs.sp = self.regs.sp.wrapping_sub(1);
-        bus.write(self.regs.sp, (value & 0xFF) as u8);
+        bus.cpu_write(self.regs.sp, (value & 0xFF) as u8);
     }
 
     /// Pop 16-bit value from stack
     fn pop(&mut self, bus: &Bus) -> u16 {
-        let lo = bus.read(self.regs.sp) as u16;
+        let lo = bus.cpu_read(self.regs.sp) as u16;
         self.regs.sp = self.regs.sp.wrapping_add(1);
-        let hi = bus.read(self.regs.sp) as u16;
+        let hi = bus.cpu_read(self.regs.sp) as u16;
         self.regs.sp = self.regs.sp.wrapping_add(1);
         (hi << 8) | lo
     }
@@ -804,19 +862,94 @@ mod tests {
         let (mut cpu, mut bus) = setup();
         bus.write(0xC000, 0x00);  // NOP
 
-        let cycles = cpu.step(&mut bus);
+        let cycles = cpu.step(&mut bus).unwrap();
 
         assert_eq!(cycles, 4);
         assert_eq!(cpu.regs.pc, 0xC001);
     }
 
+    #[test]
+    fn test_exec_one() {
+        let (mut cpu, mut bus) = setup();
+
+        let cycles = cpu.exec_one(&mut bus, &[0x3E, 0x42]).unwrap(); // LD A, 0x42
+        assert_eq!(cycles, 8);
+        assert_eq!(cpu.regs.a, 0x42);
+        assert_eq!(cpu.regs.pc, 0xC002);
+
+        // Continues from the new PC
+        let cycles = cpu.exec_one(&mut bus, &[0xCB, 0x37]).unwrap(); // SWAP A
+        assert_eq!(cycles, 8);
+        assert_eq!(cpu.regs.a, 0x24);
+        assert_eq!(cpu.regs.pc, 0xC004);
+    }
+
+    #[test]
+    fn test_stop_waits_for_joypad() {
+        let (mut cpu, mut bus) = setup();
+        bus.write(0xC000, 0x10); // STOP
+        bus.write(0xC001, 0x00);
+        bus.write(0xC002, 0x3C); // INC A
+        bus.write(0xFF00, 0x10); // Select action buttons
+
+        cpu.step(&mut bus).unwrap();
+        assert!(cpu.stopped);
+        assert_eq!(cpu.regs.pc, 0xC002);
+
+        // Interrupts don't wake it
+        cpu.ime = true;
+        bus.write(0xFFFF, 0x04);
+        bus.write(0xFF0F, 0x04);
+        assert_eq!(cpu.step(&mut bus).unwrap(), 4);
+        assert!(cpu.stopped);
+        assert_eq!(cpu.regs.pc, 0xC002);
+        cpu.ime = false;
+
+        // A button on the selected line does
+        bus.joypad.press(crate::joypad::Button::A);
+        cpu.step(&mut bus).unwrap();
+        assert!(!cpu.stopped);
+        assert_eq!(cpu.regs.a, 0x02);
+    }
+
+    #[test]
+    fn test_corrupted_stop() {
+        let (mut cpu, mut bus) = setup();
+        bus.write(0xC000, 0x10); // STOP
+        bus.write(0xC001, 0x3C); // INC A (not 0x00)
+
+        cpu.step(&mut bus).unwrap();
+        assert!(cpu.stopped);
+        assert_eq!(cpu.regs.pc, 0xC001);
+
+        // The byte after STOP runs once the CPU wakes up
+        cpu.wake_from_stop();
+        cpu.step(&mut bus).unwrap();
+        assert_eq!(cpu.regs.a, 0x02);
+        assert_eq!(cpu.regs.pc, 0xC002);
+    }
+
+    #[test]
+    fn test_stop_performs_armed_speed_switch() {
+        let (mut cpu, mut bus) = setup();
+        bus.set_model(crate::model::Model::Cgb);
+        bus.write(0xC000, 0x10); // STOP
+        bus.write(0xC001, 0x00);
+        bus.write(0xFF4D, 0x01); // Arm the switch
+
+        cpu.step(&mut bus).unwrap();
+        assert!(!cpu.stopped);
+        assert!(bus.double_speed());
+        assert_eq!(bus.read(0xFF4D), 0xFE); // Double speed, no longer armed
+    }
+
     #[test]
     fn test_ld_b_n() {
         let (mut cpu, mut bus) = setup();
         bus.write(0xC000, 0x06);  // LD B, n
         bus.write(0xC001, 0x42);  // n = 0x42
 
-        let cycles = cpu.step(&mut bus);
+        let cycles = cpu.step(&mut bus).unwrap();
 
         assert_eq!(cycles, 8);
         assert_eq!(cpu.regs.b, 0x42);
@@ -830,7 +963,7 @@ mod tests {
         bus.write(0xC001, 0x34);  // low byte
         bus.write(0xC002, 0x12);  // high byte
 
-        let cycles = cpu.step(&mut bus);
+        let cycles = cpu.step(&mut bus).unwrap();
 
         assert_eq!(cycles, 12);
         assert_eq!(cpu.regs.bc(), 0x1234);
@@ -842,7 +975,7 @@ mod tests {
         cpu.regs.a = 0xFF;
         bus.write(0xC000, 0xAF);  // XOR A
 
-        cpu.step(&mut bus);
+        cpu.step(&mut bus).unwrap();
 
         assert_eq!(cpu.regs.a, 0x00);
         assert!(cpu.regs.f.z);
@@ -854,7 +987,7 @@ mod tests {
         cpu.regs.b = 0x0F;
         bus.write(0xC000, 0x04);  // INC B
 
-        cpu.step(&mut bus);
+        cpu.step(&mut bus).unwrap();
 
         assert_eq!(cpu.regs.b, 0x10);
         assert!(!cpu.regs.f.z);
@@ -868,7 +1001,7 @@ mod tests {
         cpu.regs.b = 0x10;
         bus.write(0xC000, 0x05);  // DEC B
 
-        cpu.step(&mut bus);
+        cpu.step(&mut bus).unwrap();
 
         assert_eq!(cpu.regs.b, 0x0F);
         assert!(!cpu.regs.f.z);
@@ -883,7 +1016,7 @@ mod tests {
         bus.write(0xC001, 0x50);
         bus.write(0xC002, 0x01);  // 0x0150
 
-        cpu.step(&mut bus);
+        cpu.step(&mut bus).unwrap();
 
         assert_eq!(cpu.regs.pc, 0x0150);
     }
@@ -894,7 +1027,7 @@ mod tests {
         bus.write(0xC000, 0x18);  // JR n
         bus.write(0xC001, 0x10);  // offset +16
 
-        cpu.step(&mut bus);
+        cpu.step(&mut bus).unwrap();
 
         assert_eq!(cpu.regs.pc, 0xC012);  // 0xC002 + 0x10
     }
@@ -905,7 +1038,7 @@ mod tests {
         bus.write(0xC000, 0x18);  // JR n
         bus.write(0xC001, 0xFE);  // offset -2
 
-        cpu.step(&mut bus);
+        cpu.step(&mut bus).unwrap();
 
         assert_eq!(cpu.regs.pc, 0xC000);  // 0xC002 + (-2) = 0xC000
     }
@@ -918,12 +1051,12 @@ mod tests {
 
         // PUSH BC
         bus.write(0xC000, 0xC5);
-        cpu.step(&mut bus);
+        cpu.step(&mut bus).unwrap();
         assert_eq!(cpu.regs.sp, 0xFFFC);
 
         // POP DE
         bus.write(0xC001, 0xD1);
-        cpu.step(&mut bus);
+        cpu.step(&mut bus).unwrap();
         assert_eq!(cpu.regs.de(), 0x1234);
         assert_eq!(cpu.regs.sp, 0xFFFE);
     }
@@ -937,14 +1070,14 @@ mod tests {
         bus.write(0xC000, 0xCD);
         bus.write(0xC001, 0x00);
         bus.write(0xC002, 0xC1);  // 0xC100
-        cpu.step(&mut bus);
+        cpu.step(&mut bus).unwrap();
 
         assert_eq!(cpu.regs.pc, 0xC100);
         assert_eq!(cpu.regs.sp, 0xFFFC);
 
         // RET (at 0xC100)
         bus.write(0xC100, 0xC9);
-        cpu.step(&mut bus);
+        cpu.step(&mut bus).unwrap();
 
         assert_eq!(cpu.regs.pc, 0xC003);
         assert_eq!(cpu.regs.sp, 0xFFFE);
@@ -957,7 +1090,7 @@ mod tests {
         cpu.regs.b = 0x0F;
         bus.write(0xC000, 0x80);  // ADD A, B
 
-        cpu.step(&mut bus);
+        cpu.step(&mut bus).unwrap();
 
         assert_eq!(cpu.regs.a, 0x4B);
         assert!(!cpu.regs.f.z);
@@ -973,7 +1106,7 @@ mod tests {
         cpu.regs.b = 0x01;
         bus.write(0xC000, 0x90);  // SUB B
 
-        cpu.step(&mut bus);
+        cpu.step(&mut bus).unwrap();
 
         assert_eq!(cpu.regs.a, 0x0F);
         assert!(!cpu.regs.f.z);
@@ -989,10 +1122,244 @@ mod tests {
         cpu.regs.b = 0x10;
         bus.write(0xC000, 0xB8);  // CP B
 
-        cpu.step(&mut bus);
+        cpu.step(&mut bus).unwrap();
 
         assert_eq!(cpu.regs.a, 0x10);  // A unchanged
         assert!(cpu.regs.f.z);  // A == B
         assert!(cpu.regs.f.n);
     }
+
+    #[test]
+    fn test_adc_flags_vectors() {
+        let (mut cpu, _) = setup();
+        // (A, value, carry in) -> (result, Z, H, C), worked out by hand
+        let cases = [
+            (0x00, 0x00, false, 0x00, true, false, false),
+            (0x00, 0x00, true, 0x01, false, false, false),
+            (0x12, 0x34, true, 0x47, false, false, false),
+            (0x0F, 0x01, false, 0x10, false, true, false),
+            (0x0E, 0x01, true, 0x10, false, true, false),
+            (0x00, 0x0F, true, 0x10, false, true, false),
+            (0x0F, 0x0F, true, 0x1F, false, true, false),
+            (0x7F, 0x00, true, 0x80, false, true, false),
+            (0x80, 0x80, false, 0x00, true, false, true),
+            (0xF0, 0x10, false, 0x00, true, false, true),
+            (0xF0, 0x0F, true, 0x00, true, true, true),
+            (0x3A, 0xC6, false, 0x00, true, true, true),
+            (0xFF, 0xFF, true, 0xFF, false, true, true),
+        ];
+        for (a, value, carry, result, z, h, c) in cases {
+            cpu.regs.a = a;
+            cpu.regs.f.c = carry;
+            cpu.adc(value);
+            assert_eq!(
+                (cpu.regs.a, cpu.regs.f.z, cpu.regs.f.h, cpu.regs.f.c),
+                (result, z, h, c),
+                "ADC {:02X}+{:02X}+{}", a, value, carry as u8
+            );
+            assert!(!cpu.regs.f.n);
+        }
+    }
+
+    #[test]
+    fn test_sbc_flags_vectors() {
+        let (mut cpu, _) = setup();
+        // (A, value, carry in) -> (result, Z, H, C), worked out by hand
+        let cases = [
+            (0x00, 0x00, false, 0x00, true, false, false),
+            (0x00, 0x00, true, 0xFF, false, true, true),
+            (0x3B, 0x2A, true, 0x10, false, false, false),
+            (0x10, 0x01, false, 0x0F, false, true, false),
+            (0x80, 0x01, false, 0x7F, false, true, false),
+            (0x10, 0x0F, true, 0x00, true, true, false),
+            (0x0F, 0x0E, true, 0x00, true, false, false),
+            (0x0F, 0x0F, true, 0xFF, false, true, true),
+            (0x00, 0x0F, true, 0xF0, false, true, true),
+            (0x45, 0x50, false, 0xF5, false, false, true),
+            (0xFF, 0xFF, false, 0x00, true, false, false),
+            (0xFF, 0xFF, true, 0xFF, false, true, true),
+        ];
+        for (a, value, carry, result, z, h, c) in cases {
+            cpu.regs.a = a;
+            cpu.regs.f.c = carry;
+            cpu.sbc(value);
+            assert_eq!(
+                (cpu.regs.a, cpu.regs.f.z, cpu.regs.f.h, cpu.regs.f.c),
+                (result, z, h, c),
+                "SBC {:02X}-{:02X}-{}", a, value, carry as u8
+            );
+            assert!(cpu.regs.f.n);
+        }
+    }
+
+    #[test]
+    fn test_adc_sbc_carry_boundary() {
+        let (mut cpu, _) = setup();
+
+        // A=0x00, value=0xFF, carry=1: value + carry overflows a byte
+        cpu.regs.a = 0x00;
+        cpu.regs.f.c = true;
+        cpu.adc(0xFF);
+        assert_eq!(cpu.regs.a, 0x00);
+        assert!(cpu.regs.f.z && cpu.regs.f.h && cpu.regs.f.c);
+
+        cpu.regs.a = 0x00;
+        cpu.regs.f.c = true;
+        cpu.sbc(0xFF);
+        assert_eq!(cpu.regs.a, 0x00);
+        assert!(cpu.regs.f.z && cpu.regs.f.h && cpu.regs.f.c);
+    }
+}
+
+#[cfg(test)]
+mod timing_tests {
+    use super::*;
+
+    /// Conditions in opcode order
+    const CONDITIONS: [&str; 4] = ["NZ", "Z", "NC", "C"];
+
+    /// Set the flags so that condition `index` is true or false
+    fn set_condition(cpu: &mut Cpu, index: usize, taken: bool) {
+        match index {
+            0 => cpu.regs.f.z = !taken,
+            1 => cpu.regs.f.z = taken,
+            2 => cpu.regs.f.c = !taken,
+            _ => cpu.regs.f.c = taken,
+        }
+    }
+
+    /// Execute `bytes` at 0xC000 with the condition set up, returning the cycles
+    fn cycles_for(bytes: &[u8], condition: usize, taken: bool) -> u32 {
+        let mut cpu = Cpu::new();
+        let mut bus = Bus::new();
+        cpu.regs.pc = 0xC000;
+        cpu.regs.sp = 0xDFF0;
+        bus.write16(0xDFF0, 0xC100); // Return address for RET
+        set_condition(&mut cpu, condition, taken);
+        cpu.exec_one(&mut bus, bytes).unwrap()
+    }
+
+    #[test]
+    fn test_conditional_branch_cycles() {
+        // (base opcode, operand bytes, taken cycles, not taken cycles)
+        let families: [(&str, u8, &[u8], u32, u32); 4] = [
+            ("JR", 0x20, &[0x05], 12, 8),
+            ("JP", 0xC2, &[0x00, 0xC1], 16, 12),
+            ("CALL", 0xC4, &[0x00, 0xC1], 24, 12),
+            ("RET", 0xC0, &[], 20, 8),
+        ];
+
+        for (name, base, operands, taken_cycles, not_taken_cycles) in families {
+            for (index, condition) in CONDITIONS.iter().enumerate() {
+                let mut bytes = vec![base + (index as u8) * 8];
+                bytes.extend_from_slice(operands);
+
+                assert_eq!(
+                    cycles_for(&bytes, index, true),
+                    taken_cycles,
+                    "{} {} taken",
+                    name,
+                    condition
+                );
+                assert_eq!(
+                    cycles_for(&bytes, index, false),
+                    not_taken_cycles,
+                    "{} {} not taken",
+                    name,
+                    condition
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_unconditional_branch_cycles() {
+        let cases: [(&str, &[u8], u32); 7] = [
+            ("JR", &[0x18, 0x05], 12),
+            ("JP", &[0xC3, 0x00, 0xC1], 16),
+            ("JP HL", &[0xE9], 4),
+            ("CALL", &[0xCD, 0x00, 0xC1], 24),
+            ("RET", &[0xC9], 16),
+            ("RETI", &[0xD9], 16),
+            ("RST", &[0xFF], 16),
+        ];
+
+        for (name, bytes, expected) in cases {
+            assert_eq!(cycles_for(bytes, 0, true), expected, "{}", name);
+        }
+    }
+}
+
+#[cfg(test)]
+mod flag_tests {
+    use super::*;
+    use crate::rng::Rng;
+
+    /// Runs an ALU helper and returns the 8-bit result
+    /// (A for most, the incremented/decremented value for INC/DEC)
+    type AluOp = fn(&mut Cpu, u8) -> u8;
+
+    /// 8-bit ALU helpers: (name, N flag after the operation, operation)
+    fn alu_ops() -> Vec<(&'static str, bool, AluOp)> {
+        vec![
+            ("ADD", false, |cpu, v| { cpu.add(v); cpu.regs.a }),
+            ("ADC", false, |cpu, v| { cpu.adc(v); cpu.regs.a }),
+            ("SUB", true, |cpu, v| { cpu.sub(v); cpu.regs.a }),
+            ("SBC", true, |cpu, v| { cpu.sbc(v); cpu.regs.a }),
+            ("AND", false, |cpu, v| { cpu.and(v); cpu.regs.a }),
+            ("OR", false, |cpu, v| { cpu.or(v); cpu.regs.a }),
+            ("XOR", false, |cpu, v| { cpu.xor(v); cpu.regs.a }),
+            ("CP", true, |cpu, v| { let a = cpu.regs.a; cpu.cp(v); a.wrapping_sub(v) }),
+            ("INC", false, |cpu, v| cpu.inc(v)),
+            ("DEC", true, |cpu, v| cpu.dec(v)),
+        ]
+    }
+
+    /// Fixed seed so failures reproduce; any value works
+    const OPERAND_SEED: u64 = 42;
+
+    /// Random operands plus the edge values where flag bugs usually hide
+    fn operands(rng: &mut Rng) -> Vec<(u8, u8, bool)> {
+        const EDGES: [u8; 7] = [0x00, 0x01, 0x0F, 0x10, 0x7F, 0x80, 0xFF];
+        let mut cases = Vec::new();
+        for &a in &EDGES {
+            for &v in &EDGES {
+                cases.push((a, v, false));
+                cases.push((a, v, true));
+            }
+        }
+        for _ in 0..2000 {
+            let r = rng.next_u64();
+            cases.push((r as u8, (r >> 8) as u8, r & 0x10000 != 0));
+        }
+        cases
+    }
+
+    #[test]
+    fn test_alu_zero_and_subtract_flags() {
+        let mut rng = Rng::new(OPERAND_SEED);
+        let cases = operands(&mut rng);
+
+        for (name, subtract, op) in alu_ops() {
+            for &(a, value, carry_in) in &cases {
+                let mut cpu = Cpu::new();
+                cpu.regs.a = a;
+                cpu.regs.f.c = carry_in;
+
+                let result = op(&mut cpu, value);
+                assert_eq!(
+                    cpu.regs.f.z,
+                    result == 0,
+                    "{}: A={:02X} value={:02X} C={} -> {:02X}",
+                    name, a, value, carry_in, result
+                );
+                assert_eq!(cpu.regs.f.n, subtract, "{}: A={:02X} value={:02X}", name, a, value);
+
+                // INC/DEC leave the carry alone
+                if name == "INC" || name == "DEC" {
+                    assert_eq!(cpu.regs.f.c, carry_in, "{}: C changed", name);
+                }
+            }
+        }
+    }
 }
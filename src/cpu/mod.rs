@@ -21,16 +21,54 @@ mod registers;
 
 pub use registers::Registers;
 
+use std::fmt;
+
+use crate::interrupts::{InterruptFlags, InterruptKind};
+
+/// Errors from executing an instruction
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CpuError {
+    /// Opcode with no instruction (opcode, address); real hardware locks up
+    UndefinedOpcode(u8, u16),
+}
+
+impl fmt::Display for CpuError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            CpuError::UndefinedOpcode(opcode, addr) => {
+                write!(f, "Undefined opcode: 0x{:02X} at 0x{:04X}", opcode, addr)
+            }
+        }
+    }
+}
+
+impl std::error::Error for CpuError {}
+
 /// The Game Boy CPU
 pub struct Cpu {
     /// CPU registers
     pub regs: Registers,
     /// Halted state - CPU stops executing until interrupt
     pub halted: bool,
+    /// Stopped state (STOP) - CPU and clocks stop until a joypad line goes low
+    pub stopped: bool,
     /// Interrupt Master Enable flag
     pub ime: bool,
     /// IME will be enabled after next instruction (EI delay)
     pub ime_scheduled: bool,
+    /// Optional callback invoked on every interrupt dispatch (kind, pushed PC)
+    interrupt_log: Option<Box<dyn FnMut(InterruptKind, u16)>>,
+    /// Interrupt dispatched by the most recent step, if any
+    last_interrupt: Option<InterruptKind>,
+    /// T-cycles of the most recent step the CPU already ticked the bus for
+    ticked_cycles: u32,
+    /// Deepest allowed interrupt nesting before the warning fires
+    max_interrupt_nesting: usize,
+    /// Optional callback invoked when nesting exceeds the limit (kind, depth)
+    nesting_warning: Option<Box<dyn FnMut(InterruptKind, usize)>>,
+    /// Stack pointer right after each active interrupt's return address was pushed
+    /// (only tracked while the nesting guard is installed)
+    interrupt_frames: Vec<u16>,
 }
 
 impl Cpu {
@@ -38,13 +76,121 @@ impl Cpu {
         Self {
             regs: Registers::new(),
             halted: false,
+            stopped: false,
             ime: false,
             ime_scheduled: false,
+            interrupt_log: None,
+            last_interrupt: None,
+            ticked_cycles: 0,
+            max_interrupt_nesting: 0,
+            nesting_warning: None,
+            interrupt_frames: Vec::new(),
         }
     }
 
+    /// Restore registers, flags, IME and HALT/STOP state to post-boot values
+    /// Memory is not touched; debugging hooks stay installed
+    pub fn reset_registers(&mut self) {
+        self.regs = Registers::new();
+        self.halted = false;
+        self.stopped = false;
+        self.ime = false;
+        self.ime_scheduled = false;
+        self.last_interrupt = None;
+        self.ticked_cycles = 0;
+        self.interrupt_frames.clear();
+    }
+
+    /// Log every interrupt dispatch through the given callback
+    /// The callback receives the interrupt kind and the return address pushed on the stack
+    pub fn set_interrupt_log(&mut self, log: Box<dyn FnMut(InterruptKind, u16)>) {
+        self.interrupt_log = Some(log);
+    }
+
+    /// Stop logging interrupt dispatches
+    pub fn clear_interrupt_log(&mut self) {
+        self.interrupt_log = None;
+    }
+
+    /// Warn through the given callback when interrupt handlers nest deeper than `max`
+    /// The callback receives the interrupt kind and the new nesting depth.
+    /// Re-entering a handler is valid, but usually means it forgot to clear IF.
+    pub fn set_max_interrupt_nesting(&mut self, max: usize, warning: Box<dyn FnMut(InterruptKind, usize)>) {
+        self.max_interrupt_nesting = max;
+        self.nesting_warning = Some(warning);
+        self.interrupt_frames.clear();
+    }
+
+    /// Stop tracking interrupt nesting
+    pub fn clear_max_interrupt_nesting(&mut self) {
+        self.nesting_warning = None;
+        self.interrupt_frames.clear();
+    }
+
+    /// Number of interrupt handlers currently active (0 unless the nesting guard is installed)
+    pub fn interrupt_depth(&self) -> usize {
+        self.interrupt_frames.len()
+    }
+
+    /// Drop handlers whose return address has been popped off the stack
+    fn update_interrupt_depth(&mut self) {
+        while let Some(&frame_sp) = self.interrupt_frames.last() {
+            if self.regs.sp > frame_sp {
+                self.interrupt_frames.pop();
+            } else {
+                break;
+            }
+        }
+    }
+
+    /// Get the interrupt dispatched by the most recent step
+    /// Returns None if that step executed an instruction or idled in HALT
+    pub fn last_step_serviced_interrupt(&self) -> Option<InterruptKind> {
+        self.last_interrupt
+    }
+
+    /// T-cycles of the most recent step that were already applied to the bus
+    /// Interrupt dispatch ticks the bus itself for its first M-cycles; the
+    /// caller should only tick the rest (`cycles - ticked_cycles()`).
+    pub fn ticked_cycles(&self) -> u32 {
+        self.ticked_cycles
+    }
+
+    /// Check if the CPU is halted with no interrupt enabled in IE
+    /// Nothing can wake it then (whatever IME is), so on hardware it stays
+    /// halted forever; run loops can use this to give up early.
+    pub fn halt_with_no_wake_possible(&self, bus: &crate::bus::Bus) -> bool {
+        self.halted && bus.read(0xFFFF) & 0x1F == 0
+    }
+
+    /// Get the interrupts that are both enabled and requested (IE & IF)
+    pub fn pending_interrupts(&self, bus: &crate::bus::Bus) -> InterruptFlags {
+        let mut flags = InterruptFlags::new();
+        flags.from_byte(bus.read(0xFFFF) & bus.read(0xFF0F));
+        flags
+    }
+
+    /// Get the interrupt that would be serviced on the next step, if any
+    /// Requires IME; the highest priority pending interrupt wins
+    pub fn would_service(&self, bus: &crate::bus::Bus) -> Option<InterruptKind> {
+        if !self.ime {
+            return None;
+        }
+        InterruptKind::highest_pending(bus.read(0xFFFF) & bus.read(0xFF0F))
+    }
+
     /// Handle pending interrupts
     /// Returns cycles consumed if an interrupt was handled
+    ///
+    /// Dispatch takes 5 M-cycles:
+    ///   M1-M2: internal delay
+    ///   M3:    push PC high byte
+    ///   M4:    push PC low byte - the vector is chosen here from the current
+    ///          IE & IF, so interrupts raised during M1-M3 can redirect it and
+    ///          a high-byte push that overwrites IE (SP = 0x0000) can cancel
+    ///          it, jumping to 0x0000 without clearing any IF bit
+    ///   M5:    set PC
+    /// The bus is ticked through M3 here so those effects are observable.
     pub fn handle_interrupts(&mut self, bus: &mut crate::bus::Bus) -> u32 {
         let ie = bus.read(0xFFFF);
         let if_reg = bus.read(0xFF0F);
@@ -60,27 +206,49 @@ impl Cpu {
             return 0;
         }
 
-        if let Some((vector, bit)) = crate::interrupts::get_interrupt_vector(ie, if_reg) {
-            // Disable IME
-            self.ime = false;
+        if pending == 0 {
+            return 0;
+        }
 
-            // Clear the interrupt flag
-            bus.write(0xFF0F, if_reg & !bit);
+        self.ime = false;
+        let return_pc = self.regs.pc;
 
-            // Push PC onto stack
-            self.regs.sp = self.regs.sp.wrapping_sub(1);
-            bus.write(self.regs.sp, (self.regs.pc >> 8) as u8);
-            self.regs.sp = self.regs.sp.wrapping_sub(1);
-            bus.write(self.regs.sp, (self.regs.pc & 0xFF) as u8);
+        // M1-M3: two internal cycles, then the high byte push
+        bus.tick(8);
+        self.regs.sp = self.regs.sp.wrapping_sub(1);
+        bus.cpu_write(self.regs.sp, (return_pc >> 8) as u8);
+        bus.tick(4);
+        self.ticked_cycles = 12;
 
-            // Jump to interrupt vector
-            self.regs.pc = vector;
+        // M4: pick the vector from what is pending now, then push the low byte
+        let if_reg = bus.read(0xFF0F);
+        let kind = InterruptKind::highest_pending(bus.read(0xFFFF) & if_reg);
+        self.regs.sp = self.regs.sp.wrapping_sub(1);
+        bus.cpu_write(self.regs.sp, (return_pc & 0xFF) as u8);
 
-            // Interrupt handling takes 20 cycles (5 M-cycles)
+        // M5: jump (cancelled dispatches end up at 0x0000)
+        self.last_interrupt = kind;
+        let Some(kind) = kind else {
+            self.regs.pc = 0x0000;
             return 20;
+        };
+        bus.write(0xFF0F, if_reg & !kind.mask());
+        self.regs.pc = kind.vector();
+
+        if let Some(log) = self.interrupt_log.as_mut() {
+            log(kind, return_pc);
+        }
+
+        if let Some(warning) = self.nesting_warning.as_mut() {
+            self.interrupt_frames.push(self.regs.sp);
+            let depth = self.interrupt_frames.len();
+            if depth > self.max_interrupt_nesting {
+                warning(kind, depth);
+            }
         }
 
-        0
+        // Interrupt handling takes 20 cycles (5 M-cycles)
+        20
     }
 }
 
@@ -104,4 +272,152 @@ mod tests {
         assert!(!cpu.halted);
         assert!(!cpu.ime);
     }
+
+    #[test]
+    fn test_reset_registers() {
+        let mut cpu = Cpu::new();
+        let mut bus = crate::bus::Bus::new();
+
+        cpu.regs.a = 0x42;
+        cpu.regs.set_hl(0xC123);
+        cpu.regs.f.from_byte(0x00);
+        cpu.regs.sp = 0xD000;
+        cpu.regs.pc = 0x1234;
+        cpu.halted = true;
+        cpu.stopped = true;
+        cpu.ime = true;
+        bus.write(0xC000, 0x99);
+
+        // Dispatch an interrupt so there is step state to clear
+        bus.write(0xFFFF, 0x01);
+        bus.write(0xFF0F, 0x01);
+        cpu.stopped = false;
+        cpu.step(&mut bus).unwrap();
+        assert!(cpu.last_step_serviced_interrupt().is_some());
+        assert_ne!(cpu.ticked_cycles(), 0);
+        cpu.stopped = true;
+
+        cpu.reset_registers();
+
+        assert_eq!(cpu.regs.a, 0x01);
+        assert_eq!(cpu.regs.hl(), 0x014D);
+        assert_eq!(cpu.regs.f.to_byte(), 0xB0);
+        assert_eq!(cpu.regs.sp, 0xFFFE);
+        assert_eq!(cpu.regs.pc, 0x0100);
+        assert!(!cpu.halted);
+        assert!(!cpu.stopped);
+        assert!(!cpu.ime);
+        assert_eq!(cpu.last_step_serviced_interrupt(), None);
+        assert_eq!(cpu.ticked_cycles(), 0);
+
+        // Memory is untouched
+        assert_eq!(bus.read(0xC000), 0x99);
+    }
+
+    #[test]
+    fn test_would_service_timer() {
+        let mut cpu = Cpu::new();
+        let mut bus = crate::bus::Bus::new();
+
+        bus.write(0xFFFF, 0x04); // IE: Timer
+        bus.write(0xFF0F, 0x05); // IF: V-Blank (not enabled) and Timer
+
+        let pending = cpu.pending_interrupts(&bus);
+        assert!(pending.timer);
+        assert!(!pending.vblank);
+
+        // Without IME nothing is serviced
+        assert_eq!(cpu.would_service(&bus), None);
+
+        cpu.ime = true;
+        assert_eq!(cpu.would_service(&bus), Some(InterruptKind::Timer));
+    }
+
+    #[test]
+    fn test_stack_writes_wrap_around() {
+        // CALL, RST and interrupt dispatch all write high byte at SP-1,
+        // then low byte at SP-2; from SP=0x0000 that is IE (0xFFFF) and HRAM
+        let programs: [(&str, &[u8]); 2] = [("CALL", &[0xCD, 0x00, 0xC1]), ("RST", &[0xFF])];
+        for (name, bytes) in programs {
+            let mut cpu = Cpu::new();
+            let mut bus = crate::bus::Bus::new();
+            cpu.regs.pc = 0xC234;
+            cpu.regs.sp = 0x0000;
+            cpu.exec_one(&mut bus, bytes).unwrap();
+
+            let ret = 0xC234 + bytes.len() as u16;
+            assert_eq!(cpu.regs.sp, 0xFFFE, "{}", name);
+            assert_eq!(bus.read(0xFFFF), (ret >> 8) as u8, "{}", name);
+            assert_eq!(bus.read(0xFFFE), (ret & 0xFF) as u8, "{}", name);
+        }
+
+        let mut cpu = Cpu::new();
+        let mut bus = crate::bus::Bus::new();
+        cpu.regs.pc = 0x1234;
+        cpu.regs.sp = 0x0000;
+        cpu.ime = true;
+        bus.write(0xFFFF, 0x01);
+        bus.write(0xFF0F, 0x01);
+        assert_eq!(cpu.handle_interrupts(&mut bus), 20);
+
+        // IE = 0x12 no longer enables V-Blank: the dispatch is cancelled
+        assert_eq!(cpu.regs.pc, 0x0000);
+        assert_eq!(cpu.regs.sp, 0xFFFE);
+        assert_eq!(bus.read(0xFFFF), 0x12); // IE overwritten by the high byte
+        assert_eq!(bus.read(0xFFFE), 0x34);
+    }
+
+    #[test]
+    fn test_ie_push_cancels_dispatch() {
+        let mut cpu = Cpu::new();
+        let mut bus = crate::bus::Bus::new();
+
+        // The high byte of PC (0x02) lands in IE, disabling V-Blank
+        cpu.ime = true;
+        cpu.regs.pc = 0x0200;
+        cpu.regs.sp = 0x0000;
+        bus.write(0xFFFF, 0x01);
+        bus.write(0xFF0F, 0x01);
+
+        assert_eq!(cpu.step(&mut bus).unwrap(), 20);
+        assert_eq!(cpu.regs.pc, 0x0000);
+        assert_eq!(cpu.last_step_serviced_interrupt(), None);
+        assert_eq!(bus.read(0xFF0F) & 0x1F, 0x01); // Not acknowledged
+        assert!(!cpu.ime);
+
+        // A high byte that enables another pending interrupt redirects it
+        let mut cpu = Cpu::new();
+        let mut bus = crate::bus::Bus::new();
+        cpu.ime = true;
+        cpu.regs.pc = 0x0400;
+        cpu.regs.sp = 0x0000;
+        bus.write(0xFFFF, 0x01);
+        bus.write(0xFF0F, 0x05);
+
+        cpu.step(&mut bus).unwrap();
+        assert_eq!(cpu.regs.pc, 0x0050);
+        assert_eq!(bus.read(0xFF0F) & 0x1F, 0x01);
+    }
+
+    #[test]
+    fn test_interrupt_raised_during_dispatch() {
+        let mut cpu = Cpu::new();
+        let mut bus = crate::bus::Bus::new();
+
+        // Serial is pending; the timer overflows 8 cycles into the dispatch
+        bus.write(0xFF04, 0);
+        bus.write(0xFF07, 0x05); // 262144 Hz: TIMA ticks every 16 cycles
+        bus.write(0xFF05, 0xFF);
+        bus.tick(8);
+        cpu.ime = true;
+        cpu.regs.pc = 0xC000;
+        bus.write(0xFFFF, 0x0C);
+        bus.write(0xFF0F, 0x08);
+
+        cpu.step(&mut bus).unwrap();
+        assert_eq!(cpu.ticked_cycles(), 12);
+        // The higher-priority timer interrupt wins, serial stays requested
+        assert_eq!(cpu.regs.pc, 0x0050);
+        assert_eq!(bus.read(0xFF0F) & 0x1F, 0x08);
+    }
 }
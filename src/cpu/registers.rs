@@ -62,6 +62,16 @@ impl Flags {
         self.h = (byte & 0x20) != 0;
         self.c = (byte & 0x10) != 0;
     }
+
+    /// Get the flags as (z, n, h, c)
+    pub fn as_tuple(&self) -> (bool, bool, bool, bool) {
+        (self.z, self.n, self.h, self.c)
+    }
+
+    /// Create flags from (z, n, h, c)
+    pub fn from_tuple((z, n, h, c): (bool, bool, bool, bool)) -> Self {
+        Self { z, n, h, c }
+    }
 }
 
 impl Default for Flags {
@@ -206,6 +216,19 @@ mod tests {
         assert!(flags.z && flags.n && flags.h && flags.c);
     }
 
+    #[test]
+    fn test_flags_tuple_round_trip() {
+        let flags = Flags::from_tuple((true, false, true, false));
+        assert_eq!(flags.as_tuple(), (true, false, true, false));
+        assert_eq!(flags.to_byte(), 0xA0);
+
+        for byte in (0x00..=0xF0).step_by(0x10) {
+            let mut flags = Flags::new();
+            flags.from_byte(byte);
+            assert_eq!(Flags::from_tuple(flags.as_tuple()).to_byte(), byte);
+        }
+    }
+
     #[test]
     fn test_register_pairs() {
         let mut regs = Registers::new();
@@ -0,0 +1,330 @@
+// Disassembler
+//
+// Decodes SM83 machine code into human-readable mnemonics without executing it.
+//
+// Base opcodes are decoded from their bit fields:
+//   Bits 7-6: x (instruction block)
+//   Bits 5-3: y (destination register / condition / ALU operation)
+//   Bits 2-0: z (source register / sub-operation)
+//
+// Register index order (same as the CB handler): B, C, D, E, H, L, (HL), A
+//
+// Output conventions:
+//   - Immediates are hexadecimal ("LD A, 0x42", "LD HL, 0xC000")
+//   - JR offsets are resolved to absolute targets ("JR NZ, 0x0150")
+//   - LDH operands are shown as full addresses ("LDH (0xFF01), A")
+//   - Undefined opcodes are shown as data bytes ("DB 0xD3")
+
+use crate::bus::Bus;
+
+/// 8-bit register names by index
+const REGS: [&str; 8] = ["B", "C", "D", "E", "H", "L", "(HL)", "A"];
+
+/// 16-bit register pairs for LD/INC/DEC/ADD HL
+const PAIRS_SP: [&str; 4] = ["BC", "DE", "HL", "SP"];
+
+/// 16-bit register pairs for PUSH/POP
+const PAIRS_AF: [&str; 4] = ["BC", "DE", "HL", "AF"];
+
+/// Branch conditions
+const CONDITIONS: [&str; 4] = ["NZ", "Z", "NC", "C"];
+
+/// ALU operations (0x80-0xBF and the immediate forms)
+const ALU: [&str; 8] = ["ADD A,", "ADC A,", "SUB", "SBC A,", "AND", "XOR", "OR", "CP"];
+
+/// CB rotate/shift operations (0x00-0x3F)
+const ROTATES: [&str; 8] = ["RLC", "RRC", "RL", "RR", "SLA", "SRA", "SWAP", "SRL"];
+
+/// Format a signed 8-bit offset as "+0x05" / "-0x02"
+fn signed_hex(value: u8) -> String {
+    let offset = value as i8;
+    if offset < 0 {
+        format!("-0x{:02X}", (offset as i16).unsigned_abs())
+    } else {
+        format!("+0x{:02X}", offset)
+    }
+}
+
+/// Disassemble one instruction at the given address
+///
+/// Returns the mnemonic and the instruction length in bytes.
+/// Memory is read with `Bus::peek`, so disassembling never changes state.
+pub fn disassemble(bus: &Bus, addr: u16) -> (String, u16) {
+    let opcode = bus.peek(addr);
+    let n = bus.peek(addr.wrapping_add(1));
+    let nn = (bus.peek(addr.wrapping_add(2)) as u16) << 8 | n as u16;
+
+    let x = opcode >> 6;
+    let y = ((opcode >> 3) & 0x07) as usize;
+    let z = (opcode & 0x07) as usize;
+    let p = y >> 1;
+    let q = y & 1;
+
+    match x {
+        0 => match z {
+            0 => match y {
+                0 => ("NOP".to_string(), 1),
+                1 => (format!("LD (0x{:04X}), SP", nn), 3),
+                2 => ("STOP".to_string(), 2),
+                3 => {
+                    let target = addr.wrapping_add(2).wrapping_add(n as i8 as u16);
+                    (format!("JR 0x{:04X}", target), 2)
+                }
+                _ => {
+                    let target = addr.wrapping_add(2).wrapping_add(n as i8 as u16);
+                    (format!("JR {}, 0x{:04X}", CONDITIONS[y - 4], target), 2)
+                }
+            },
+            1 => {
+                if q == 0 {
+                    (format!("LD {}, 0x{:04X}", PAIRS_SP[p], nn), 3)
+                } else {
+                    (format!("ADD HL, {}", PAIRS_SP[p]), 1)
+                }
+            }
+            2 => {
+                let mem = ["(BC)", "(DE)", "(HL+)", "(HL-)"][p];
+                if q == 0 {
+                    (format!("LD {}, A", mem), 1)
+                } else {
+                    (format!("LD A, {}", mem), 1)
+                }
+            }
+            3 => {
+                let op = if q == 0 { "INC" } else { "DEC" };
+                (format!("{} {}", op, PAIRS_SP[p]), 1)
+            }
+            4 => (format!("INC {}", REGS[y]), 1),
+            5 => (format!("DEC {}", REGS[y]), 1),
+            6 => (format!("LD {}, 0x{:02X}", REGS[y], n), 2),
+            _ => {
+                let op = ["RLCA", "RRCA", "RLA", "RRA", "DAA", "CPL", "SCF", "CCF"][y];
+                (op.to_string(), 1)
+            }
+        },
+
+        1 => {
+            if opcode == 0x76 {
+                ("HALT".to_string(), 1)
+            } else {
+                (format!("LD {}, {}", REGS[y], REGS[z]), 1)
+            }
+        }
+
+        2 => (format!("{} {}", ALU[y], REGS[z]), 1),
+
+        _ => match opcode {
+            0xC0 | 0xC8 | 0xD0 | 0xD8 => (format!("RET {}", CONDITIONS[y]), 1),
+            0xC2 | 0xCA | 0xD2 | 0xDA => (format!("JP {}, 0x{:04X}", CONDITIONS[y], nn), 3),
+            0xC4 | 0xCC | 0xD4 | 0xDC => (format!("CALL {}, 0x{:04X}", CONDITIONS[y], nn), 3),
+            0xC1 | 0xD1 | 0xE1 | 0xF1 => (format!("POP {}", PAIRS_AF[p]), 1),
+            0xC5 | 0xD5 | 0xE5 | 0xF5 => (format!("PUSH {}", PAIRS_AF[p]), 1),
+            0xC6 | 0xCE | 0xD6 | 0xDE | 0xE6 | 0xEE | 0xF6 | 0xFE => {
+                (format!("{} 0x{:02X}", ALU[y], n), 2)
+            }
+            0xC7 | 0xCF | 0xD7 | 0xDF | 0xE7 | 0xEF | 0xF7 | 0xFF => {
+                (format!("RST 0x{:02X}", y * 8), 1)
+            }
+            0xC3 => (format!("JP 0x{:04X}", nn), 3),
+            0xC9 => ("RET".to_string(), 1),
+            0xCB => (disassemble_cb(n), 2),
+            0xCD => (format!("CALL 0x{:04X}", nn), 3),
+            0xD9 => ("RETI".to_string(), 1),
+            0xE0 => (format!("LDH (0x{:04X}), A", 0xFF00 | n as u16), 2),
+            0xE2 => ("LD (0xFF00+C), A".to_string(), 1),
+            0xE8 => (format!("ADD SP, {}", signed_hex(n)), 2),
+            0xE9 => ("JP HL".to_string(), 1),
+            0xEA => (format!("LD (0x{:04X}), A", nn), 3),
+            0xF0 => (format!("LDH A, (0x{:04X})", 0xFF00 | n as u16), 2),
+            0xF2 => ("LD A, (0xFF00+C)".to_string(), 1),
+            0xF3 => ("DI".to_string(), 1),
+            0xF8 => (format!("LD HL, SP{}", signed_hex(n)), 2),
+            0xF9 => ("LD SP, HL".to_string(), 1),
+            0xFA => (format!("LD A, (0x{:04X})", nn), 3),
+            0xFB => ("EI".to_string(), 1),
+
+            // Undefined opcodes (0xD3, 0xDB, 0xDD, 0xE3, 0xE4, 0xEB, 0xEC, 0xED, 0xF4, 0xFC, 0xFD)
+            _ => (format!("DB 0x{:02X}", opcode), 1),
+        },
+    }
+}
+
+/// One decoded instruction, as shown by a step debugger
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DecodedInstruction {
+    /// Address of the opcode
+    pub address: u16,
+    /// Mnemonic as produced by `disassemble`
+    pub mnemonic: String,
+    /// Opcode and operand bytes
+    pub bytes: Vec<u8>,
+    /// Destination of JP/JR/CALL/RST (taken or not), from the immediate operand
+    /// None for other instructions and for JP HL, whose target is a register
+    pub target: Option<u16>,
+}
+
+/// Decode the instruction at the given address with its raw bytes and jump target
+/// Reads go through `Bus::peek`, which has no side effects and ignores OAM DMA.
+pub fn decode(bus: &Bus, addr: u16) -> DecodedInstruction {
+    let (mnemonic, len) = disassemble(bus, addr);
+    let bytes: Vec<u8> = (0..len).map(|i| bus.peek(addr.wrapping_add(i))).collect();
+
+    let opcode = bytes[0];
+    let target = match opcode {
+        // JR e / JR cc, e: relative to the next instruction
+        0x18 | 0x20 | 0x28 | 0x30 | 0x38 => {
+            Some(addr.wrapping_add(2).wrapping_add(bytes[1] as i8 as u16))
+        }
+        // JP nn / JP cc, nn / CALL nn / CALL cc, nn
+        0xC2 | 0xC3 | 0xC4 | 0xCA | 0xCC | 0xCD | 0xD2 | 0xD4 | 0xDA | 0xDC => {
+            Some((bytes[2] as u16) << 8 | bytes[1] as u16)
+        }
+        // RST n
+        0xC7 | 0xCF | 0xD7 | 0xDF | 0xE7 | 0xEF | 0xF7 | 0xFF => Some((opcode & 0x38) as u16),
+        _ => None,
+    };
+
+    DecodedInstruction {
+        address: addr,
+        mnemonic,
+        bytes,
+        target,
+    }
+}
+
+/// Decode a CB-prefixed opcode (the byte after 0xCB)
+fn disassemble_cb(opcode: u8) -> String {
+    let bit = (opcode >> 3) & 0x07;
+    let reg = REGS[(opcode & 0x07) as usize];
+
+    match opcode >> 6 {
+        0 => format!("{} {}", ROTATES[bit as usize], reg),
+        1 => format!("BIT {}, {}", bit, reg),
+        2 => format!("RES {}, {}", bit, reg),
+        _ => format!("SET {}, {}", bit, reg),
+    }
+}
+
+/// Disassemble a contiguous memory range (start..=end) instruction by instruction
+///
+/// Returns (address, mnemonic) pairs. Decoding stops at the range end; an
+/// instruction whose operands would extend past `end` is not included.
+pub fn linear_sweep(bus: &Bus, start: u16, end: u16) -> Vec<(u16, String)> {
+    let mut listing = Vec::new();
+    let mut addr = start as u32;
+
+    while addr <= end as u32 {
+        let (mnemonic, len) = disassemble(bus, addr as u16);
+        if addr + len as u32 - 1 > end as u32 {
+            break;
+        }
+        listing.push((addr as u16, mnemonic));
+        addr += len as u32;
+    }
+
+    listing
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn bus_with_program(program: &[u8]) -> Bus {
+        let mut rom = vec![0u8; 0x8000];
+        rom[0x0100..0x0100 + program.len()].copy_from_slice(program);
+        let mut bus = Bus::new();
+        bus.load_rom(&rom);
+        bus
+    }
+
+    #[test]
+    fn test_linear_sweep() {
+        let bus = bus_with_program(&[
+            0x3E, 0x42,       // LD A, 0x42
+            0x21, 0x00, 0xC0, // LD HL, 0xC000
+            0xCB, 0x7C,       // BIT 7, H
+            0x20, 0xFA,       // JR NZ, -6
+            0xE0, 0x01,       // LDH (0x01), A
+            0x76,             // HALT
+        ]);
+
+        let listing = linear_sweep(&bus, 0x0100, 0x010B);
+        let mnemonics: Vec<&str> = listing.iter().map(|(_, m)| m.as_str()).collect();
+
+        assert_eq!(
+            mnemonics,
+            vec![
+                "LD A, 0x42",
+                "LD HL, 0xC000",
+                "BIT 7, H",
+                "JR NZ, 0x0103",
+                "LDH (0xFF01), A",
+                "HALT",
+            ]
+        );
+        let addresses: Vec<u16> = listing.iter().map(|(a, _)| *a).collect();
+        assert_eq!(addresses, vec![0x0100, 0x0102, 0x0105, 0x0107, 0x0109, 0x010B]);
+    }
+
+    #[test]
+    fn test_linear_sweep_stops_at_range_end() {
+        let bus = bus_with_program(&[
+            0x00,             // NOP
+            0xC3, 0x50, 0x01, // JP 0x0150
+        ]);
+
+        // The range ends in the middle of JP nn
+        let listing = linear_sweep(&bus, 0x0100, 0x0102);
+        assert_eq!(listing, vec![(0x0100, "NOP".to_string())]);
+    }
+
+    #[test]
+    fn test_decode_jump_targets() {
+        let bus = bus_with_program(&[
+            0xC3, 0x50, 0x01, // JP 0x0150
+            0x20, 0xFB,       // JR NZ, -5
+            0xFF,             // RST 0x38
+            0x3E, 0x42,       // LD A, 0x42
+        ]);
+
+        let jp = decode(&bus, 0x0100);
+        assert_eq!(jp.mnemonic, "JP 0x0150");
+        assert_eq!(jp.bytes, vec![0xC3, 0x50, 0x01]);
+        assert_eq!(jp.target, Some(0x0150));
+
+        assert_eq!(decode(&bus, 0x0103).target, Some(0x0100));
+        assert_eq!(decode(&bus, 0x0105).target, Some(0x0038));
+
+        let ld = decode(&bus, 0x0106);
+        assert_eq!(ld.bytes, vec![0x3E, 0x42]);
+        assert_eq!(ld.target, None);
+    }
+
+    #[test]
+    fn test_disassemble_lengths() {
+        let bus = bus_with_program(&[
+            0xCB, 0x37,       // SWAP A
+            0x38, 0x10,       // JR C, +16
+            0x01, 0x34, 0x12, // LD BC, 0x1234
+            0xFA, 0xCD, 0xAB, // LD A, (0xABCD)
+            0x00,             // NOP
+        ]);
+
+        assert_eq!(disassemble(&bus, 0x0100), ("SWAP A".to_string(), 2));
+        assert_eq!(disassemble(&bus, 0x0102), ("JR C, 0x0114".to_string(), 2));
+        assert_eq!(disassemble(&bus, 0x0104), ("LD BC, 0x1234".to_string(), 3));
+        assert_eq!(disassemble(&bus, 0x0107), ("LD A, (0xABCD)".to_string(), 3));
+        assert_eq!(disassemble(&bus, 0x010A), ("NOP".to_string(), 1));
+    }
+
+    #[test]
+    fn test_decode_ignores_oam_dma() {
+        let mut bus = bus_with_program(&[0xCD, 0x00, 0x20]); // CALL 0x2000
+        bus.write(0xFF46, 0xC0);
+
+        let instruction = decode(&bus, 0x0100);
+        assert_eq!(instruction.mnemonic, "CALL 0x2000");
+        assert_eq!(instruction.bytes, vec![0xCD, 0x00, 0x20]);
+        assert_eq!(instruction.target, Some(0x2000));
+    }
+}
@@ -3,9 +3,21 @@
 // This module ties together all components (CPU, Bus, etc.) and
 // provides the main emulation loop.
 
+use std::fmt::Write;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::bess::{self, BessWriter, CoreState, ExecutionState};
 use crate::bus::Bus;
 use crate::cartridge::Cartridge;
-use crate::cpu::Cpu;
+use crate::cpu::{Cpu, CpuError, Registers};
+use crate::disasm::{self, DecodedInstruction};
+use crate::interrupts::InterruptKind;
+use crate::mbc::Rtc;
+use crate::ppu::{Ppu, DOTS_PER_LINE};
+use crate::rng::Rng;
+
+/// T-cycles per frame (154 scanlines * 456 dots)
+pub const CYCLES_PER_FRAME: u64 = 70224;
 
 /// The main emulator structure
 pub struct Emulator {
@@ -13,64 +25,371 @@ pub struct Emulator {
     pub bus: Bus,
     /// Total cycles executed
     pub cycles: u64,
+    /// Serial output marker for a passing test ROM (case-sensitive)
+    pass_marker: String,
+    /// Serial output marker for a failing test ROM (case-sensitive)
+    fail_marker: String,
+    /// Host clock in UNIX seconds (used to timestamp RTC saves)
+    host_clock: Box<dyn Fn() -> u64>,
+    /// Stop the run loops once the CPU is stuck in a self-loop
+    exit_on_self_loop: bool,
+    /// Stop the run loops once the CPU is halted with nothing able to wake it
+    exit_on_dead_halt: bool,
+    /// The last instruction jumped to itself with no interrupt able to break out
+    self_loop: bool,
+    /// Source of all emulated randomness (unused until set_seed)
+    rng: Rng,
+    /// Paused: step and all run methods do nothing
+    paused: bool,
+    /// The CPU hit an instruction it can't execute and stopped
+    cpu_error: Option<CpuError>,
+    /// Cycle count at each V-Blank entry (None = not recording)
+    frame_cycle_log: Option<Vec<u64>>,
+    /// Serial bytes already returned by poll_new_serial (counted like output_total)
+    serial_cursor: usize,
+    /// Callback invoked when hardware requests an interrupt (kind, cycle)
+    interrupt_pending_hook: Option<Box<dyn FnMut(InterruptKind, u64)>>,
+}
+
+/// Current host time in UNIX seconds
+fn system_clock() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
 }
 
 impl Emulator {
     /// Create a new emulator with a loaded cartridge
     pub fn new(cartridge: &Cartridge) -> Self {
-        let bus = Bus::with_cartridge(
+        Self::with_bus(Bus::with_cartridge(
             cartridge.info.cartridge_type_byte,
             cartridge.rom.clone(),
             cartridge.info.ram_size,
-        );
-
-        Self {
-            cpu: Cpu::new(),
-            bus,
-            cycles: 0,
-        }
+        ))
     }
 
     /// Create a new emulator with raw ROM data
     pub fn with_rom(rom: &[u8]) -> Self {
         let mut bus = Bus::new();
         bus.load_rom(rom);
+        Self::with_bus(bus)
+    }
 
+    /// Power-on emulator state around a prepared bus (shared by the constructors)
+    fn with_bus(bus: Bus) -> Self {
         Self {
             cpu: Cpu::new(),
             bus,
             cycles: 0,
+            pass_marker: "Passed".to_string(),
+            fail_marker: "Failed".to_string(),
+            host_clock: Box::new(system_clock),
+            exit_on_self_loop: false,
+            exit_on_dead_halt: false,
+            self_loop: false,
+            rng: Rng::new(0),
+            paused: false,
+            cpu_error: None,
+            frame_cycle_log: None,
+            serial_cursor: 0,
+            interrupt_pending_hook: None,
         }
     }
 
+    /// Pause emulation: step and the run methods return without executing
+    pub fn pause(&mut self) {
+        self.paused = true;
+    }
+
+    /// Resume emulation after pause()
+    pub fn resume(&mut self) {
+        self.paused = false;
+    }
+
+    /// Check if emulation is paused
+    pub fn is_paused(&self) -> bool {
+        self.paused
+    }
+
+    /// Error that stopped the CPU (e.g. an undefined opcode), if any
+    /// Like real hardware, the CPU stays locked up: step and the run methods
+    /// do nothing from then on.
+    pub fn cpu_error(&self) -> Option<CpuError> {
+        self.cpu_error
+    }
+
+    /// Replace the post-boot register values before the first step
+    /// For CPU test suites that expect a different starting state
+    /// (e.g. all zeros, or the gameboy-doctor initial state). Takes effect
+    /// immediately, so traces and debugger views see the new values.
+    pub fn set_initial_registers(&mut self, regs: Registers) {
+        self.cpu.regs = regs;
+    }
+
+    /// Neither paused nor stopped by a CPU error
+    fn can_run(&self) -> bool {
+        !self.paused && self.cpu_error.is_none()
+    }
+
+    /// Run loop condition: can_run, and not given up on a self-loop or dead HALT
+    /// (per `set_exit_on_self_loop` / `set_exit_on_dead_halt`). Front-ends
+    /// that call `step` themselves should loop while this holds.
+    pub fn keep_running(&self) -> bool {
+        self.can_run()
+            && !(self.exit_on_self_loop && self.self_loop)
+            && !(self.exit_on_dead_halt && self.cpu.halt_with_no_wake_possible(&self.bus))
+    }
+
     /// Execute one CPU instruction
+    /// Returns 0 without executing anything while paused or after a CPU error
     pub fn step(&mut self) -> u32 {
-        let cycles = self.cpu.step(&mut self.bus);
-        // Update timer and other hardware
-        self.bus.tick(cycles);
-        self.cycles += cycles as u64;
+        if !self.can_run() {
+            return 0;
+        }
+        self.bus.set_cycles(self.cycles);
+        let pc = self.cpu.regs.pc;
+        let cycles = match self.cpu.step(&mut self.bus) {
+            Ok(cycles) => cycles,
+            Err(error) => {
+                self.cpu_error = Some(error);
+                return 0;
+            }
+        };
+        let ticked = self.cpu.ticked_cycles();
+        if ticked > 0 {
+            self.report_raised_interrupts(self.cycles.saturating_add(ticked as u64));
+        }
+        self.self_loop = self.cpu.regs.pc == pc
+            && !self.cpu.halted
+            && !self.cpu.stopped
+            && (!self.cpu.ime || self.bus.read(0xFFFF) & 0x1F == 0);
+        // Update timer and other hardware (their clock is stopped by STOP)
+        if !self.cpu.stopped {
+            self.bus.tick(cycles - ticked);
+        }
+        self.cycles = self.cycles.saturating_add(cycles as u64);
+        self.report_raised_interrupts(self.cycles);
+        if self.bus.ppu.vblank_interrupt {
+            self.log_vblank();
+        }
         cycles
     }
 
-    /// Run until the CPU halts or reaches max cycles
-    pub fn run_until_halt(&mut self, max_cycles: u64) -> bool {
-        while !self.cpu.halted && self.cycles < max_cycles {
+    /// Report IF bits raised by hardware to the pending hook, if installed
+    /// `cycle` is the cycle count at the end of the bus tick that raised them.
+    fn report_raised_interrupts(&mut self, cycle: u64) {
+        let raised = self.bus.take_raised_interrupts();
+        if let Some(hook) = self.interrupt_pending_hook.as_mut() {
+            for kind in InterruptKind::ALL {
+                if raised & kind.mask() != 0 {
+                    hook(kind, cycle);
+                }
+            }
+        }
+    }
+
+    /// Call `hook` with the interrupt kind and cycle count whenever timer,
+    /// PPU, serial or joypad hardware sets an IF bit
+    pub fn set_interrupt_pending_hook(&mut self, hook: Box<dyn FnMut(InterruptKind, u64)>) {
+        self.interrupt_pending_hook = Some(hook);
+    }
+
+    /// Remove the interrupt pending hook
+    pub fn clear_interrupt_pending_hook(&mut self) {
+        self.interrupt_pending_hook = None;
+    }
+
+    /// Record the exact cycle V-Blank started at, if frame logging is on
+    /// The PPU has run `dot` dots of line 144 by the end of the step.
+    fn log_vblank(&mut self) {
+        if let Some(log) = self.frame_cycle_log.as_mut() {
+            let dots = self.bus.ppu.dot() as u64;
+            let cycles = if self.bus.double_speed() { dots * 2 } else { dots };
+            log.push(self.cycles - cycles);
+        }
+    }
+
+    /// Start or stop recording the cycle count at each V-Blank (clears the log)
+    /// Consecutive entries should be exactly one frame apart; anything else
+    /// points at a PPU timing bug.
+    pub fn set_frame_cycle_logging(&mut self, enabled: bool) {
+        self.frame_cycle_log = if enabled { Some(Vec::new()) } else { None };
+    }
+
+    /// Cycle counts at which V-Blank started (empty unless logging is on)
+    pub fn frame_cycle_log(&self) -> &[u64] {
+        self.frame_cycle_log.as_deref().unwrap_or(&[])
+    }
+
+    /// The current 160x144 frame as palette indices (0-3), row by row
+    pub fn framebuffer(&self) -> &[u8] {
+        &self.bus.ppu.framebuffer
+    }
+
+    /// Decode the instruction at PC without executing it (for step debuggers)
+    pub fn current_instruction(&self) -> DecodedInstruction {
+        disasm::decode(&self.bus, self.cpu.regs.pc)
+    }
+
+    /// Disassemble up to `before` instructions before PC and `after` instructions
+    /// after it, as (address, mnemonic, is_current) for a debugger's code pane
+    /// Instruction lengths vary, so the lines before PC are a guess: decode
+    /// forward from the farthest start (up to 3 bytes per instruction back)
+    /// whose instruction chain lands exactly on PC. None are shown if no start does.
+    pub fn disasm_window(&self, before: usize, after: usize) -> Vec<(u16, String, bool)> {
+        let pc = self.cpu.regs.pc;
+        let mut lines = Vec::new();
+
+        let max_back = before.saturating_mul(3).min(pc as usize) as u16;
+        for back in (1..=max_back).rev() {
+            let mut addr = Some(pc - back);
+            let mut chain = Vec::new();
+            while let Some(at) = addr.filter(|&at| at < pc) {
+                let (mnemonic, len) = disasm::disassemble(&self.bus, at);
+                chain.push((at, mnemonic, false));
+                addr = at.checked_add(len);
+            }
+            if addr == Some(pc) {
+                let skip = chain.len().saturating_sub(before);
+                lines.extend(chain.into_iter().skip(skip));
+                break;
+            }
+        }
+
+        let mut addr = pc;
+        for i in 0..=after {
+            let (mnemonic, len) = disasm::disassemble(&self.bus, addr);
+            lines.push((addr, mnemonic, i == 0));
+            addr = addr.wrapping_add(len);
+        }
+        lines
+    }
+
+    /// Absolute cycle cap for a run loop `budget` cycles from now
+    /// Saturates at u64::MAX, where the cycle count stops advancing; a loop
+    /// capped there only ends by its own condition (halt, pause, CPU error).
+    pub fn cycle_cap(&self, budget: u64) -> u64 {
+        self.cycles.saturating_add(budget)
+    }
+
+    /// Run until the CPU halts or `budget` more cycles have run
+    /// Like every run loop, the budget is relative to the current cycle count
+    /// (see `cycle_cap`), so it can't already be used up.
+    pub fn run_until_halt(&mut self, budget: u64) -> bool {
+        let max_cycles = self.cycle_cap(budget);
+        while !self.cpu.halted && self.cycles < max_cycles && self.keep_running() {
             self.step();
         }
         self.cpu.halted
     }
 
     /// Run for a specific number of cycles
+    /// Budgets past u64::MAX are clamped (see `cycle_cap`).
     pub fn run_cycles(&mut self, cycles: u64) {
-        let target = self.cycles + cycles;
-        while self.cycles < target && !self.cpu.halted {
+        let target = self.cycle_cap(cycles);
+        while self.cycles < target && !self.cpu.halted && self.keep_running() {
+            self.step();
+        }
+    }
+
+    /// Current cycle count, to be passed to `cycles_since` later
+    pub fn checkpoint_cycles(&self) -> u64 {
+        self.cycles
+    }
+
+    /// Cycles executed since a checkpoint taken with `checkpoint_cycles`
+    /// A checkpoint ahead of the current count (e.g. after `cycles` was
+    /// reassigned, or taken on another emulator) gives 0.
+    pub fn cycles_since(&self, checkpoint: u64) -> u64 {
+        self.cycles.saturating_sub(checkpoint)
+    }
+
+    /// CPU cycles per frame at the current speed
+    /// (the PPU always takes 70224 dots, which is twice as many CPU cycles in double speed)
+    pub fn cycles_per_frame(&self) -> u64 {
+        if self.bus.double_speed() {
+            CYCLES_PER_FRAME * 2
+        } else {
+            CYCLES_PER_FRAME
+        }
+    }
+
+    /// Run until the PPU enters V-Blank (one complete frame)
+    /// Gives up after two frames' worth of cycles if no V-Blank occurs.
+    /// With the LCD off there are no V-Blanks, so one frame's worth of
+    /// cycles is run instead to keep frame-based loops progressing.
+    /// Returns the number of cycles executed
+    pub fn run_frame(&mut self) -> u64 {
+        let start = self.cycles;
+        if !self.bus.ppu.lcdc.lcd_enable() {
+            let target = self.cycle_cap(self.cycles_per_frame());
+            while self.cycles < target && self.keep_running() {
+                self.step();
+            }
+            return self.cycles - start;
+        }
+
+        let limit = self.cycle_cap(self.cycles_per_frame() * 2);
+        while self.cycles < limit && self.keep_running() {
             self.step();
+            if self.bus.ppu.vblank_interrupt {
+                break;
+            }
+        }
+        self.cycles - start
+    }
+
+    /// Configure the PPU with `setup`, then render one complete frame with the
+    /// PPU alone (no CPU, timer or interrupts) and return the framebuffer
+    /// Meant for fast, isolated PPU tests. The LCD must be left on.
+    pub fn render_test_scene(&mut self, setup: impl FnOnce(&mut Ppu)) -> Vec<u8> {
+        let ppu = &mut self.bus.ppu;
+        setup(ppu);
+
+        // Finish the frame in progress, then render a whole one
+        ppu.frame_ready();
+        for _ in 0..2 {
+            let mut dots = 0;
+            while !ppu.frame_ready() && dots < CYCLES_PER_FRAME {
+                ppu.tick(DOTS_PER_LINE);
+                dots += DOTS_PER_LINE as u64;
+            }
         }
+        ppu.framebuffer.to_vec()
     }
 
-    /// Run until serial output contains a specific string or max cycles reached
-    pub fn run_until_serial_contains(&mut self, needle: &str, max_cycles: u64) -> bool {
-        while self.cycles < max_cycles && !self.cpu.halted {
+    /// Run frames until the framebuffer is identical for two consecutive frames
+    /// Returns the stable framebuffer, or None if it still changed after max_frames
+    pub fn boot_to_stable_frame(&mut self, max_frames: u32) -> Option<Vec<u8>> {
+        self.run_frame();
+        let mut previous = self.bus.ppu.framebuffer.to_vec();
+
+        for _ in 1..max_frames {
+            self.run_frame();
+            if self.bus.ppu.framebuffer[..] == previous[..] {
+                return Some(previous);
+            }
+            previous.copy_from_slice(&self.bus.ppu.framebuffer);
+        }
+        None
+    }
+
+    /// Run `count` frames and return a copy of the framebuffer after each one
+    /// Each frame is 160x144 bytes (23KB); keeping many frames is the caller's concern.
+    pub fn capture_frames(&mut self, count: usize) -> Vec<Vec<u8>> {
+        let mut frames = Vec::with_capacity(count);
+        for _ in 0..count {
+            self.run_frame();
+            frames.push(self.bus.ppu.framebuffer.to_vec());
+        }
+        frames
+    }
+
+    /// Run until serial output contains a specific string or `budget` more cycles have run
+    pub fn run_until_serial_contains(&mut self, needle: &str, budget: u64) -> bool {
+        let max_cycles = self.cycle_cap(budget);
+        while self.cycles < max_cycles && !self.cpu.halted && self.keep_running() {
             self.step();
             if self.bus.get_serial_output().contains(needle) {
                 return true;
@@ -79,27 +398,313 @@ impl Emulator {
         false
     }
 
+    /// Run until the program writes to `addr` or `budget` more cycles have run
+    /// Returns the value written, or None on timeout
+    pub fn run_until_write(&mut self, addr: u16, budget: u64) -> Option<u8> {
+        let max_cycles = self.cycle_cap(budget);
+        self.bus.set_write_watch(Some(addr));
+        let mut written = None;
+        while written.is_none() && self.cycles < max_cycles && self.keep_running() {
+            self.step();
+            written = self.bus.take_watch_hit();
+        }
+        self.bus.set_write_watch(None);
+        written
+    }
+
+    /// Stop the run loops when the CPU enters a PC-stationary loop (e.g. `jr -2`)
+    /// with no enabled interrupts, as test ROMs do after printing their result
+    pub fn set_exit_on_self_loop(&mut self, enabled: bool) {
+        self.exit_on_self_loop = enabled;
+    }
+
+    /// Stop the run loops when the CPU halts with IE = 0 (nothing can wake it)
+    /// Distinct from a self-loop: the CPU is halted rather than spinning.
+    pub fn set_exit_on_dead_halt(&mut self, enabled: bool) {
+        self.exit_on_dead_halt = enabled;
+    }
+
+    /// Check if the last instruction left the CPU stuck in a self-loop
+    pub fn in_self_loop(&self) -> bool {
+        self.self_loop
+    }
+
+    /// Check if the machine will make no further progress: the CPU is halted
+    /// with no interrupt enabled in IE to wake it, or stuck in a self-loop
+    /// Front-ends can stop stepping when this is true.
+    pub fn is_idle(&self) -> bool {
+        if self.cpu.halted {
+            self.cpu.halt_with_no_wake_possible(&self.bus)
+        } else {
+            self.self_loop
+        }
+    }
+
+    /// Run until no serial transfer is in progress (SC bit 7 clear)
+    /// Returns true if the transfer finished within `budget` more cycles
+    pub fn run_until_serial_idle(&mut self, budget: u64) -> bool {
+        let max_cycles = self.cycle_cap(budget);
+        while self.bus.serial.transfer_in_progress() {
+            if self.cycles >= max_cycles || !self.keep_running() {
+                return false;
+            }
+            self.step();
+        }
+        true
+    }
+
+    /// Seed the emulator's random number generator and randomize RAM
+    /// with a power-on pattern, as real hardware does
+    /// Without a seed, RAM starts zeroed and runs are fully deterministic.
+    pub fn set_seed(&mut self, seed: u64) {
+        self.rng = Rng::new(seed);
+        self.bus.randomize_ram(&mut self.rng);
+    }
+
+    /// The shared random number generator
+    pub fn rng(&mut self) -> &mut Rng {
+        &mut self.rng
+    }
+
+    /// Replace the host clock (UNIX seconds) used for RTC save timestamps
+    pub fn set_host_clock(&mut self, clock: Box<dyn Fn() -> u64>) {
+        self.host_clock = clock;
+    }
+
+    /// Serialize battery-backed state: cartridge RAM, followed by the 48-byte
+    /// RTC block (VBA-M/BGB layout) for cartridges with a real-time clock
+    pub fn save_ram(&self) -> Vec<u8> {
+        let mbc = self.bus.mbc();
+        let mut data = mbc.ram().to_vec();
+        if let Some(rtc) = mbc.rtc() {
+            data.extend_from_slice(&rtc.to_save_block((self.host_clock)()));
+        }
+        data
+    }
+
+    /// Restore battery-backed state written by save_ram (or another emulator)
+    ///
+    /// The RTC block is optional. When present, the real time elapsed since it
+    /// was saved is applied to the clock.
+    pub fn load_save(&mut self, data: &[u8]) -> Result<(), String> {
+        let now = (self.host_clock)();
+        let mbc = self.bus.mbc_mut();
+
+        let ram_size = mbc.ram().len();
+        if data.len() < ram_size {
+            return Err(format!(
+                "Save too small: {} bytes, expected at least {}",
+                data.len(),
+                ram_size
+            ));
+        }
+        mbc.load_ram(&data[..ram_size]);
+
+        let rest = &data[ram_size..];
+        if let Some(rtc) = mbc.rtc_mut() {
+            if !rest.is_empty() {
+                let (saved, timestamp) = Rtc::from_save_block(rest)
+                    .ok_or_else(|| format!("Invalid RTC block size: {} bytes", rest.len()))?;
+                *rtc = saved;
+                rtc.advance(now.saturating_sub(timestamp));
+            }
+        }
+        Ok(())
+    }
+
+    /// Export the machine state in the BESS format shared with other emulators
+    /// (SameBoy, BGB, ...): WRAM, VRAM, cartridge RAM, OAM, HRAM and the CGB
+    /// palettes, followed by the NAME, INFO, CORE, XRNG (RNG state) and END blocks
+    pub fn save_state_bess(&self) -> Vec<u8> {
+        let bus = &self.bus;
+        let cgb = bus.model().is_cgb();
+
+        // Header title and global checksum, straight from the ROM (not banked)
+        let rom = bus.mbc().rom();
+        let mut info = [0u8; 0x12];
+        for (i, byte) in info[..0x10].iter_mut().enumerate() {
+            *byte = rom.get(0x0134 + i).copied().unwrap_or(0);
+        }
+        info[0x10] = rom.get(0x014E).copied().unwrap_or(0);
+        info[0x11] = rom.get(0x014F).copied().unwrap_or(0);
+
+        let mut io = [0u8; 0x80];
+        for (i, byte) in io.iter_mut().enumerate() {
+            *byte = bus.read_io_raw(0xFF00 + i as u16);
+        }
+
+        // Both VRAM banks are stored back to back on CGB
+        let mut vram = bus.vram_bank_slice(0).to_vec();
+        if cgb {
+            vram.extend_from_slice(bus.vram_bank_slice(1));
+        }
+        let (bg_palettes, obj_palettes): (&[u8], &[u8]) = if cgb {
+            (&bus.ppu.bg_palette_ram, &bus.ppu.obj_palette_ram)
+        } else {
+            (&[], &[])
+        };
+
+        let regs = &self.cpu.regs;
+        let core = CoreState {
+            model: if cgb { *b"CCE " } else { *b"GDB " },
+            pc: regs.pc,
+            af: regs.af(),
+            bc: regs.bc(),
+            de: regs.de(),
+            hl: regs.hl(),
+            sp: regs.sp,
+            ime: self.cpu.ime,
+            ie: bus.read(0xFFFF),
+            execution_state: if self.cpu.stopped {
+                ExecutionState::Stopped
+            } else if self.cpu.halted {
+                ExecutionState::Halted
+            } else {
+                ExecutionState::Running
+            },
+            io,
+            regions: [
+                bus.wram_slice(),
+                &vram,
+                bus.mbc().ram(),
+                bus.oam_slice(),
+                bus.hram_slice(),
+                bg_palettes,
+                obj_palettes,
+            ],
+        };
+
+        let name = concat!("rust-gb-emu ", env!("CARGO_PKG_VERSION"));
+        BessWriter::new().write(name, &info, &core, self.rng.state())
+    }
+
+    /// Restore the RNG state from a BESS file written by `save_state_bess`
+    pub fn load_rng_state_bess(&mut self, state: &[u8]) -> Result<(), String> {
+        let block = bess::find_block(state, bess::RNG_BLOCK_ID)
+            .ok_or_else(|| "No RNG block in BESS state".to_string())?;
+        let bytes: [u8; 8] = block
+            .try_into()
+            .map_err(|_| format!("Invalid RNG block size: {} bytes", block.len()))?;
+        self.rng = Rng::from_state(u64::from_le_bytes(bytes));
+        Ok(())
+    }
+
+    /// Format a multi-line dump of the CPU, interrupt, timer, PPU and MBC state
+    pub fn state_report(&self) -> String {
+        let regs = &self.cpu.regs;
+        let flag = |set: bool, name: char| if set { name } else { '-' };
+        let ppu = &self.bus.ppu;
+        let ie = self.bus.read(0xFFFF);
+        let if_reg = self.bus.read(0xFF0F);
+
+        let mut report = String::new();
+        let _ = writeln!(
+            report,
+            "CPU:    AF={:04X} BC={:04X} DE={:04X} HL={:04X} SP={:04X} PC={:04X}",
+            regs.af(),
+            regs.bc(),
+            regs.de(),
+            regs.hl(),
+            regs.sp,
+            regs.pc
+        );
+        let _ = writeln!(
+            report,
+            "Flags:  {}{}{}{}  IME={} HALT={}",
+            flag(regs.f.z, 'Z'),
+            flag(regs.f.n, 'N'),
+            flag(regs.f.h, 'H'),
+            flag(regs.f.c, 'C'),
+            self.cpu.ime as u8,
+            self.cpu.halted as u8
+        );
+        let _ = writeln!(report, "IE:     {:02X} [{}]", ie, interrupt_names(ie));
+        let _ = writeln!(report, "IF:     {:02X} [{}]", if_reg, interrupt_names(if_reg));
+        let _ = writeln!(
+            report,
+            "Timer:  DIV={:02X} TIMA={:02X} TMA={:02X} TAC={:02X}",
+            self.bus.read(0xFF04),
+            self.bus.read(0xFF05),
+            self.bus.read(0xFF06),
+            self.bus.read(0xFF07)
+        );
+        let _ = writeln!(
+            report,
+            "PPU:    LCDC={:02X} STAT={:02X} LY={:02X} SCX={:02X} SCY={:02X} MODE={} DOT={}",
+            ppu.lcdc.0,
+            self.bus.read(0xFF41),
+            ppu.ly,
+            ppu.scx,
+            ppu.scy,
+            ppu.stat.mode(),
+            ppu.dot()
+        );
+        let _ = writeln!(
+            report,
+            "MBC:    ROM bank={} RAM bank={}",
+            self.bus.current_rom_bank(),
+            self.bus.current_ram_bank()
+        );
+        let _ = write!(report, "Cycles: {}", self.cycles);
+        report
+    }
+
     /// Get current serial output
     pub fn get_serial_output(&self) -> String {
         self.bus.get_serial_output()
     }
 
-    /// Check if test passed (output contains "Passed")
+    /// Serial bytes sent since the previous call (all of them on the first call)
+    /// Bytes dropped by the serial buffer cap before being polled are skipped.
+    /// If `bus.serial` was replaced or reset, polling restarts from its output.
+    pub fn poll_new_serial(&mut self) -> &[u8] {
+        let output = &self.bus.serial.output;
+        let total = self.bus.serial.output_total();
+        if total < self.serial_cursor {
+            self.serial_cursor = 0;
+        }
+        let new = total.saturating_sub(self.serial_cursor).min(output.len());
+        self.serial_cursor = total;
+        &output[output.len() - new..]
+    }
+
+    /// Set the exact serial output markers for test ROM results
+    /// Defaults to "Passed" / "Failed"; matching is case-sensitive
+    pub fn set_test_markers(&mut self, pass: &str, fail: &str) {
+        self.pass_marker = pass.to_string();
+        self.fail_marker = fail.to_string();
+    }
+
+    /// Check if test passed (output contains the pass marker)
     pub fn test_passed(&self) -> bool {
-        let output = self.get_serial_output();
-        output.contains("Passed") || output.contains("passed")
+        self.get_serial_output().contains(&self.pass_marker)
     }
 
-    /// Check if test failed (output contains "Failed")
+    /// Check if test failed (output contains the fail marker)
     pub fn test_failed(&self) -> bool {
-        let output = self.get_serial_output();
-        output.contains("Failed") || output.contains("failed")
+        self.get_serial_output().contains(&self.fail_marker)
+    }
+}
+
+/// Names of the interrupts set in an IE/IF value ("-" if none)
+fn interrupt_names(value: u8) -> String {
+    let names: Vec<String> = InterruptKind::ALL
+        .iter()
+        .filter(|kind| value & kind.mask() != 0)
+        .map(|kind| format!("{:?}", kind))
+        .collect();
+
+    if names.is_empty() {
+        "-".to_string()
+    } else {
+        names.join(" ")
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::serial::SerialMode;
 
     #[test]
     fn test_emulator_creation() {
@@ -148,14 +753,1101 @@ mod tests {
         assert_eq!(emu.get_serial_output(), "Hi");
     }
 
+    /// ROM that sends one byte and polls SC bit 7 until the transfer is done
+    fn serial_poll_rom() -> Vec<u8> {
+        let mut rom = vec![0u8; 0x8000];
+        let program: &[u8] = &[
+            0x3E, b'X',       // LD A, 'X'
+            0xE0, 0x01,       // LDH (0x01), A  -> SB
+            0x3E, 0x81,       // LD A, 0x81
+            0xE0, 0x02,       // LDH (0x02), A  -> SC (start)
+            0xF0, 0x02,       // LDH A, (0x02)  <- poll
+            0xCB, 0x7F,       // BIT 7, A
+            0x20, 0xFA,       // JR NZ, poll
+            0x76,             // HALT
+        ];
+        rom[0x0100..0x0100 + program.len()].copy_from_slice(program);
+        rom
+    }
+
     #[test]
-    fn test_run_cycles() {
+    fn test_serial_poll_loop_instant() {
+        let mut emu = Emulator::with_rom(&serial_poll_rom());
+
+        assert!(emu.run_until_halt(10_000));
+        assert_eq!(emu.get_serial_output(), "X");
+        // Instant mode: the first poll already sees the transfer done
+        assert!(emu.cycles < 100);
+    }
+
+    #[test]
+    fn test_serial_poll_loop_timed() {
+        let mut emu = Emulator::with_rom(&serial_poll_rom());
+        emu.bus.serial.set_mode(SerialMode::Timed);
+
+        // Run until the transfer starts
+        while !emu.bus.serial.transfer_in_progress() {
+            emu.step();
+        }
+        assert!(emu.run_until_serial_idle(10_000));
+
+        assert!(emu.run_until_halt(20_000));
+        assert_eq!(emu.get_serial_output(), "X");
+        // Timed mode: the loop spins for 8 bit periods
+        assert!(emu.cycles >= 8 * crate::serial::CYCLES_PER_BIT as u64);
+    }
+
+    #[test]
+    fn test_interrupt_log() {
+        use std::cell::RefCell;
+        use std::rc::Rc;
+
+        let mut rom = vec![0u8; 0x8000];
+        let program: &[u8] = &[
+            0x3E, 0x04,       // LD A, 0x04
+            0xE0, 0xFF,       // LDH (0xFF), A  -> IE: Timer
+            0x3E, 0xFF,       // LD A, 0xFF
+            0xE0, 0x05,       // LDH (0x05), A  -> TIMA about to overflow
+            0x3E, 0x05,       // LD A, 0x05
+            0xE0, 0x07,       // LDH (0x07), A  -> TAC: enabled, 16 cycles
+            0xFB,             // EI
+            0x18, 0xFE,       // JR -2 (0x010D)
+        ];
+        rom[0x0100..0x0100 + program.len()].copy_from_slice(program);
+        rom[0x0050] = 0x76; // HALT in the timer handler
+
+        let log = Rc::new(RefCell::new(Vec::new()));
+        let log_clone = Rc::clone(&log);
+
+        let mut emu = Emulator::with_rom(&rom);
+        emu.cpu.set_interrupt_log(Box::new(move |kind, pc| {
+            log_clone.borrow_mut().push((kind, pc));
+        }));
+        emu.run_until_halt(10_000);
+
+        assert_eq!(emu.cpu.regs.pc, 0x0051);
+        assert_eq!(*log.borrow(), vec![(InterruptKind::Timer, 0x010D)]);
+    }
+
+    #[test]
+    fn test_last_step_serviced_interrupt() {
+        let mut rom = vec![0u8; 0x8000];
+        let program: &[u8] = &[
+            0x3E, 0x04,       // LD A, 0x04
+            0xE0, 0xFF,       // LDH (0xFF), A  -> IE: Timer
+            0x3E, 0xFF,       // LD A, 0xFF
+            0xE0, 0x05,       // LDH (0x05), A  -> TIMA about to overflow
+            0x3E, 0x05,       // LD A, 0x05
+            0xE0, 0x07,       // LDH (0x07), A  -> TAC: enabled, 16 cycles
+            0xFB,             // EI
+            0x18, 0xFE,       // JR -2 (0x010D)
+        ];
+        rom[0x0100..0x0100 + program.len()].copy_from_slice(program);
+        rom[0x0050] = 0x00; // NOP in the timer handler
+
+        let mut emu = Emulator::with_rom(&rom);
+        let mut steps = 0;
+        while emu.cpu.last_step_serviced_interrupt().is_none() {
+            emu.step();
+            steps += 1;
+            assert!(steps < 1000, "timer interrupt never dispatched");
+        }
+
+        assert_eq!(emu.cpu.last_step_serviced_interrupt(), Some(InterruptKind::Timer));
+        assert_eq!(emu.cpu.regs.pc, 0x0050);
+
+        // The next step executes the handler's NOP
+        emu.step();
+        assert_eq!(emu.cpu.last_step_serviced_interrupt(), None);
+    }
+
+    #[test]
+    fn test_interrupt_nesting_warning() {
+        use std::cell::RefCell;
+        use std::rc::Rc;
+
+        let mut rom = vec![0u8; 0x8000];
+        let program: &[u8] = &[
+            0x3E, 0x04,       // LD A, 0x04
+            0xE0, 0xFF,       // LDH (0xFF), A  -> IE: Timer
+            0xE0, 0x0F,       // LDH (0x0F), A  -> IF: Timer
+            0xFB,             // EI
+            0x18, 0xFE,       // JR -2 (0x0107)
+        ];
+        rom[0x0100..0x0100 + program.len()].copy_from_slice(program);
+        // Buggy timer handler: re-requests its own interrupt before EI
+        let handler: &[u8] = &[
+            0x3E, 0x04,       // LD A, 0x04
+            0xE0, 0x0F,       // LDH (0x0F), A
+            0xFB,             // EI
+            0x00,             // NOP
+            0xD9,             // RETI
+        ];
+        rom[0x0050..0x0050 + handler.len()].copy_from_slice(handler);
+
+        let warnings = Rc::new(RefCell::new(Vec::new()));
+        let warnings_clone = Rc::clone(&warnings);
+
+        let mut emu = Emulator::with_rom(&rom);
+        emu.cpu.set_max_interrupt_nesting(3, Box::new(move |kind, depth| {
+            warnings_clone.borrow_mut().push((kind, depth));
+        }));
+
+        // Dispatch the first 3 levels: within the limit
+        while emu.cpu.interrupt_depth() < 3 {
+            emu.step();
+        }
+        assert!(warnings.borrow().is_empty());
+
+        // The 4th nested dispatch exceeds it
+        while emu.cpu.interrupt_depth() < 4 {
+            emu.step();
+        }
+        assert_eq!(*warnings.borrow(), vec![(InterruptKind::Timer, 4)]);
+    }
+
+    #[test]
+    fn test_interrupt_depth_unwinds_on_return() {
+        let mut rom = vec![0u8; 0x8000];
+        let program: &[u8] = &[
+            0x3E, 0x04,       // LD A, 0x04
+            0xE0, 0xFF,       // LDH (0xFF), A  -> IE: Timer
+            0xE0, 0x0F,       // LDH (0x0F), A  -> IF: Timer
+            0xFB,             // EI
+            0x00,             // NOP
+            0x76,             // HALT
+        ];
+        rom[0x0100..0x0100 + program.len()].copy_from_slice(program);
+        rom[0x0050] = 0xD9; // RETI
+
+        let mut emu = Emulator::with_rom(&rom);
+        emu.cpu.set_max_interrupt_nesting(1, Box::new(|_, _| panic!("unexpected nesting")));
+
+        while emu.cpu.regs.pc != 0x0050 {
+            emu.step();
+        }
+        assert_eq!(emu.cpu.interrupt_depth(), 1);
+
+        emu.run_until_halt(100);
+        assert_eq!(emu.cpu.interrupt_depth(), 0);
+    }
+
+    #[test]
+    fn test_halt_wake_timing_matches_busy_loop() {
+        // Halting in 4-cycle steps must dispatch the timer interrupt on the
+        // same cycle as executing NOPs would (both advance one M-cycle at a time)
+        let cycles_until_dispatch = |wait_opcode: u8| {
+            let mut rom = vec![0u8; 0x8000];
+            let program: &[u8] = &[
+                0x3E, 0x04,       // LD A, 0x04
+                0xE0, 0xFF,       // LDH (0xFF), A  -> IE: Timer
+                0x3E, 0xF0,       // LD A, 0xF0
+                0xE0, 0x05,       // LDH (0x05), A  -> TIMA: 16 ticks to overflow
+                0x3E, 0x05,       // LD A, 0x05
+                0xE0, 0x07,       // LDH (0x07), A  -> TAC: enabled, 16 cycles
+                0xFB,             // EI
+                wait_opcode,      // HALT or NOP (followed by a NOP sled)
+            ];
+            rom[0x0100..0x0100 + program.len()].copy_from_slice(program);
+
+            let mut emu = Emulator::with_rom(&rom);
+            while emu.cpu.regs.pc != 0x0050 {
+                emu.step();
+                assert!(emu.cycles < 10_000, "timer interrupt never dispatched");
+            }
+            emu.cycles
+        };
+
+        let halted = cycles_until_dispatch(0x76);
+        let busy = cycles_until_dispatch(0x00);
+        assert_eq!(halted, busy);
+        assert_eq!(halted % 4, 0);
+    }
+
+    #[test]
+    fn test_run_frame() {
         let rom = vec![0u8; 0x8000]; // All NOPs
         let mut emu = Emulator::with_rom(&rom);
 
-        emu.run_cycles(100);
+        // First frame: from LY=0 to the start of V-Blank (144 lines)
+        assert_eq!(emu.run_frame(), 144 * 456);
+        assert_eq!(emu.bus.ppu.ly, 144);
 
-        // Each NOP is 4 cycles, so we should have executed ~25 NOPs
-        assert!(emu.cycles >= 100);
+        // Following frames are a full 154 lines
+        assert_eq!(emu.run_frame(), CYCLES_PER_FRAME);
+    }
+
+    #[test]
+    fn test_save_ram_with_rtc() {
+        use std::cell::Cell;
+        use std::rc::Rc as Shared;
+
+        let mut rom = vec![0u8; 0x8000];
+        rom[0x0147] = 0x10; // MBC3+TIMER+RAM+BATTERY
+        rom[0x0149] = 0x02; // 8KB RAM
+        let cart = Cartridge::from_bytes(rom).unwrap();
+
+        let clock = Shared::new(Cell::new(1_000_000u64));
+        let make_emu = || {
+            let mut emu = Emulator::new(&cart);
+            let clock = Shared::clone(&clock);
+            emu.set_host_clock(Box::new(move || clock.get()));
+            emu
+        };
+
+        // Write RAM and set the clock to day 5, 12:00:00
+        let mut emu = make_emu();
+        emu.bus.write(0x0000, 0x0A);
+        emu.bus.write(0xA000, 0x42);
+        for (register, value) in [(0x0A, 12), (0x0B, 5)] {
+            emu.bus.write(0x4000, register);
+            emu.bus.write(0xA000, value);
+        }
+
+        let save = emu.save_ram();
+        assert_eq!(save.len(), 0x2000 + 48);
+
+        // Two and a half days pass on the host
+        clock.set(clock.get() + 2 * 86400 + 12 * 3600);
+
+        let mut emu = make_emu();
+        emu.load_save(&save).unwrap();
+        emu.bus.write(0x0000, 0x0A);
+        emu.bus.write(0x4000, 0x00);
+        assert_eq!(emu.bus.read(0xA000), 0x42);
+
+        let rtc = emu.bus.mbc().rtc().unwrap();
+        assert_eq!(rtc.days(), 8);
+        assert_eq!(rtc.registers[2], 0);
+
+        // Truncated saves are rejected
+        assert!(emu.load_save(&save[..100]).is_err());
+        assert!(emu.load_save(&save[..0x2000 + 10]).is_err());
+    }
+
+    #[test]
+    fn test_test_markers() {
+        let rom = vec![0u8; 0x8000];
+        let mut emu = Emulator::with_rom(&rom);
+
+        // Default markers are case-sensitive
+        emu.bus.serial.output = b"You passed the level".to_vec();
+        assert!(!emu.test_passed());
+        emu.bus.serial.output = b"cpu_instrs\n\nPassed all tests".to_vec();
+        assert!(emu.test_passed());
+        assert!(!emu.test_failed());
+
+        // Custom markers only match the exact strings
+        emu.set_test_markers("OK!", "BAD!");
+        assert!(!emu.test_passed());
+        emu.bus.serial.output = b"ok! BAD".to_vec();
+        assert!(!emu.test_passed());
+        assert!(!emu.test_failed());
+        emu.bus.serial.output = b"all OK!".to_vec();
+        assert!(emu.test_passed());
+        emu.bus.serial.output = b"BAD!".to_vec();
+        assert!(emu.test_failed());
+    }
+
+    #[test]
+    fn test_state_report() {
+        let mut rom = vec![0u8; 0x8000];
+        rom[0x0100] = 0x00; // NOP
+        let mut emu = Emulator::with_rom(&rom);
+        emu.bus.write(0xFFFF, 0x05);
+        emu.bus.write(0xFF0F, 0x04);
+        emu.step();
+
+        let report = emu.state_report();
+        for label in ["AF=", "SP=", "IME=", "HALT=", "IE:", "IF:", "DIV=", "TIMA=", "TMA=",
+                      "TAC=", "LCDC=", "STAT=", "LY=", "SCX=", "SCY=", "MODE=", "ROM bank=",
+                      "RAM bank="] {
+            assert!(report.contains(label), "missing {} in:\n{}", label, report);
+        }
+        assert!(report.contains("PC=0101"), "{}", report);
+        assert!(report.contains("Flags:  Z-HC"), "{}", report);
+        assert!(report.contains("IE:     05 [VBlank Timer]"), "{}", report);
+        assert!(report.contains("ROM bank=1"), "{}", report);
+    }
+
+    #[test]
+    fn test_frame_ready() {
+        let rom = vec![0u8; 0x8000]; // All NOPs
+        let mut emu = Emulator::with_rom(&rom);
+
+        let mut steps = 0;
+        while !emu.bus.ppu.frame_ready() {
+            emu.step();
+            steps += 1;
+            assert!(steps < CYCLES_PER_FRAME, "no frame completed");
+        }
+        assert_eq!(emu.bus.ppu.ly, 144);
+
+        // Cleared by the poll, even though V-Blank is still in progress
+        assert!(!emu.bus.ppu.frame_ready());
+        emu.step();
+        assert!(!emu.bus.ppu.frame_ready());
+    }
+
+    #[test]
+    fn test_double_speed_frame_timing() {
+        let rom = vec![0u8; 0x8000]; // All NOPs
+        let mut emu = Emulator::with_rom(&rom);
+        emu.bus.set_double_speed(true);
+
+        // The PPU still takes 70224 dots per frame, which is
+        // twice as many CPU cycles at double speed
+        emu.run_frame();
+        assert_eq!(emu.run_frame(), 2 * CYCLES_PER_FRAME);
+        assert_eq!(emu.cycles_per_frame(), 2 * CYCLES_PER_FRAME);
+
+        // The timer stays in the CPU domain: DIV ticks every 256 CPU cycles
+        let div = emu.bus.read(0xFF04);
+        emu.run_cycles(256);
+        assert_eq!(emu.bus.read(0xFF04), div.wrapping_add(1));
+    }
+
+    #[test]
+    fn test_cycle_checkpoint() {
+        let mut rom = vec![0u8; 0x8000];
+        let program: &[u8] = &[
+            0x00,             // NOP            (4)
+            0x3E, 0x42,       // LD A, 0x42     (8)
+            0x21, 0x00, 0xC0, // LD HL, 0xC000  (12)
+            0x77,             // LD (HL), A     (8)
+            0xC3, 0x00, 0x02, // JP 0x0200      (16)
+        ];
+        rom[0x0100..0x0100 + program.len()].copy_from_slice(program);
+
+        let mut emu = Emulator::with_rom(&rom);
+        emu.step();
+        let start = emu.checkpoint_cycles();
+        assert_eq!(emu.cycles_since(start), 0);
+
+        for _ in 0..3 {
+            emu.step();
+        }
+        let middle = emu.checkpoint_cycles();
+        assert_eq!(emu.cycles_since(start), 28);
+
+        emu.step();
+        assert_eq!(emu.cycles_since(middle), 16);
+        assert_eq!(emu.cycles_since(start), 44);
+
+        // A checkpoint from the future doesn't underflow
+        emu.cycles = 0;
+        assert_eq!(emu.cycles_since(middle), 0);
+    }
+
+    #[test]
+    fn test_exit_on_self_loop() {
+        let mut rom = vec![0u8; 0x8000];
+        let program: &[u8] = &[
+            0x21, 0x20, 0x01, // LD HL, 0x0120
+            0x2A,             // LD A, (HL+)    <- loop
+            0xB7,             // OR A
+            0x28, 0x08,       // JR Z, done
+            0xE0, 0x01,       // LDH (0x01), A  -> SB
+            0x3E, 0x81,       // LD A, 0x81
+            0xE0, 0x02,       // LDH (0x02), A  -> SC: transfer
+            0x18, 0xF4,       // JR loop
+            0x18, 0xFE,       // JR -2          <- done
+        ];
+        rom[0x0100..0x0100 + program.len()].copy_from_slice(program);
+        rom[0x0120..0x0127].copy_from_slice(b"Passed\0");
+
+        // Without the option, the loop spins to the cycle cap
+        let mut emu = Emulator::with_rom(&rom);
+        assert!(!emu.run_until_serial_contains("Done", 100_000));
+        assert!(emu.cycles >= 100_000);
+
+        let mut emu = Emulator::with_rom(&rom);
+        emu.set_exit_on_self_loop(true);
+        assert!(!emu.run_until_serial_contains("Done", 100_000_000));
+        assert!(emu.cycles < 10_000);
+        assert!(emu.in_self_loop());
+        assert!(emu.test_passed());
+
+        // Every run loop honours it
+        let mut emu = Emulator::with_rom(&rom);
+        emu.set_exit_on_self_loop(true);
+        emu.run_cycles(100_000_000);
+        assert!(emu.cycles < 10_000);
+        assert!(!emu.keep_running());
+        assert_eq!(emu.run_until_write(0xC000, 100_000_000), None);
+        assert!(emu.cycles < 10_000);
+    }
+
+    #[test]
+    fn test_seeded_ram_is_reproducible() {
+        let rom = vec![0u8; 0x8000];
+        let wram = |emu: &Emulator| -> Vec<u8> { (0xC000..=0xDFFF).map(|addr| emu.bus.read(addr)).collect() };
+
+        // Default: zeroed RAM
+        let emu = Emulator::with_rom(&rom);
+        assert!(wram(&emu).iter().all(|&b| b == 0));
+
+        let mut a = Emulator::with_rom(&rom);
+        let mut b = Emulator::with_rom(&rom);
+        a.set_seed(1234);
+        b.set_seed(1234);
+        assert_eq!(wram(&a), wram(&b));
+        assert_eq!(a.bus.read(0xFF80), b.bus.read(0xFF80));
+        assert!(wram(&a).iter().any(|&b| b != 0));
+
+        let mut c = Emulator::with_rom(&rom);
+        c.set_seed(5678);
+        assert_ne!(wram(&a), wram(&c));
+    }
+
+    #[test]
+    fn test_rng_state_in_bess() {
+        let rom = vec![0u8; 0x8000];
+        let mut a = Emulator::with_rom(&rom);
+        a.set_seed(1234);
+        a.rng().next_u64();
+        let state = a.save_state_bess();
+
+        let mut b = Emulator::with_rom(&rom);
+        b.load_rng_state_bess(&state).unwrap();
+        for _ in 0..10 {
+            assert_eq!(a.rng().next_u64(), b.rng().next_u64());
+        }
+
+        assert!(b.load_rng_state_bess(&[]).is_err());
+    }
+
+    #[test]
+    fn test_pause_resume() {
+        let mut rom = vec![0u8; 0x8000];
+        rom[0x0100..0x0102].copy_from_slice(&[0x18, 0xFE]); // JR -2
+
+        let mut emu = Emulator::with_rom(&rom);
+        emu.pause();
+        assert!(emu.is_paused());
+        assert_eq!(emu.step(), 0);
+        assert_eq!(emu.run_frame(), 0);
+        emu.run_cycles(1000);
+        assert!(!emu.run_until_halt(1000));
+        assert_eq!(emu.cycles, 0);
+        assert_eq!(emu.cpu.regs.pc, 0x0100);
+
+        emu.resume();
+        assert!(emu.run_frame() > 0);
+        assert!(emu.cycles > 0);
+    }
+
+    #[test]
+    fn test_is_idle() {
+        let mut rom = vec![0u8; 0x8000];
+        let program: &[u8] = &[
+            0xAF,             // XOR A
+            0xE0, 0xFF,       // LDH (0xFF), A  -> IE = 0
+            0x76,             // HALT
+        ];
+        rom[0x0100..0x0100 + program.len()].copy_from_slice(program);
+
+        let mut emu = Emulator::with_rom(&rom);
+        assert!(!emu.is_idle());
+        emu.run_until_halt(1000);
+        assert!(emu.is_idle());
+
+        // Timer interrupt armed: the HALT will end
+        rom[0x0100..0x0108].copy_from_slice(&[
+            0x3E, 0x05,       // LD A, 0x05
+            0xE0, 0x07,       // LDH (0x07), A  -> TAC: enabled, 262144 Hz
+            0x3E, 0x04,       // LD A, 0x04
+            0xE0, 0xFF,       // LDH (0xFF), A  -> IE: Timer
+        ]);
+        rom[0x0108] = 0x76;   // HALT
+        let mut emu = Emulator::with_rom(&rom);
+        emu.run_until_halt(1000);
+        assert!(emu.cpu.halted);
+        assert!(!emu.is_idle());
+    }
+
+    #[test]
+    fn test_run_frame_with_lcd_off() {
+        let mut rom = vec![0u8; 0x8000];
+        let program: &[u8] = &[
+            0xAF,             // XOR A
+            0xE0, 0x40,       // LDH (0x40), A  -> LCD off
+            0x18, 0xFE,       // JR -2
+        ];
+        rom[0x0100..0x0100 + program.len()].copy_from_slice(program);
+
+        let mut emu = Emulator::with_rom(&rom);
+        emu.run_cycles(100);
+        assert!(!emu.bus.ppu.lcdc.lcd_enable());
+
+        for _ in 0..3 {
+            let cycles = emu.run_frame();
+            assert!((CYCLES_PER_FRAME..CYCLES_PER_FRAME + 16).contains(&cycles), "{}", cycles);
+        }
+    }
+
+    #[test]
+    fn test_capture_frames() {
+        let mut rom = vec![0u8; 0x8000];
+        let program: &[u8] = &[
+            0x3E, 0x01,       // LD A, 0x01
+            0xE0, 0xFF,       // LDH (0xFF), A  -> IE: VBlank
+            0x21, 0x00, 0x80, // LD HL, 0x8000  -> tile 0, row 0
+            0xFB,             // EI
+            0x76,             // HALT           (0x0108)
+            0x34,             // INC (HL)       -> animate the tile during V-Blank
+            0x18, 0xFC,       // JR -4 (0x0108)
+        ];
+        rom[0x0100..0x0100 + program.len()].copy_from_slice(program);
+        rom[0x0040] = 0xD9; // RETI
+
+        let mut emu = Emulator::with_rom(&rom);
+        let frames = emu.capture_frames(3);
+
+        assert_eq!(frames.len(), 3);
+        for frame in &frames {
+            assert_eq!(frame.len(), 160 * 144);
+        }
+        assert_ne!(frames[0], frames[1]);
+        assert_ne!(frames[1], frames[2]);
+    }
+
+    #[test]
+    fn test_boot_to_stable_frame() {
+        let mut rom = vec![0u8; 0x8000];
+        let program: &[u8] = &[
+            0xAF,             // XOR A
+            0xE0, 0x40,       // LDH (0x40), A  -> LCD off
+            0x21, 0x00, 0x80, // LD HL, 0x8000
+            0x06, 0x10,       // LD B, 16
+            0x3E, 0xFF,       // LD A, 0xFF
+            0x22,             // LD (HL+), A   <- loop
+            0x05,             // DEC B
+            0x20, 0xFC,       // JR NZ, loop
+            0x3E, 0x91,       // LD A, 0x91
+            0xE0, 0x40,       // LDH (0x40), A  -> LCD on
+            0x18, 0xFE,       // JR -2 (spin)
+        ];
+        rom[0x0100..0x0100 + program.len()].copy_from_slice(program);
+
+        let mut emu = Emulator::with_rom(&rom);
+        let frame = emu.boot_to_stable_frame(10).expect("screen should stabilize");
+
+        // Tile 0 is solid color 1, which BGP 0xFC maps to shade 3
+        assert!(frame.iter().all(|&p| p == 3));
+    }
+
+    #[test]
+    fn test_run_cycles() {
+        let rom = vec![0u8; 0x8000]; // All NOPs
+        let mut emu = Emulator::with_rom(&rom);
+
+        emu.run_cycles(100);
+
+        // Each NOP is 4 cycles, so we should have executed ~25 NOPs
+        assert!(emu.cycles >= 100);
+    }
+
+    #[test]
+    fn test_run_cycles_saturates() {
+        // di; halt
+        let mut rom = vec![0u8; 0x8000];
+        rom[0x0100..0x0102].copy_from_slice(&[0xF3, 0x76]);
+        let mut emu = Emulator::with_rom(&rom);
+        emu.bus.write(0xFFFF, 0x00);
+
+        // An unbounded budget still ends at the HALT
+        emu.run_cycles(u64::MAX);
+        assert!(emu.cpu.halted);
+
+        // Near the top of the range, the count clamps instead of overflowing
+        let mut emu = Emulator::with_rom(&[0u8; 0x8000]);
+        emu.cycles = u64::MAX - 10;
+        assert_eq!(emu.cycle_cap(100), u64::MAX);
+        emu.run_cycles(u64::MAX);
+        assert_eq!(emu.cycles, u64::MAX);
+
+        // A zero budget is a no-op
+        let pc = emu.cpu.regs.pc;
+        assert!(!emu.run_until_halt(0));
+        assert_eq!(emu.cpu.regs.pc, pc);
+    }
+
+    #[test]
+    fn test_run_budgets_are_relative() {
+        // nop; nop; di; halt
+        let mut rom = vec![0u8; 0x8000];
+        rom[0x0102..0x0104].copy_from_slice(&[0xF3, 0x76]);
+        let mut emu = Emulator::with_rom(&rom);
+        emu.bus.write(0xFFFF, 0x00);
+
+        // Far past any budget: the loop still runs instead of returning at once
+        emu.cycles = 1_000_000;
+        assert!(emu.run_until_halt(1_000));
+        assert!(emu.cycles_since(1_000_000) < 1_000);
+        assert_eq!(emu.cpu.regs.pc, 0x0104);
+    }
+
+    #[test]
+    fn test_save_state_bess() {
+        let mut rom = vec![0u8; 0x8000];
+        rom[0x0134..0x0138].copy_from_slice(b"TEST");
+        let mut emu = Emulator::with_rom(&rom);
+        emu.cpu.regs.pc = 0x1234;
+        emu.bus.write(0xC000, 0x5A);
+        emu.bus.write(0xFF80, 0xA5);
+
+        let state = emu.save_state_bess();
+        let u32_at = |at: usize| u32::from_le_bytes(state[at..at + 4].try_into().unwrap()) as usize;
+
+        // Footer: first block offset, then the magic
+        assert_eq!(&state[state.len() - 4..], b"BESS");
+        let mut at = u32_at(state.len() - 8);
+
+        // Walk the block list
+        let mut ids = Vec::new();
+        let mut core = None;
+        loop {
+            let id = &state[at..at + 4];
+            let len = u32_at(at + 4);
+            ids.push(String::from_utf8_lossy(id).to_string());
+            match id {
+                b"INFO" => assert_eq!(&state[at + 8..at + 12], b"TEST"),
+                b"CORE" => {
+                    assert_eq!(len, 0xD0);
+                    core = Some(at + 8);
+                }
+                _ => {}
+            }
+            at += 8 + len;
+            if id == b"END " {
+                break;
+            }
+        }
+        assert_eq!(ids, ["NAME", "INFO", "CORE", "XRNG", "END "]);
+        assert_eq!(at, state.len() - 8);
+
+        let core = core.unwrap();
+        assert_eq!(&state[core + 4..core + 8], b"GDB ");
+        assert_eq!(&state[core + 8..core + 10], &0x1234u16.to_le_bytes());
+        assert_eq!(state[core + 0x16], ExecutionState::Running as u8);
+
+        // Memory descriptors point at the stored regions
+        let wram = (u32_at(core + 0x98), u32_at(core + 0x9C));
+        assert_eq!(wram.0, 0x2000);
+        assert_eq!(state[wram.1], 0x5A);
+        let hram = (u32_at(core + 0xB8), u32_at(core + 0xBC));
+        assert_eq!(hram.0, 0x7F);
+        assert_eq!(state[hram.1], 0xA5);
+        // No palettes on DMG
+        assert_eq!(u32_at(core + 0xC0), 0);
+        assert_eq!(u32_at(core + 0xC8), 0);
+
+        // STOP is recorded as such, not as running
+        emu.cpu.stopped = true;
+        let state = emu.save_state_bess();
+        let core = state.windows(4).position(|id| id == b"CORE").unwrap() + 8;
+        assert_eq!(state[core + 0x16], ExecutionState::Stopped as u8);
+    }
+
+    #[test]
+    fn test_save_state_bess_raw_header_and_io() {
+        // 1MB MBC1 cartridge; in mode 1 the 0x0000 region shows bank 0x20
+        let mut rom = vec![0u8; 0x10_0000];
+        rom[0x0134..0x0138].copy_from_slice(b"REAL");
+        rom[0x0147] = 0x01;
+        rom[0x014F] = 0x5C;
+        rom[0x8_0134..0x8_0138].copy_from_slice(b"BANK");
+        let mut emu = Emulator::with_rom(&[0u8; 0x8000]);
+        emu.bus = Bus::with_cartridge(0x01, rom, 0);
+        emu.bus.write(0x6000, 0x01);
+        emu.bus.write(0x4000, 0x01);
+        assert_eq!(emu.bus.read(0x0134), b'B');
+
+        // NR13 is write-only and reads back as 0xFF
+        emu.bus.write(0xFF13, 0x42);
+        assert_eq!(emu.bus.read(0xFF13), 0xFF);
+
+        let state = emu.save_state_bess();
+        let block = |id: &[u8]| state.windows(4).position(|w| w == id).unwrap() + 8;
+        let info = block(b"INFO");
+        assert_eq!(&state[info..info + 4], b"REAL");
+        assert_eq!(state[info + 0x11], 0x5C);
+        let core = block(b"CORE");
+        assert_eq!(state[core + 0x18 + 0x13], 0x42);
+    }
+
+    #[test]
+    fn test_current_instruction() {
+        let mut rom = vec![0u8; 0x8000];
+        rom[0x0100..0x0103].copy_from_slice(&[0xC3, 0x34, 0x12]); // JP 0x1234
+        let mut emu = Emulator::with_rom(&rom);
+
+        let instruction = emu.current_instruction();
+        assert_eq!(instruction.address, 0x0100);
+        assert_eq!(instruction.mnemonic, "JP 0x1234");
+        assert_eq!(instruction.target, Some(0x1234));
+
+        // Decoding doesn't execute anything
+        assert_eq!(emu.cpu.regs.pc, 0x0100);
+        emu.step();
+        assert_eq!(emu.cpu.regs.pc, 0x1234);
+    }
+
+    #[test]
+    fn test_decode_during_oam_dma() {
+        let mut rom = vec![0u8; 0x8000];
+        rom[0x0100..0x0103].copy_from_slice(&[0xC3, 0x34, 0x12]); // JP 0x1234
+        let mut emu = Emulator::with_rom(&rom);
+        emu.bus.write(0xFF46, 0xC0);
+        assert!(emu.bus.oam_dma_status().is_some());
+
+        // The CPU is cut off from ROM, but debugging views are not
+        assert_eq!(emu.bus.cpu_read(0x0100), 0xFF);
+        assert_eq!(emu.current_instruction().mnemonic, "JP 0x1234");
+        assert!(crate::trace::trace_line(&emu).ends_with("PCMEM:C3,34,12,00"));
+    }
+
+    #[test]
+    fn test_disasm_window() {
+        let mut rom = vec![0u8; 0x8000];
+        rom[0x0150..0x015A].copy_from_slice(&[
+            0x3E, 0x12, // LD A, 0x12
+            0x21, 0x00, 0xC0, // LD HL, 0xC000
+            0x77, // LD (HL), A
+            0xC3, 0x34, 0x12, // JP 0x1234
+            0x00, // NOP
+        ]);
+        let mut emu = Emulator::with_rom(&rom);
+        emu.cpu.regs.pc = 0x0155;
+
+        let window = emu.disasm_window(2, 2);
+        let addresses: Vec<u16> = window.iter().map(|line| line.0).collect();
+        assert_eq!(addresses, vec![0x0150, 0x0152, 0x0155, 0x0156, 0x0159]);
+
+        let current: Vec<_> = window.iter().filter(|line| line.2).collect();
+        assert_eq!(current.len(), 1);
+        assert_eq!(current[0].0, 0x0155);
+        assert_eq!(window[3].1, "JP 0x1234");
+        assert_eq!(window[4].1, "NOP");
+    }
+
+    #[test]
+    fn test_frame_cycle_log() {
+        let mut rom = vec![0u8; 0x8000];
+        rom[0x0100..0x0102].copy_from_slice(&[0x18, 0xFE]); // JR -2
+        let mut emu = Emulator::with_rom(&rom);
+
+        emu.run_frame();
+        assert!(emu.frame_cycle_log().is_empty());
+
+        emu.set_frame_cycle_logging(true);
+        for _ in 0..5 {
+            emu.run_frame();
+        }
+
+        let log = emu.frame_cycle_log();
+        assert_eq!(log.len(), 5);
+        for pair in log.windows(2) {
+            assert_eq!(pair[1] - pair[0], CYCLES_PER_FRAME);
+        }
+    }
+
+    #[test]
+    fn test_run_until_write() {
+        let mut rom = vec![0u8; 0x8000];
+        let program = [
+            0x06, 0x10,       // LD B, 0x10
+            0x05,             // DEC B
+            0x20, 0xFD,       // JR NZ, -3
+            0x3E, 0x42,       // LD A, 0x42
+            0xEA, 0x00, 0xC0, // LD (0xC000), A
+            0x18, 0xFE,       // JR -2
+        ];
+        rom[0x0100..0x0100 + program.len()].copy_from_slice(&program);
+        let mut emu = Emulator::with_rom(&rom);
+
+        assert_eq!(emu.run_until_write(0xC000, 10_000), Some(0x42));
+        // Stopped right after the LD (0xC000), A
+        assert_eq!(emu.cpu.regs.pc, 0x010A);
+
+        // Nothing else writes to it
+        assert_eq!(emu.run_until_write(0xC000, 20_000), None);
+        assert!(emu.cycles >= 20_000);
+    }
+
+    #[test]
+    fn test_render_test_scene() {
+        let rom = vec![0u8; 0x8000];
+        let mut emu = Emulator::with_rom(&rom);
+
+        let frame = emu.render_test_scene(|ppu| {
+            ppu.load_scene_fixture(
+                "
+                tile 1
+                01230123
+                01230123
+                01230123
+                01230123
+                01230123
+                01230123
+                01230123
+                01230123
+
+                map
+                1
+                ",
+            )
+            .unwrap();
+            ppu.bgp = 0xE4;
+        });
+
+        assert_eq!(&frame[0..10], &[0, 1, 2, 3, 0, 1, 2, 3, 0, 0]);
+        assert_eq!(&frame[7 * 160..7 * 160 + 4], &[0, 1, 2, 3]);
+        assert_eq!(frame[8 * 160], 0);
+
+        // The CPU never ran
+        assert_eq!(emu.cycles, 0);
+        assert_eq!(emu.cpu.regs.pc, 0x0100);
+    }
+
+    #[test]
+    fn test_undefined_opcode_stops_the_cpu() {
+        let mut rom = vec![0u8; 0x8000];
+        rom[0x0101] = 0xD3; // Undefined
+        let mut emu = Emulator::with_rom(&rom);
+
+        assert_eq!(emu.step(), 4);
+        assert_eq!(emu.cpu_error(), None);
+
+        assert_eq!(emu.step(), 0);
+        assert_eq!(emu.cpu_error(), Some(CpuError::UndefinedOpcode(0xD3, 0x0101)));
+
+        // Locked up: nothing runs any more
+        let cycles = emu.cycles;
+        emu.run_cycles(10_000);
+        emu.run_frame();
+        assert_eq!(emu.cycles, cycles);
+    }
+
+    #[test]
+    fn test_set_initial_registers() {
+        let mut rom = vec![0u8; 0x8000];
+        rom[0x0200] = 0x80; // ADD A,B
+        let mut emu = Emulator::with_rom(&rom);
+
+        let mut regs = emu.cpu.regs.clone();
+        regs.a = 0x00;
+        regs.b = 0x42;
+        regs.f.from_byte(0x00);
+        regs.pc = 0x0200;
+        emu.set_initial_registers(regs);
+        // Visible before the first step
+        assert_eq!(emu.cpu.regs.pc, 0x0200);
+        assert_eq!(emu.current_instruction().mnemonic, "ADD A, B");
+        assert!(crate::trace::trace_line(&emu).starts_with("A:00 F:00 B:42"));
+
+        emu.step();
+        assert_eq!(emu.cpu.regs.a, 0x42);
+        assert_eq!(emu.cpu.regs.pc, 0x0201);
+        assert!(!emu.cpu.regs.f.z);
+    }
+
+    #[test]
+    fn test_interrupt_pending_hook() {
+        use std::cell::RefCell;
+        use std::rc::Rc;
+
+        let mut emu = Emulator::with_rom(&vec![0u8; 0x8000]); // NOPs
+        emu.bus.write(0xFF40, 0x00); // LCD off: no PPU interrupts
+        emu.bus.write(0xFF04, 0x00); // Internal counter = 0
+        emu.bus.write(0xFF05, 0xFF);
+        emu.bus.write(0xFF07, 0x05); // TIMA ticks every 16 cycles
+
+        let events = Rc::new(RefCell::new(Vec::new()));
+        let sink = events.clone();
+        emu.set_interrupt_pending_hook(Box::new(move |kind, cycle| sink.borrow_mut().push((kind, cycle))));
+
+        for _ in 0..8 {
+            emu.step();
+        }
+        // TIMA overflows on the 16th cycle; the next overflow takes 256 more
+        assert_eq!(*events.borrow(), vec![(InterruptKind::Timer, 16)]);
+        assert_eq!(emu.bus.read(0xFF0F) & 0x04, 0x04);
+    }
+
+    #[test]
+    fn test_interrupt_pending_hook_external_serial() {
+        use std::cell::RefCell;
+        use std::rc::Rc;
+
+        let mut emu = Emulator::with_rom(&vec![0u8; 0x8000]); // NOPs
+        emu.bus.write(0xFF40, 0x00);
+        emu.bus.write(0xFF02, 0x80); // Slave transfer, external clock
+
+        let events = Rc::new(RefCell::new(Vec::new()));
+        let sink = events.clone();
+        emu.set_interrupt_pending_hook(Box::new(move |kind, _| sink.borrow_mut().push(kind)));
+
+        // The link partner clocks all 8 bits between two steps
+        emu.step();
+        for _ in 0..8 {
+            emu.bus.serial_clock_pulse(1);
+        }
+        emu.step();
+        assert_eq!(*events.borrow(), vec![InterruptKind::Serial]);
+    }
+
+    #[test]
+    fn test_timer_edge_during_add_hl() {
+        let mut rom = vec![0u8; 0x8000];
+        rom[0x0103] = 0x09; // NOP x3, ADD HL,BC
+        let mut emu = Emulator::with_rom(&rom);
+        emu.bus.write(0xFF40, 0x00);
+        emu.bus.write(0xFF04, 0x00);
+        emu.bus.write(0xFF05, 0xFF);
+        emu.bus.write(0xFF06, 0x42);
+        emu.bus.write(0xFF07, 0x05); // TIMA ticks every 16 cycles
+
+        for _ in 0..3 {
+            emu.step();
+        }
+        assert_eq!(emu.bus.read(0xFF05), 0xFF);
+
+        // The overflow at cycle 16 falls in ADD HL's second M-cycle
+        let hl = emu.cpu.regs.hl();
+        assert_eq!(emu.step(), 8);
+        assert_eq!(emu.cycles, 20);
+        assert_eq!(emu.cpu.regs.hl(), hl.wrapping_add(emu.cpu.regs.bc()));
+        assert_eq!(emu.bus.read(0xFF05), 0x42);
+        assert_eq!(emu.bus.read(0xFF0F) & 0x04, 0x04);
+    }
+
+    #[test]
+    fn test_framebuffer() {
+        let mut emu = Emulator::with_rom(&vec![0u8; 0x8000]);
+        // Tile 0 row 0 = color 3; the map is all tile 0
+        emu.bus.write(0xFF40, 0x00);
+        emu.bus.write(0x8000, 0xFF);
+        emu.bus.write(0x8001, 0xFF);
+        emu.bus.write(0xFF47, 0xE4);
+        emu.bus.write(0xFF40, 0x91);
+        emu.run_frame();
+        emu.run_frame();
+
+        let frame = emu.framebuffer();
+        assert_eq!(frame.len(), 160 * 144);
+        assert!(frame[..160].iter().all(|&c| c == 3));
+        assert!(frame[160..320].iter().all(|&c| c == 0));
+        assert!(frame[8 * 160..8 * 160 + 160].iter().all(|&c| c == 3));
+    }
+
+    #[test]
+    fn test_poll_new_serial() {
+        let mut emu = Emulator::with_rom(&vec![0u8; 0x8000]);
+        let send = |emu: &mut Emulator, bytes: &[u8]| {
+            for &byte in bytes {
+                emu.bus.write(0xFF01, byte);
+                emu.bus.write(0xFF02, 0x81);
+            }
+        };
+
+        assert_eq!(emu.poll_new_serial(), b"");
+        send(&mut emu, b"Hello");
+        assert_eq!(emu.poll_new_serial(), b"Hello");
+        assert_eq!(emu.poll_new_serial(), b"");
+        send(&mut emu, b", world");
+        assert_eq!(emu.poll_new_serial(), b", world");
+
+        // With a cap, only the bytes still buffered can be returned
+        emu.bus.set_serial_buffer_cap(3);
+        send(&mut emu, b"abcde");
+        assert_eq!(emu.poll_new_serial(), b"cde");
+
+        // A replaced serial port starts over instead of underflowing
+        emu.bus.serial = crate::serial::Serial::new();
+        send(&mut emu, b"OK");
+        assert_eq!(emu.poll_new_serial(), b"OK");
+        assert_eq!(emu.poll_new_serial(), b"");
+    }
+
+    #[test]
+    fn test_exit_on_dead_halt() {
+        let mut rom = vec![0u8; 0x8000];
+        rom[0x0100..0x0104].copy_from_slice(&[
+            0xF3,       // DI
+            0xAF,       // XOR A
+            0xE0, 0xFF, // LDH (0xFF), A  -> IE = 0
+        ]);
+        rom[0x0104] = 0x76; // HALT
+
+        let mut emu = Emulator::with_rom(&rom);
+        emu.run_until_halt(1_000);
+        assert!(emu.cpu.halt_with_no_wake_possible(&emu.bus));
+        assert!(!emu.in_self_loop());
+
+        // run_frame keeps idling in HALT unless told to give up
+        emu.run_frame();
+        assert!(emu.cycles > CYCLES_PER_FRAME / 2);
+        let cycles = emu.cycles;
+        emu.set_exit_on_dead_halt(true);
+        emu.run_frame();
+        assert_eq!(emu.cycles, cycles);
+
+        // An enabled interrupt could still wake it
+        emu.bus.write(0xFFFF, 0x04);
+        assert!(!emu.cpu.halt_with_no_wake_possible(&emu.bus));
+    }
+
+    #[test]
+    fn test_vblank_interrupt_jumps_to_vector() {
+        let mut rom = vec![0u8; 0x8000];
+        rom[0x0100..0x0106].copy_from_slice(&[
+            0x3E, 0x01, // LD A, 0x01
+            0xE0, 0xFF, // LDH (0xFF), A  -> IE = V-Blank
+            0xFB,       // EI
+            0x00,       // NOP
+        ]);
+        rom[0x0106..0x0108].copy_from_slice(&[0x18, 0xFE]); // JR -2
+        let mut emu = Emulator::with_rom(&rom);
+
+        while emu.cpu.regs.pc != 0x0040 && emu.cycles < 2 * CYCLES_PER_FRAME {
+            emu.step();
+        }
+        assert_eq!(emu.cpu.regs.pc, 0x0040);
+        assert_eq!(emu.cpu.last_step_serviced_interrupt(), Some(InterruptKind::VBlank));
+        // V-Blank starts after the 144 visible lines of the first frame
+        assert!(emu.cycles >= 144 * DOTS_PER_LINE as u64 && emu.cycles <= CYCLES_PER_FRAME);
+    }
+
+    #[test]
+    fn test_serial_byte_callback() {
+        use std::cell::RefCell;
+        use std::rc::Rc;
+
+        let mut rom = vec![0u8; 0x8000];
+        let program: &[u8] = &[
+            0x3E, b'O',       // LD A, 'O'
+            0xE0, 0x01,       // LDH (0x01), A
+            0x3E, 0x81,       // LD A, 0x81
+            0xE0, 0x02,       // LDH (0x02), A
+            0x00, 0x00, 0x00, // NOP x3
+            0x3E, b'K',       // LD A, 'K'
+            0xE0, 0x01,       // LDH (0x01), A
+            0x3E, 0x81,       // LD A, 0x81
+            0xE0, 0x02,       // LDH (0x02), A
+            0x18, 0xFE,       // JR -2
+        ];
+        rom[0x0100..0x0100 + program.len()].copy_from_slice(program);
+        let mut emu = Emulator::with_rom(&rom);
+
+        let bytes = Rc::new(RefCell::new(Vec::new()));
+        let sink = bytes.clone();
+        emu.bus.set_serial_byte_callback(Box::new(move |byte, cycles| sink.borrow_mut().push((byte, cycles))));
+        emu.run_cycles(200);
+
+        // Timestamps are the cycle counts at the start of each LDH (0x02), A
+        assert_eq!(*bytes.borrow(), vec![(b'O', 28), (b'K', 80)]);
+        assert_eq!(emu.get_serial_output(), "OK");
     }
 }
@@ -26,6 +26,52 @@
 // - EI enables interrupts after the NEXT instruction (1 instruction delay)
 // - HALT wakes up when (IE & IF) != 0, even if IME is false
 
+/// Interrupt sources, in priority order (highest first)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum InterruptKind {
+    VBlank,  // Bit 0
+    LcdStat, // Bit 1
+    Timer,   // Bit 2
+    Serial,  // Bit 3
+    Joypad,  // Bit 4
+}
+
+impl InterruptKind {
+    /// All interrupt sources, in priority order (highest first)
+    pub const ALL: [InterruptKind; 5] = [
+        InterruptKind::VBlank,
+        InterruptKind::LcdStat,
+        InterruptKind::Timer,
+        InterruptKind::Serial,
+        InterruptKind::Joypad,
+    ];
+
+    /// Bit position in the IE/IF registers (0-4)
+    pub const fn if_bit(self) -> u8 {
+        self as u8
+    }
+
+    /// Bit mask in the IE/IF registers (0x01, 0x02, ...)
+    pub const fn mask(self) -> u8 {
+        1 << self.if_bit()
+    }
+
+    /// Handler address the CPU jumps to (0x0040 + 8 * bit)
+    pub const fn vector(self) -> u16 {
+        0x0040 + 8 * self.if_bit() as u16
+    }
+
+    /// Get the interrupt kind for a single IE/IF bit mask (0x01, 0x02, ...)
+    pub fn from_bit(bit: u8) -> Option<Self> {
+        Self::ALL.into_iter().find(|kind| kind.mask() == bit)
+    }
+
+    /// Get the highest priority interrupt set in an IE & IF mask
+    pub fn highest_pending(pending: u8) -> Option<Self> {
+        Self::ALL.into_iter().find(|kind| pending & kind.mask() != 0)
+    }
+}
+
 /// Interrupt bit flags
 #[derive(Debug, Clone, Copy)]
 pub struct InterruptFlags {
@@ -75,29 +121,15 @@ impl Default for InterruptFlags {
 }
 
 /// Interrupt vectors
-pub const VBLANK_VECTOR: u16 = 0x0040;
-pub const LCD_STAT_VECTOR: u16 = 0x0048;
-pub const TIMER_VECTOR: u16 = 0x0050;
-pub const SERIAL_VECTOR: u16 = 0x0058;
-pub const JOYPAD_VECTOR: u16 = 0x0060;
+pub const VBLANK_VECTOR: u16 = InterruptKind::VBlank.vector();
+pub const LCD_STAT_VECTOR: u16 = InterruptKind::LcdStat.vector();
+pub const TIMER_VECTOR: u16 = InterruptKind::Timer.vector();
+pub const SERIAL_VECTOR: u16 = InterruptKind::Serial.vector();
+pub const JOYPAD_VECTOR: u16 = InterruptKind::Joypad.vector();
 
-/// Get the vector address for the highest priority pending interrupt
+/// Get the vector address and bit mask of the highest priority pending interrupt
 pub fn get_interrupt_vector(ie: u8, if_reg: u8) -> Option<(u16, u8)> {
-    let pending = ie & if_reg;
-
-    if pending & 0x01 != 0 {
-        Some((VBLANK_VECTOR, 0x01))
-    } else if pending & 0x02 != 0 {
-        Some((LCD_STAT_VECTOR, 0x02))
-    } else if pending & 0x04 != 0 {
-        Some((TIMER_VECTOR, 0x04))
-    } else if pending & 0x08 != 0 {
-        Some((SERIAL_VECTOR, 0x08))
-    } else if pending & 0x10 != 0 {
-        Some((JOYPAD_VECTOR, 0x10))
-    } else {
-        None
-    }
+    InterruptKind::highest_pending(ie & if_reg).map(|kind| (kind.vector(), kind.mask()))
 }
 
 #[cfg(test)]
@@ -144,4 +176,29 @@ mod tests {
         let (vector, _) = get_interrupt_vector(ie, if_reg).unwrap();
         assert_eq!(vector, TIMER_VECTOR);
     }
+
+    #[test]
+    fn test_interrupt_kind_from_bit() {
+        assert_eq!(InterruptKind::from_bit(0x01), Some(InterruptKind::VBlank));
+        assert_eq!(InterruptKind::from_bit(0x10), Some(InterruptKind::Joypad));
+        assert_eq!(InterruptKind::from_bit(0x03), None);
+    }
+
+    #[test]
+    fn test_interrupt_kind_table() {
+        let expected = [
+            (InterruptKind::VBlank, 0x0040, 0x01),
+            (InterruptKind::LcdStat, 0x0048, 0x02),
+            (InterruptKind::Timer, 0x0050, 0x04),
+            (InterruptKind::Serial, 0x0058, 0x08),
+            (InterruptKind::Joypad, 0x0060, 0x10),
+        ];
+        assert_eq!(InterruptKind::ALL.len(), expected.len());
+        for (bit, (kind, vector, mask)) in expected.into_iter().enumerate() {
+            assert_eq!(InterruptKind::ALL[bit], kind);
+            assert_eq!(kind.vector(), vector, "{:?}", kind);
+            assert_eq!(kind.mask(), mask, "{:?}", kind);
+            assert_eq!(kind.if_bit(), bit as u8, "{:?}", kind);
+        }
+    }
 }
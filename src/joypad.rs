@@ -18,45 +18,79 @@
 //   2. Reading to get button states (bits 0-3)
 //
 // Note: 0 = pressed, 1 = not pressed (active low)
+//
+// With both groups selected, a line reads 0 if the button of either group on
+// it is pressed (the groups are ANDed). With neither selected, bits 0-3 read 1.
+//
+// Remapping: buttons can be remapped in the core (e.g. A -> B). The physical
+// button state is stored and the remap is applied when the lines are read, so
+// the register, the select lines and the joypad interrupt all see the
+// remapped button, and two buttons mapped to the same target don't release
+// each other.
 
 /// Joypad state
 #[derive(Debug, Clone, Copy, Default)]
 pub struct Joypad {
-    /// Direction buttons (active low internally)
-    /// Bit 0: Right, Bit 1: Left, Bit 2: Up, Bit 3: Down
-    directions: u8,
-    /// Action buttons (active low internally)
-    /// Bit 0: A, Bit 1: B, Bit 2: Select, Bit 3: Start
-    actions: u8,
+    /// Physical buttons held, one bit per `Button` in declaration order
+    /// (bits 0-3: Right, Left, Up, Down; bits 4-7: A, B, Select, Start)
+    pressed: u8,
     /// Button group selection
     /// Bit 4: Select directions, Bit 5: Select actions
     select: u8,
     /// Joypad interrupt pending
     pub interrupt: bool,
+    /// Button remap, indexed by the physical button (None = unchanged)
+    remap: [Option<Button>; 8],
 }
 
 impl Joypad {
     pub fn new() -> Self {
         Self {
-            directions: 0x0F, // All released (1 = not pressed)
-            actions: 0x0F,    // All released
-            select: 0x30,     // Neither group selected
+            pressed: 0,   // All released
+            select: 0x30, // Neither group selected
             interrupt: false,
+            remap: [None; 8],
         }
     }
 
+    /// Remap a button: pressing `from` acts as pressing `to`
+    pub fn set_remap(&mut self, from: Button, to: Button) {
+        self.remap[from.index()] = Some(to);
+    }
+
+    /// Remove all button remaps
+    pub fn clear_remap(&mut self) {
+        self.remap = [None; 8];
+    }
+
+    /// Remapped button lines as (directions, actions), active low
+    /// Directions: bit 0 Right, 1 Left, 2 Up, 3 Down
+    /// Actions: bit 0 A, 1 B, 2 Select, 3 Start
+    fn lines(&self) -> (u8, u8) {
+        let mut low = 0u8;
+        for (index, target) in self.remap.iter().enumerate() {
+            if self.pressed & (1 << index) != 0 {
+                let index = target.map_or(index, |button| button.index());
+                low |= 1 << index;
+            }
+        }
+        (!low & 0x0F, !(low >> 4) & 0x0F)
+    }
+
     /// Read the joypad register (0xFF00)
     pub fn read(&self) -> u8 {
         let mut result = 0xCF; // Bits 7-6 always 1, bits 3-0 start as 1
+        let (directions, actions) = self.lines();
 
-        // Check which button group is selected (active low)
+        // Check which button group is selected (active low); a selected
+        // group pulls its pressed lines low, so two groups combine with AND
         if self.select & 0x10 == 0 {
             // Direction buttons selected
-            result = (result & 0xF0) | (self.directions & 0x0F);
+            result &= 0xF0 | directions;
         }
         if self.select & 0x20 == 0 {
             // Action buttons selected
-            result = (result & 0xF0) | (self.actions & 0x0F);
+            result &= 0xF0 | actions;
         }
 
         // Include selection bits
@@ -74,17 +108,7 @@ impl Joypad {
     /// Press a button
     pub fn press(&mut self, button: Button) {
         let old_state = self.read() & 0x0F;
-
-        match button {
-            Button::Right => self.directions &= !0x01,
-            Button::Left => self.directions &= !0x02,
-            Button::Up => self.directions &= !0x04,
-            Button::Down => self.directions &= !0x08,
-            Button::A => self.actions &= !0x01,
-            Button::B => self.actions &= !0x02,
-            Button::Select => self.actions &= !0x04,
-            Button::Start => self.actions &= !0x08,
-        }
+        self.pressed |= 1 << button.index();
 
         // Check if any button went from high to low (interrupt condition)
         let new_state = self.read() & 0x0F;
@@ -95,16 +119,7 @@ impl Joypad {
 
     /// Release a button
     pub fn release(&mut self, button: Button) {
-        match button {
-            Button::Right => self.directions |= 0x01,
-            Button::Left => self.directions |= 0x02,
-            Button::Up => self.directions |= 0x04,
-            Button::Down => self.directions |= 0x08,
-            Button::A => self.actions |= 0x01,
-            Button::B => self.actions |= 0x02,
-            Button::Select => self.actions |= 0x04,
-            Button::Start => self.actions |= 0x08,
-        }
+        self.pressed &= !(1 << button.index());
     }
 
     /// Update button state (true = pressed)
@@ -137,6 +152,13 @@ pub enum Button {
     Start,
 }
 
+impl Button {
+    /// Position in declaration order (0-7)
+    fn index(self) -> usize {
+        self as usize
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -207,4 +229,61 @@ mod tests {
         joypad.write(0x00);
         assert_eq!(joypad.read() & 0x0F, 0x0E); // Both show (AND together)
     }
+
+    #[test]
+    fn test_both_groups_combine_lines() {
+        let mut joypad = Joypad::new();
+
+        // Down (direction bit 3) and B (action bit 1) are on different lines
+        joypad.press(Button::Down);
+        joypad.press(Button::B);
+
+        joypad.write(0x00);
+        assert_eq!(joypad.read(), 0xC5);
+
+        // Neither group selected: all lines read high
+        joypad.write(0x30);
+        assert_eq!(joypad.read(), 0xFF);
+    }
+
+    #[test]
+    fn test_remap() {
+        let mut joypad = Joypad::new();
+        joypad.write(0x10); // Select action buttons
+        joypad.set_remap(Button::A, Button::B);
+
+        joypad.press(Button::A);
+        assert_eq!(joypad.read() & 0x03, 0x01); // B low, A high
+
+        // Remapping across groups follows the select lines of the target
+        joypad.set_remap(Button::Start, Button::Down);
+        joypad.press(Button::Start);
+        assert_eq!(joypad.read() & 0x0F, 0x0D); // Only B shows in the action group
+        joypad.write(0x20); // Select directions
+        assert_eq!(joypad.read() & 0x0F, 0x07); // Down
+        joypad.release(Button::Start);
+        joypad.write(0x10);
+
+        joypad.release(Button::A);
+        assert_eq!(joypad.read() & 0x0F, 0x0F);
+
+        joypad.clear_remap();
+        joypad.press(Button::A);
+        assert_eq!(joypad.read() & 0x03, 0x02);
+    }
+
+    #[test]
+    fn test_remap_shared_target() {
+        let mut joypad = Joypad::new();
+        joypad.write(0x10); // Select action buttons
+        joypad.set_remap(Button::A, Button::B);
+
+        // Physical B and A (-> B) both hold the B line
+        joypad.press(Button::B);
+        joypad.press(Button::A);
+        joypad.release(Button::A);
+        assert_eq!(joypad.read() & 0x03, 0x01); // B still low
+        joypad.release(Button::B);
+        assert_eq!(joypad.read() & 0x03, 0x03);
+    }
 }
@@ -2,12 +2,19 @@
 //
 // This module exports the emulator components for use in tests and external code.
 
+pub mod apu;
+pub mod bess;
 pub mod bus;
 pub mod cartridge;
 pub mod cpu;
+pub mod disasm;
 pub mod emulator;
 pub mod interrupts;
 pub mod joypad;
 pub mod mbc;
+pub mod model;
 pub mod ppu;
+pub mod rng;
+pub mod serial;
 pub mod timer;
+pub mod trace;
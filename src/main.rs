@@ -8,14 +8,20 @@
 //   Display: 160x144 pixels, 4 shades of gray
 //   Sound: 4 channels (2 pulse, 1 wave, 1 noise)
 
+mod apu;
+mod bess;
 mod bus;
 mod cartridge;
 mod cpu;
+mod disasm;
 mod emulator;
 mod interrupts;
 mod joypad;
 mod mbc;
+mod model;
 mod ppu;
+mod rng;
+mod serial;
 mod timer;
 
 use bus::Bus;
@@ -61,6 +67,9 @@ fn main() {
                 cart.info.header_checksum,
                 if cart.info.checksum_valid { "valid" } else { "INVALID" }
             );
+            if let Some(warning) = cart.detect_mbc().warning {
+                println!("  Warning: {}", warning);
+            }
 
             if gui_mode {
                 run_gui(&cart, debug_mode);
@@ -95,15 +104,17 @@ fn run_rom(cart: &Cartridge, debug: bool) {
     println!("\n--- Executing ROM ---\n");
 
     let mut emu = Emulator::new(cart);
+    emu.set_exit_on_self_loop(true);
+    emu.set_exit_on_dead_halt(true);
 
     // Maximum cycles to run (about 1200 seconds of emulated time)
     // 4.194304 MHz * 1200 seconds = ~5 billion cycles
-    let max_cycles: u64 = 5_000_000_000;
+    let max_cycles = emu.cycle_cap(5_000_000_000);
 
-    let mut last_output_len = 0;
     let mut instructions_executed = 0u64;
 
-    while emu.cycles < max_cycles {
+    // Test ROMs end in a `jr -2` loop or a HALT nothing can wake; the run stops there
+    while emu.cycles < max_cycles && emu.keep_running() {
         if debug && instructions_executed % 100_000 == 0 {
             let ie = emu.bus.read(0xFFFF);
             let if_reg = emu.bus.read(0xFF0F);
@@ -117,19 +128,22 @@ fn run_rom(cart: &Cartridge, debug: bool) {
         instructions_executed += 1;
 
         // Check for new serial output
-        let output = emu.get_serial_output();
-        if output.len() > last_output_len {
-            let new_chars = &output[last_output_len..];
-            print!("{}", new_chars);
-            last_output_len = output.len();
+        let new_bytes = emu.poll_new_serial();
+        if !new_bytes.is_empty() {
+            print!("{}", String::from_utf8_lossy(new_bytes));
 
             // Check for test completion
-            if output.contains("Passed") || output.contains("Failed") {
+            if emu.test_passed() || emu.test_failed() {
                 println!();
                 break;
             }
         }
 
+        if let Some(error) = emu.cpu_error() {
+            println!("\n[Stopped: {}]", error);
+            break;
+        }
+
         // Safety check for infinite loops without output
         if instructions_executed > 500_000_000 {
             println!("\n[Timeout: 500M instructions without completion]");
@@ -137,11 +151,22 @@ fn run_rom(cart: &Cartridge, debug: bool) {
         }
     }
 
+    if emu.in_self_loop() {
+        println!("\n[Stopped: CPU in a self-loop at 0x{:04X}]", emu.cpu.regs.pc);
+    } else if emu.cpu.halt_with_no_wake_possible(&emu.bus) {
+        println!("\n[Stopped: CPU halted with no interrupts enabled]");
+    }
+
     println!("\n--- Execution Summary ---");
     println!("  Instructions: {}", instructions_executed);
     println!("  Cycles: {}", emu.cycles);
     println!("  CPU halted: {}", emu.cpu.halted);
 
+    if debug {
+        println!("\n--- Hardware State ---");
+        println!("{}", emu.state_report());
+    }
+
     let output = emu.get_serial_output();
     if !output.is_empty() {
         println!("\n--- Serial Output ---");
@@ -215,14 +240,14 @@ fn run_gui(cart: &Cartridge, debug: bool) {
 
         // Run emulator for one frame
         let target_cycles = emu.cycles + cycles_per_frame;
-        while emu.cycles < target_cycles {
+        while emu.cycles < target_cycles && emu.cpu_error().is_none() {
             emu.step();
         }
 
         // Convert framebuffer to ARGB and scale
         for y in 0..SCREEN_HEIGHT {
             for x in 0..SCREEN_WIDTH {
-                let color_index = emu.bus.ppu.framebuffer[y * SCREEN_WIDTH + x] as usize;
+                let color_index = emu.framebuffer()[y * SCREEN_WIDTH + x] as usize;
                 let color = PALETTE[color_index & 3];
 
                 // Scale up the pixel
@@ -318,7 +343,7 @@ fn run_demo() {
 
     while !cpu.halted && inst_idx < instructions.len() {
         let pc_before = cpu.regs.pc;
-        let cycles = cpu.step(&mut bus);
+        let cycles = cpu.step_unchecked(&mut bus);
         total_cycles += cycles;
 
         println!(
@@ -15,12 +15,19 @@
 //   0x2000-0x3FFF: ROM Bank Number (lower 5 bits)
 //   0x4000-0x5FFF: RAM Bank Number OR upper ROM bank bits
 //   0x6000-0x7FFF: Banking Mode Select (0=ROM, 1=RAM)
+//
+// The 2-bit secondary register (0x4000-0x5FFF) always supplies ROM bank
+// bits 5-6 for the 0x4000-0x7FFF region. In mode 1 it is additionally
+// applied to the 0x0000-0x3FFF region (bank 0x00/0x20/0x40/0x60) and
+// selects the RAM bank. Each use is masked to what the cartridge has, so it
+// effectively only matters for ROMs over 512KB or RAM over 8KB.
 
 use super::Mbc;
 
 pub struct Mbc1 {
     rom: Vec<u8>,
     ram: Vec<u8>,
+    ram_size: usize,    // Actual cartridge RAM size (ram is padded to 8KB)
     ram_enabled: bool,
     rom_bank: u8,      // Lower 5 bits of ROM bank
     ram_bank: u8,      // RAM bank OR upper 2 bits of ROM bank
@@ -34,6 +41,7 @@ impl Mbc1 {
         Self {
             rom,
             ram: vec![0; ram_size.max(0x2000)], // At least 8KB for simplicity
+            ram_size,
             ram_enabled: false,
             rom_bank: 1,
             ram_bank: 0,
@@ -68,10 +76,11 @@ impl Mbc1 {
         bank % self.rom_bank_count
     }
 
-    /// Get the effective RAM bank
+    /// Get the effective RAM bank (wraps on carts with less than 4 banks)
     fn effective_ram_bank(&self) -> usize {
         if self.banking_mode {
-            (self.ram_bank as usize) & 0x03
+            let ram_bank_count = (self.ram.len() / 0x2000).max(1);
+            (self.ram_bank as usize & 0x03) % ram_bank_count
         } else {
             0
         }
@@ -173,6 +182,19 @@ impl Mbc for Mbc1 {
     fn current_ram_bank(&self) -> usize {
         self.effective_ram_bank()
     }
+
+    fn rom(&self) -> &[u8] {
+        &self.rom
+    }
+
+    fn ram(&self) -> &[u8] {
+        &self.ram[..self.ram_size]
+    }
+
+    fn load_ram(&mut self, data: &[u8]) {
+        let len = data.len().min(self.ram_size);
+        self.ram[..len].copy_from_slice(&data[..len]);
+    }
 }
 
 #[cfg(test)]
@@ -229,6 +251,68 @@ mod tests {
         assert_eq!(mbc.current_rom_bank(), 1);
     }
 
+    #[test]
+    fn test_mode_1_remaps_bank_0_region() {
+        // 1MB ROM: 64 banks, needs the secondary register for banks 0x20+
+        let rom = create_test_rom(64);
+        let mut mbc = Mbc1::new(rom, 0x2000);
+
+        mbc.write(0x4000, 0x01); // Secondary register = 1
+        mbc.write(0x2000, 0x00); // Lower bits 0 -> treated as 1
+
+        // Mode 0: 0x0000 region stays at bank 0, switchable region is 0x21
+        assert_eq!(mbc.read(0x0000), 0x00);
+        assert_eq!(mbc.read(0x4000), 0x21);
+
+        // Mode 1: 0x0000 region follows the secondary register
+        mbc.write(0x6000, 0x01);
+        assert_eq!(mbc.read(0x0000), 0x20);
+        assert_eq!(mbc.read(0x4000), 0x21);
+
+        // Upper bits beyond the ROM size are masked off
+        mbc.write(0x4000, 0x02);
+        assert_eq!(mbc.read(0x0000), 0x00);
+
+        // Back to mode 0
+        mbc.write(0x4000, 0x01);
+        mbc.write(0x6000, 0x00);
+        assert_eq!(mbc.read(0x0000), 0x00);
+    }
+
+    #[test]
+    fn test_mode_1_ram_banking() {
+        let rom = create_test_rom(4);
+        let mut mbc = Mbc1::new(rom, 0x8000);
+        mbc.write(0x0000, 0x0A);
+
+        // Mode 0: always RAM bank 0
+        mbc.write(0x4000, 0x02);
+        mbc.write(0xA000, 0x11);
+        assert_eq!(mbc.current_ram_bank(), 0);
+
+        // Mode 1: secondary register selects the RAM bank
+        mbc.write(0x6000, 0x01);
+        assert_eq!(mbc.current_ram_bank(), 2);
+        mbc.write(0xA000, 0x22);
+        assert_eq!(mbc.read(0xA000), 0x22);
+
+        mbc.write(0x4000, 0x00);
+        assert_eq!(mbc.read(0xA000), 0x11);
+    }
+
+    #[test]
+    fn test_mode_1_small_ram_wraps() {
+        let rom = create_test_rom(4);
+        let mut mbc = Mbc1::new(rom, 0x2000);
+        mbc.write(0x0000, 0x0A);
+        mbc.write(0xA000, 0x42);
+
+        // Only one 8KB bank: any secondary register value maps to it
+        mbc.write(0x6000, 0x01);
+        mbc.write(0x4000, 0x03);
+        assert_eq!(mbc.read(0xA000), 0x42);
+    }
+
     #[test]
     fn test_ram_enable() {
         let mut rom = create_test_rom(2);
@@ -15,23 +15,120 @@
 //   0x2000-0x3FFF: ROM Bank Number (7 bits, 0x01-0x7F)
 //   0x4000-0x5FFF: RAM Bank Number (0x00-0x03) or RTC Register Select (0x08-0x0C)
 //   0x6000-0x7FFF: Latch Clock Data (write 0x00 then 0x01 to latch)
+//
+// RTC Registers (selected with 0x08-0x0C at 0x4000-0x5FFF):
+//   0x08: Seconds (0-59)
+//   0x09: Minutes (0-59)
+//   0x0A: Hours (0-23)
+//   0x0B: Day counter, lower 8 bits
+//   0x0C: Bit 0 = day counter bit 8, Bit 6 = halt, Bit 7 = day counter carry
+//
+// Reads return the latched copy; latching copies the live clock into it.
+//
+// Save files (VBA-M/BGB layout) append a 48-byte RTC block after the RAM:
+//   5 x u32 LE: live S, M, H, DL, DH
+//   5 x u32 LE: latched S, M, H, DL, DH
+//   u64 LE:     host UNIX timestamp at save time
 
 use super::Mbc;
 
+/// Size of the RTC block appended to save files
+pub const RTC_SAVE_SIZE: usize = 48;
+
+/// MBC3 real-time clock registers
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Rtc {
+    /// Live registers (S, M, H, DL, DH)
+    pub registers: [u8; 5],
+    /// Latched registers visible to reads
+    pub latched: [u8; 5],
+}
+
+impl Rtc {
+    /// Day counter (9 bits)
+    pub fn days(&self) -> u16 {
+        (self.registers[4] as u16 & 0x01) << 8 | self.registers[3] as u16
+    }
+
+    /// Check if the clock is halted (DH bit 6)
+    pub fn halted(&self) -> bool {
+        self.registers[4] & 0x40 != 0
+    }
+
+    /// Copy the live registers into the latched registers
+    pub fn latch(&mut self) {
+        self.latched = self.registers;
+    }
+
+    /// Advance the live clock by the given number of seconds
+    /// Day counter overflow past 511 sets the carry bit
+    pub fn advance(&mut self, seconds: u64) {
+        if self.halted() || seconds == 0 {
+            return;
+        }
+
+        let [s, m, h, _, dh] = self.registers;
+        let mut total = s as u64 + m as u64 * 60 + h as u64 * 3600 + self.days() as u64 * 86400;
+        total += seconds;
+
+        let mut days = total / 86400;
+        let mut carry = dh & 0x80;
+        if days > 0x1FF {
+            carry = 0x80;
+            days &= 0x1FF;
+        }
+
+        self.registers = [
+            (total % 60) as u8,
+            (total / 60 % 60) as u8,
+            (total / 3600 % 24) as u8,
+            days as u8,
+            carry | (dh & 0x40) | (days >> 8) as u8,
+        ];
+    }
+
+    /// Encode the 48-byte save block with the given host timestamp
+    pub fn to_save_block(&self, timestamp: u64) -> [u8; RTC_SAVE_SIZE] {
+        let mut block = [0u8; RTC_SAVE_SIZE];
+        for (i, &value) in self.registers.iter().chain(self.latched.iter()).enumerate() {
+            block[i * 4..i * 4 + 4].copy_from_slice(&(value as u32).to_le_bytes());
+        }
+        block[40..48].copy_from_slice(&timestamp.to_le_bytes());
+        block
+    }
+
+    /// Decode a save block into (rtc, host timestamp)
+    /// Accepts the 48-byte layout and the older 44-byte one with a 32-bit timestamp
+    pub fn from_save_block(block: &[u8]) -> Option<(Self, u64)> {
+        let timestamp = match block.len() {
+            48 => u64::from_le_bytes(block[40..48].try_into().ok()?),
+            44 => u32::from_le_bytes(block[40..44].try_into().ok()?) as u64,
+            _ => return None,
+        };
+
+        let mut rtc = Rtc::default();
+        for i in 0..10 {
+            let value = block[i * 4];
+            if i < 5 {
+                rtc.registers[i] = value;
+            } else {
+                rtc.latched[i - 5] = value;
+            }
+        }
+        Some((rtc, timestamp))
+    }
+}
+
 pub struct Mbc3 {
     rom: Vec<u8>,
     ram: Vec<u8>,
+    ram_size: usize,    // Actual cartridge RAM size (ram is padded to 8KB)
     ram_enabled: bool,
     rom_bank: u8,
     ram_bank: u8,      // Also used for RTC register select
-    rtc_latched: bool,
     latch_prepare: bool,
-    // RTC registers (not fully implemented)
-    rtc_s: u8,
-    rtc_m: u8,
-    rtc_h: u8,
-    rtc_dl: u8,
-    rtc_dh: u8,
+    rtc: Rtc,
+    has_rtc: bool,     // MBC3+TIMER cartridges persist the RTC
     rom_bank_count: usize,
 }
 
@@ -41,20 +138,25 @@ impl Mbc3 {
         Self {
             rom,
             ram: vec![0; ram_size.max(0x2000)],
+            ram_size,
             ram_enabled: false,
             rom_bank: 1,
             ram_bank: 0,
-            rtc_latched: false,
             latch_prepare: false,
-            rtc_s: 0,
-            rtc_m: 0,
-            rtc_h: 0,
-            rtc_dl: 0,
-            rtc_dh: 0,
+            rtc: Rtc::default(),
+            has_rtc: false,
             rom_bank_count,
         }
     }
 
+    /// Create an MBC3 with a real-time clock (MBC3+TIMER)
+    pub fn with_rtc(rom: Vec<u8>, ram_size: usize) -> Self {
+        Self {
+            has_rtc: true,
+            ..Self::new(rom, ram_size)
+        }
+    }
+
     fn effective_rom_bank(&self) -> usize {
         let bank = if self.rom_bank == 0 { 1 } else { self.rom_bank as usize };
         bank % self.rom_bank_count
@@ -62,24 +164,23 @@ impl Mbc3 {
 
     fn read_rtc(&self) -> u8 {
         match self.ram_bank {
-            0x08 => self.rtc_s,
-            0x09 => self.rtc_m,
-            0x0A => self.rtc_h,
-            0x0B => self.rtc_dl,
-            0x0C => self.rtc_dh,
+            0x08..=0x0C => self.rtc.latched[(self.ram_bank - 0x08) as usize],
             _ => 0xFF,
         }
     }
 
     fn write_rtc(&mut self, value: u8) {
-        match self.ram_bank {
-            0x08 => self.rtc_s = value & 0x3F,
-            0x09 => self.rtc_m = value & 0x3F,
-            0x0A => self.rtc_h = value & 0x1F,
-            0x0B => self.rtc_dl = value,
-            0x0C => self.rtc_dh = value & 0xC1,
-            _ => {}
-        }
+        let mask = match self.ram_bank {
+            0x08 | 0x09 => 0x3F,
+            0x0A => 0x1F,
+            0x0B => 0xFF,
+            0x0C => 0xC1,
+            _ => return,
+        };
+        // Writes go to the live clock and are visible immediately
+        let index = (self.ram_bank - 0x08) as usize;
+        self.rtc.registers[index] = value & mask;
+        self.rtc.latched[index] = value & mask;
     }
 }
 
@@ -155,8 +256,8 @@ impl Mbc for Mbc3 {
                 if !self.latch_prepare && value == 0x00 {
                     self.latch_prepare = true;
                 } else if self.latch_prepare && value == 0x01 {
-                    // Latch current time (not implemented - would copy current time to latched)
-                    self.rtc_latched = true;
+                    // Latch current time
+                    self.rtc.latch();
                     self.latch_prepare = false;
                 } else {
                     self.latch_prepare = false;
@@ -201,6 +302,27 @@ impl Mbc for Mbc3 {
             0
         }
     }
+
+    fn rom(&self) -> &[u8] {
+        &self.rom
+    }
+
+    fn ram(&self) -> &[u8] {
+        &self.ram[..self.ram_size]
+    }
+
+    fn load_ram(&mut self, data: &[u8]) {
+        let len = data.len().min(self.ram_size);
+        self.ram[..len].copy_from_slice(&data[..len]);
+    }
+
+    fn rtc(&self) -> Option<&Rtc> {
+        self.has_rtc.then_some(&self.rtc)
+    }
+
+    fn rtc_mut(&mut self) -> Option<&mut Rtc> {
+        self.has_rtc.then_some(&mut self.rtc)
+    }
 }
 
 #[cfg(test)]
@@ -232,6 +354,57 @@ mod tests {
         assert_eq!(mbc.read(0x4000), 5);
     }
 
+    #[test]
+    fn test_rtc_latch() {
+        let rom = create_test_rom(2);
+        let mut mbc = Mbc3::with_rtc(rom, 0);
+        mbc.write(0x0000, 0x0A);
+
+        mbc.write(0x4000, 0x09); // Minutes
+        mbc.write(0xA000, 30);
+        assert_eq!(mbc.read(0xA000), 30);
+
+        // Live clock moves on, latched value stays until the next latch
+        mbc.rtc_mut().unwrap().advance(60);
+        assert_eq!(mbc.read(0xA000), 30);
+        mbc.write(0x6000, 0x00);
+        mbc.write(0x6000, 0x01);
+        assert_eq!(mbc.read(0xA000), 31);
+    }
+
+    #[test]
+    fn test_rtc_advance() {
+        let mut rtc = Rtc {
+            registers: [59, 59, 23, 0xFF, 0x01], // Day 511, 23:59:59
+            latched: [0; 5],
+        };
+
+        rtc.advance(1);
+        assert_eq!(rtc.registers, [0, 0, 0, 0, 0x80]); // Day 0 with carry
+
+        rtc.advance(3 * 86400 + 3661);
+        assert_eq!(rtc.registers, [1, 1, 1, 3, 0x80]);
+        assert_eq!(rtc.days(), 3);
+
+        // Halted clocks don't move
+        rtc.registers[4] |= 0x40;
+        rtc.advance(100);
+        assert_eq!(rtc.registers[0], 1);
+    }
+
+    #[test]
+    fn test_rtc_save_block_round_trip() {
+        let rtc = Rtc {
+            registers: [1, 2, 3, 4, 0x41],
+            latched: [5, 6, 7, 8, 0x01],
+        };
+        let block = rtc.to_save_block(1_700_000_000);
+        assert_eq!(block.len(), RTC_SAVE_SIZE);
+        assert_eq!(&block[16..20], &[0x41, 0, 0, 0]);
+        assert_eq!(Rtc::from_save_block(&block), Some((rtc, 1_700_000_000)));
+        assert_eq!(Rtc::from_save_block(&block[..40]), None);
+    }
+
     #[test]
     fn test_ram_banking() {
         let rom = create_test_rom(2);
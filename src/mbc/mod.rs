@@ -15,9 +15,12 @@ mod mbc3;
 mod no_mbc;
 
 pub use mbc1::Mbc1;
-pub use mbc3::Mbc3;
+pub use mbc3::{Mbc3, Rtc};
 pub use no_mbc::NoMbc;
 
+/// Callback for writes to MBC control regions on a ROM-only cartridge (address, value)
+pub type RomWriteWarning = Box<dyn FnMut(u16, u8)>;
+
 /// Trait for Memory Bank Controllers
 pub trait Mbc {
     /// Read a byte from the cartridge
@@ -34,6 +37,32 @@ pub trait Mbc {
 
     /// Get the current RAM bank number (for debugging)
     fn current_ram_bank(&self) -> usize;
+
+    /// Raw cartridge ROM contents, regardless of banking
+    fn rom(&self) -> &[u8];
+
+    /// Cartridge RAM contents to persist (empty if the cartridge has no RAM)
+    fn ram(&self) -> &[u8] {
+        &[]
+    }
+
+    /// Restore cartridge RAM from a save (extra bytes are ignored)
+    fn load_ram(&mut self, _data: &[u8]) {}
+
+    /// Real-time clock, if the cartridge has one
+    fn rtc(&self) -> Option<&Rtc> {
+        None
+    }
+
+    /// Mutable real-time clock, if the cartridge has one
+    fn rtc_mut(&mut self) -> Option<&mut Rtc> {
+        None
+    }
+
+    /// Warn through `hook` when a ROM-only cartridge gets a write that looks
+    /// like an MBC command (a hint the header's cartridge type is wrong)
+    /// Cartridges with an MBC ignore this.
+    fn set_rom_write_warning(&mut self, _hook: RomWriteWarning) {}
 }
 
 /// Create an MBC based on cartridge type
@@ -54,8 +83,8 @@ pub fn create_mbc(cartridge_type: u8, rom: Vec<u8>, ram_size: usize) -> Box<dyn
         }
 
         // MBC3
-        0x0F => Box::new(Mbc3::new(rom, 0)),           // MBC3+TIMER+BATTERY
-        0x10 => Box::new(Mbc3::new(rom, ram_size)),    // MBC3+TIMER+RAM+BATTERY
+        0x0F => Box::new(Mbc3::with_rtc(rom, 0)),         // MBC3+TIMER+BATTERY
+        0x10 => Box::new(Mbc3::with_rtc(rom, ram_size)),  // MBC3+TIMER+RAM+BATTERY
         0x11 => Box::new(Mbc3::new(rom, 0)),           // MBC3
         0x12 => Box::new(Mbc3::new(rom, ram_size)),    // MBC3+RAM
         0x13 => Box::new(Mbc3::new(rom, ram_size)),    // MBC3+RAM+BATTERY
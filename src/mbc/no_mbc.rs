@@ -2,16 +2,26 @@
 //
 // Simple cartridges with no memory bank controller.
 // Maximum 32KB ROM, no external RAM.
+//
+// ROM writes are ignored. Writes to the regions MBCs use for bank and mode
+// selection (0x2000-0x7FFF) can optionally be reported: a game doing that
+// probably expects an MBC its header doesn't declare. Some ROM-only games
+// write there anyway (e.g. Tetris writes 0x2000), so it is only a hint.
 
-use super::Mbc;
+use super::{Mbc, RomWriteWarning};
 
 pub struct NoMbc {
     rom: Vec<u8>,
+    /// Called on writes to MBC control regions
+    rom_write_warning: Option<RomWriteWarning>,
 }
 
 impl NoMbc {
     pub fn new(rom: Vec<u8>) -> Self {
-        Self { rom }
+        Self {
+            rom,
+            rom_write_warning: None,
+        }
     }
 }
 
@@ -32,8 +42,15 @@ impl Mbc for NoMbc {
         }
     }
 
-    fn write(&mut self, _addr: u16, _value: u8) {
+    fn write(&mut self, addr: u16, value: u8) {
         // ROM only - writes are ignored
+        if let (0x2000..=0x7FFF, Some(warning)) = (addr, self.rom_write_warning.as_mut()) {
+            warning(addr, value);
+        }
+    }
+
+    fn set_rom_write_warning(&mut self, hook: RomWriteWarning) {
+        self.rom_write_warning = Some(hook);
     }
 
     fn ram_enabled(&self) -> bool {
@@ -47,6 +64,10 @@ impl Mbc for NoMbc {
     fn current_ram_bank(&self) -> usize {
         0
     }
+
+    fn rom(&self) -> &[u8] {
+        &self.rom
+    }
 }
 
 #[cfg(test)]
@@ -81,4 +102,24 @@ mod tests {
         assert!(!mbc.ram_enabled());
         assert_eq!(mbc.read(0xA000), 0xFF);
     }
+
+    #[test]
+    fn test_rom_write_warning() {
+        use std::cell::RefCell;
+        use std::rc::Rc;
+
+        let mut mbc = NoMbc::new(vec![0x00; 0x8000]);
+        let writes = Rc::new(RefCell::new(Vec::new()));
+        let sink = writes.clone();
+        mbc.set_rom_write_warning(Box::new(move |addr, value| sink.borrow_mut().push((addr, value))));
+
+        mbc.write(0x0000, 0x0A); // Not a bank/mode register
+        mbc.write(0x2000, 0x05);
+        mbc.write(0x4000, 0x01);
+        mbc.write(0x6000, 0x01);
+        mbc.write(0xA000, 0x42); // External RAM
+
+        assert_eq!(*writes.borrow(), vec![(0x2000, 0x05), (0x4000, 0x01), (0x6000, 0x01)]);
+        assert_eq!(mbc.read(0x2000), 0x00);
+    }
 }
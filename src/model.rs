@@ -0,0 +1,29 @@
+// Hardware Model
+//
+// The Game Boy Color (CGB) is a superset of the original Game Boy (DMG).
+// On top of the DMG hardware it adds:
+//   - KEY1 (0xFF4D): double-speed switch
+//   - VBK  (0xFF4F): VRAM bank select (2 x 8KB)
+//   - HDMA1-5 (0xFF51-0xFF55): VRAM DMA
+//   - BCPS/BCPD/OCPS/OCPD (0xFF68-0xFF6B): color palette RAM
+//   - SVBK (0xFF70): WRAM bank select (8 x 4KB)
+//
+// On a DMG these addresses have nothing behind them: reads return 0xFF and
+// writes are ignored. Games detect a CGB through these differences.
+
+/// Emulated hardware model
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Model {
+    /// Original Game Boy
+    #[default]
+    Dmg,
+    /// Game Boy Color
+    Cgb,
+}
+
+impl Model {
+    /// Check if this model has the CGB-only hardware
+    pub fn is_cgb(&self) -> bool {
+        *self == Model::Cgb
+    }
+}
@@ -17,10 +17,25 @@
 //   Mode 3 (Drawing): 168-291 dots - Transferring pixels to LCD
 //   Mode 0 (HBlank): 85-208 dots - Horizontal blank
 //   Mode 1 (VBlank): 4560 dots - Vertical blank (10 scanlines)
+//
+// STAT interrupt:
+// The enabled sources (mode 0/1/2 selects and LY=LYC, STAT bits 3-6) are ORed
+// into a single internal line, and the interrupt is requested only when that
+// line goes from low to high. While one source holds it high, other sources
+// becoming true don't request another interrupt ("STAT blocking").
+//
+// CGB mode changes the BG/sprite priority rules (colors are still DMG shades):
+//   - LCDC bit 0 is a master priority switch instead of a BG enable;
+//     when clear, sprites are always drawn above the background
+//   - Otherwise a sprite pixel loses to BG colors 1-3 when either its OAM
+//     priority bit or the BG tile's attribute bit 7 (VRAM bank 1 map) is set
+//   - Overlapping sprites are prioritized by OAM index only, not X
 
 pub mod registers;
+pub mod renderer;
 
 use registers::*;
+use renderer::Renderer;
 
 /// Screen dimensions
 pub const SCREEN_WIDTH: usize = 160;
@@ -32,6 +47,10 @@ pub const TOTAL_SCANLINES: u8 = 154;
 /// Dots per scanline
 pub const DOTS_PER_LINE: u32 = 456;
 
+/// Dots from the start of mode 3 until the first pixel is output
+/// (pixel x leaves the PPU at dot 80 + 12 + x)
+const PIXEL_OUTPUT_DELAY: u32 = 12;
+
 /// PPU modes
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum PpuMode {
@@ -41,8 +60,11 @@ pub enum PpuMode {
     Drawing = 3, // Mode 3
 }
 
+/// Hardware limit of sprites selected per scanline
+pub const SPRITES_PER_LINE: usize = 10;
+
 /// Sprite attributes from OAM
-#[derive(Debug, Clone, Copy, Default)]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
 pub struct Sprite {
     pub y: u8,
     pub x: u8,
@@ -70,8 +92,32 @@ impl Sprite {
     pub fn palette(&self) -> bool {
         self.flags & 0x10 != 0
     }
+
+    /// CGB only: tile VRAM bank (bit 3)
+    pub fn vram_bank(&self) -> u8 {
+        (self.flags >> 3) & 0x01
+    }
+
+    /// CGB only: object palette number OBP0-7 (bits 0-2)
+    pub fn cgb_palette(&self) -> u8 {
+        self.flags & 0x07
+    }
 }
 
+/// Layer an output pixel came from (debugging aid)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PixelSource {
+    /// Background (also when the BG is disabled and the pixel is blank)
+    Background,
+    /// Window
+    Window,
+    /// Sprite with the given OAM index (0-39)
+    Sprite(u8),
+}
+
+/// Hook called at the start of each scanline with LY and the LCD registers
+pub type ScanlineCallback = Box<dyn FnMut(u8, &LcdRegisters)>;
+
 /// The PPU state
 pub struct Ppu {
     /// LCD Control register (0xFF40)
@@ -99,6 +145,18 @@ pub struct Ppu {
 
     /// Video RAM (8KB)
     pub vram: [u8; 0x2000],
+    /// CGB only: second VRAM bank (8KB)
+    pub vram1: [u8; 0x2000],
+    /// CGB only: VBK - VRAM bank visible to the CPU (0 or 1)
+    vram_bank: u8,
+    /// CGB only: background palette RAM (8 palettes x 4 colors x 2 bytes)
+    pub bg_palette_ram: [u8; 64],
+    /// CGB only: object palette RAM
+    pub obj_palette_ram: [u8; 64],
+    /// CGB only: BCPS - background palette index (bit 7 = auto-increment)
+    bcps: u8,
+    /// CGB only: OCPS - object palette index (bit 7 = auto-increment)
+    ocps: u8,
     /// OAM - Object Attribute Memory (160 bytes for 40 sprites)
     pub oam: [u8; 160],
 
@@ -109,6 +167,23 @@ pub struct Ppu {
 
     /// Frame buffer (160x144 pixels, 2-bit color values 0-3)
     pub framebuffer: [u8; SCREEN_WIDTH * SCREEN_HEIGHT],
+    /// BG/window color indices (before palette) of the current scanline,
+    /// used for sprite-to-background priority
+    bg_line: [u8; SCREEN_WIDTH],
+    /// CGB only: BG-over-OBJ attribute bit of each pixel of the current scanline
+    bg_priority_line: [bool; SCREEN_WIDTH],
+    /// CGB priority rules (set by the bus from the hardware model)
+    cgb_mode: bool,
+
+    /// Maximum sprites selected per scanline (hardware: 10)
+    sprite_limit: usize,
+    /// Sprites selected by the last OAM scan
+    line_sprite_count: usize,
+
+    /// Block CPU VRAM access during mode 3 (accurate default)
+    vram_block: bool,
+    /// Block CPU OAM access during modes 2 and 3 (accurate default)
+    oam_block: bool,
 
     /// Internal window line counter
     window_line: u8,
@@ -119,6 +194,18 @@ pub struct Ppu {
     pub vblank_interrupt: bool,
     /// STAT interrupt request flag
     pub stat_interrupt: bool,
+    /// Internal STAT interrupt line (OR of the enabled sources)
+    stat_line: bool,
+    /// A frame completed since the last frame_ready() poll
+    frame_ready: bool,
+    /// Optional front-end receiving each scanline
+    renderer: Option<Box<dyn Renderer>>,
+    /// Optional hook called as each scanline starts (LY, register snapshot)
+    scanline_callback: Option<ScanlineCallback>,
+    /// BGP writes during mode 3 on the current line: (first pixel affected, previous value)
+    bgp_changes: Vec<(usize, u8)>,
+    /// Source layer of each framebuffer pixel (None = tracking disabled)
+    pixel_sources: Option<Box<[PixelSource]>>,
 }
 
 impl Ppu {
@@ -136,17 +223,201 @@ impl Ppu {
             wy: 0,
             wx: 0,
             vram: [0; 0x2000],
+            vram1: [0; 0x2000],
+            vram_bank: 0,
+            bg_palette_ram: [0; 64],
+            obj_palette_ram: [0; 64],
+            bcps: 0,
+            ocps: 0,
             oam: [0; 160],
             dot: 0,
             mode: PpuMode::OamScan,
             framebuffer: [0; SCREEN_WIDTH * SCREEN_HEIGHT],
+            bg_line: [0; SCREEN_WIDTH],
+            bg_priority_line: [false; SCREEN_WIDTH],
+            cgb_mode: false,
+            sprite_limit: SPRITES_PER_LINE,
+            line_sprite_count: 0,
+            vram_block: true,
+            oam_block: true,
             window_line: 0,
             window_triggered: false,
             vblank_interrupt: false,
             stat_interrupt: false,
+            stat_line: false,
+            frame_ready: false,
+            renderer: None,
+            scanline_callback: None,
+            bgp_changes: Vec::new(),
+            pixel_sources: None,
+        }
+    }
+
+    /// Use the CGB BG/sprite priority rules
+    pub fn set_cgb_mode(&mut self, enabled: bool) {
+        self.cgb_mode = enabled;
+    }
+
+    /// Current dot position within the scanline (0-455)
+    /// Mode 2 -> 3 happens at dot 80, mode 3 -> 0 at dot 252
+    pub fn dot(&self) -> u16 {
+        self.dot as u16
+    }
+
+    /// Number of sprites selected by the OAM scan of the current (last rendered) scanline
+    pub fn sprites_on_current_line(&self) -> usize {
+        self.line_sprite_count
+    }
+
+    /// Override the per-scanline sprite limit (debugging aid to remove sprite dropouts)
+    /// The hardware limit is SPRITES_PER_LINE (10)
+    pub fn set_sprite_limit(&mut self, limit: usize) {
+        self.sprite_limit = limit.min(40);
+    }
+
+    /// Enable or disable VRAM blocking during mode 3
+    pub fn set_vram_block(&mut self, enabled: bool) {
+        self.vram_block = enabled;
+    }
+
+    /// Enable or disable OAM blocking during modes 2 and 3
+    pub fn set_oam_block(&mut self, enabled: bool) {
+        self.oam_block = enabled;
+    }
+
+    /// Check if the CPU is currently locked out of VRAM
+    fn vram_blocked(&self) -> bool {
+        self.vram_block && self.mode == PpuMode::Drawing && self.lcdc.lcd_enable()
+    }
+
+    /// Check if the CPU is currently locked out of OAM
+    fn oam_blocked(&self) -> bool {
+        self.oam_block
+            && (self.mode == PpuMode::OamScan || self.mode == PpuMode::Drawing)
+            && self.lcdc.lcd_enable()
+    }
+
+    /// Stable 64-bit hash (FNV-1a) of the framebuffer
+    /// Independent of platform and Rust version, so it can be used as a golden value
+    pub fn frame_hash(&self) -> u64 {
+        let mut hash: u64 = 0xCBF2_9CE4_8422_2325;
+        for &pixel in self.framebuffer.iter() {
+            hash ^= pixel as u64;
+            hash = hash.wrapping_mul(0x0000_0100_0000_01B3);
+        }
+        hash
+    }
+
+    /// Nearest-neighbor upscale of the framebuffer by an integer factor
+    /// Returns (160 * factor) x (144 * factor) palette indices, row by row;
+    /// the native framebuffer is untouched.
+    pub fn render_scaled(&self, factor: usize) -> Vec<u8> {
+        let width = SCREEN_WIDTH * factor;
+        let mut scaled = Vec::with_capacity(width * SCREEN_HEIGHT * factor);
+        for row in self.framebuffer.chunks(SCREEN_WIDTH) {
+            let line: Vec<u8> = row.iter().flat_map(|&pixel| std::iter::repeat_n(pixel, factor)).collect();
+            for _ in 0..factor {
+                scaled.extend_from_slice(&line);
+            }
+        }
+        scaled
+    }
+
+    /// Check whether a frame completed since the last poll
+    /// Latched on entering V-Blank and cleared by this call
+    pub fn frame_ready(&mut self) -> bool {
+        let ready = self.frame_ready;
+        self.frame_ready = false;
+        ready
+    }
+
+    /// Enable or disable recording which layer each pixel came from
+    /// Off by default to avoid the per-pixel bookkeeping.
+    pub fn set_pixel_source_tracking(&mut self, enabled: bool) {
+        self.pixel_sources = if enabled {
+            Some(vec![PixelSource::Background; SCREEN_WIDTH * SCREEN_HEIGHT].into_boxed_slice())
+        } else {
+            None
+        };
+    }
+
+    /// Layer the pixel at (x, y) of the last rendered frame came from
+    /// Returns None if tracking is disabled or the position is off-screen
+    pub fn pixel_source(&self, x: usize, y: usize) -> Option<PixelSource> {
+        if x >= SCREEN_WIDTH || y >= SCREEN_HEIGHT {
+            return None;
+        }
+        self.pixel_sources.as_ref().map(|sources| sources[y * SCREEN_WIDTH + x])
+    }
+
+    /// Record the source of a framebuffer pixel (if tracking is enabled)
+    fn set_pixel_source(&mut self, index: usize, source: PixelSource) {
+        if let Some(sources) = self.pixel_sources.as_mut() {
+            sources[index] = source;
         }
     }
 
+    /// Attach a renderer that receives each scanline as it is drawn
+    /// The internal framebuffer keeps being updated as well.
+    pub fn set_renderer(&mut self, renderer: Box<dyn Renderer>) {
+        self.renderer = Some(renderer);
+    }
+
+    /// Detach the renderer, returning it
+    pub fn take_renderer(&mut self) -> Option<Box<dyn Renderer>> {
+        self.renderer.take()
+    }
+
+    /// Call `callback` at the start of every scanline (0-153) with LY and
+    /// the LCD registers at that point, e.g. to record raster effects
+    pub fn set_scanline_callback(&mut self, callback: ScanlineCallback) {
+        self.scanline_callback = Some(callback);
+    }
+
+    /// Remove the scanline callback
+    pub fn clear_scanline_callback(&mut self) {
+        self.scanline_callback = None;
+    }
+
+    /// Snapshot of the LCD registers as the CPU would read them
+    pub fn lcd_registers(&self) -> LcdRegisters {
+        LcdRegisters {
+            lcdc: self.lcdc.0,
+            stat: self.read_register(0xFF41),
+            scy: self.scy,
+            scx: self.scx,
+            ly: self.ly,
+            lyc: self.lyc,
+            bgp: self.bgp,
+            obp0: self.obp0,
+            obp1: self.obp1,
+            wy: self.wy,
+            wx: self.wx,
+        }
+    }
+
+    /// Report the start of a new scanline to the callback
+    fn start_scanline(&mut self) {
+        if self.scanline_callback.is_some() {
+            let registers = self.lcd_registers();
+            if let Some(callback) = self.scanline_callback.as_mut() {
+                callback(self.ly, &registers);
+            }
+        }
+    }
+
+    /// Testing aid: jump straight to a mode, scanline and dot
+    /// Only STAT's mode bits are updated; no interrupts fire and nothing is rendered.
+    /// Available in the crate's tests or with the `test-utils` feature.
+    #[cfg(any(test, feature = "test-utils"))]
+    pub fn force_mode(&mut self, mode: PpuMode, ly: u8, dot: u16) {
+        self.mode = mode;
+        self.stat.set_mode(mode as u8);
+        self.ly = ly;
+        self.dot = dot as u32;
+        self.bgp_changes.clear();
+    }
+
     /// Tick the PPU by the given number of CPU cycles (T-cycles)
     pub fn tick(&mut self, cycles: u32) {
         if !self.lcdc.lcd_enable() {
@@ -170,6 +441,7 @@ impl Ppu {
                     // Mode 3: Drawing takes variable time, we use 172 dots
                     if self.dot >= 80 + 172 {
                         self.render_scanline();
+                        self.push_scanline();
                         self.set_mode(PpuMode::HBlank);
                     }
                 }
@@ -183,11 +455,16 @@ impl Ppu {
                         if self.ly >= SCREEN_HEIGHT as u8 {
                             self.set_mode(PpuMode::VBlank);
                             self.vblank_interrupt = true;
+                            self.frame_ready = true;
+                            if let Some(renderer) = self.renderer.as_mut() {
+                                renderer.end_frame();
+                            }
                             self.window_triggered = false;
                             self.window_line = 0;
                         } else {
                             self.set_mode(PpuMode::OamScan);
                         }
+                        self.start_scanline();
                     }
                 }
                 PpuMode::VBlank => {
@@ -202,6 +479,7 @@ impl Ppu {
                             self.check_lyc();
                             self.set_mode(PpuMode::OamScan);
                         }
+                        self.start_scanline();
                     }
                 }
             }
@@ -212,27 +490,41 @@ impl Ppu {
     fn set_mode(&mut self, mode: PpuMode) {
         self.mode = mode;
         self.stat.set_mode(mode as u8);
+        self.update_stat_line();
+    }
 
-        // Check STAT interrupt conditions
-        let trigger = match mode {
+    /// Check LY == LYC and potentially trigger STAT interrupt
+    fn check_lyc(&mut self) {
+        let coincidence = self.ly == self.lyc;
+        self.stat.set_coincidence(coincidence);
+        self.update_stat_line();
+    }
+
+    /// Recompute the STAT interrupt line; a rising edge requests the interrupt
+    fn update_stat_line(&mut self) {
+        let mode_source = match self.mode {
             PpuMode::HBlank => self.stat.hblank_interrupt(),
             PpuMode::VBlank => self.stat.vblank_interrupt(),
             PpuMode::OamScan => self.stat.oam_interrupt(),
             PpuMode::Drawing => false,
         };
+        let line = mode_source || (self.stat.coincidence() && self.stat.lyc_interrupt());
 
-        if trigger {
+        if line && !self.stat_line {
             self.stat_interrupt = true;
         }
+        self.stat_line = line;
     }
 
-    /// Check LY == LYC and potentially trigger STAT interrupt
-    fn check_lyc(&mut self) {
-        let coincidence = self.ly == self.lyc;
-        self.stat.set_coincidence(coincidence);
-
-        if coincidence && self.stat.lyc_interrupt() {
-            self.stat_interrupt = true;
+    /// Hand the scanline just rendered to the attached renderer
+    fn push_scanline(&mut self) {
+        let ly = self.ly as usize;
+        if ly >= SCREEN_HEIGHT {
+            return;
+        }
+        if let Some(renderer) = self.renderer.as_mut() {
+            let start = ly * SCREEN_WIDTH;
+            renderer.push_scanline(self.ly, &self.framebuffer[start..start + SCREEN_WIDTH]);
         }
     }
 
@@ -247,10 +539,15 @@ impl Ppu {
         let line_start = ly * SCREEN_WIDTH;
         for x in 0..SCREEN_WIDTH {
             self.framebuffer[line_start + x] = 0;
+            self.bg_line[x] = 0;
+            self.bg_priority_line[x] = false;
+        }
+        if let Some(sources) = self.pixel_sources.as_mut() {
+            sources[line_start..line_start + SCREEN_WIDTH].fill(PixelSource::Background);
         }
 
-        // Render background
-        if self.lcdc.bg_enable() {
+        // Render background (on CGB, LCDC bit 0 is master priority instead)
+        if self.lcdc.bg_enable() || self.cgb_mode {
             self.render_background(ly);
         }
 
@@ -259,10 +556,30 @@ impl Ppu {
             self.render_window(ly);
         }
 
+        // OAM scan happens even with sprites disabled
+        let sprites_on_line = self.scan_oam(ly);
+        self.line_sprite_count = sprites_on_line.len();
+
         // Render sprites
         if self.lcdc.obj_enable() {
-            self.render_sprites(ly);
+            self.render_sprites(ly, sprites_on_line);
         }
+
+        self.bgp_changes.clear();
+    }
+
+    /// BGP value in effect when pixel x of the current line was output
+    /// Writes during mode 3 only affect the pixels after the write
+    fn bgp_at(&self, x: usize) -> u8 {
+        self.bgp_changes
+            .iter()
+            .find(|&&(first_pixel, _)| first_pixel > x)
+            .map_or(self.bgp, |&(_, previous)| previous)
+    }
+
+    /// CGB only: BG-over-OBJ bit (7) of the tile attributes for a tile map entry
+    fn bg_attribute_priority(&self, tile_map_addr: u16) -> bool {
+        self.cgb_mode && self.vram1[tile_map_addr as usize] & 0x80 != 0
     }
 
     /// Render background for one scanline
@@ -293,8 +610,10 @@ impl Ppu {
             };
 
             let color = self.get_tile_pixel(tile_addr, tile_x, tile_y);
-            let palette_color = self.apply_palette(color, self.bgp);
+            let palette_color = self.apply_palette(color, self.bgp_at(screen_x));
 
+            self.bg_line[screen_x] = color;
+            self.bg_priority_line[screen_x] = self.bg_attribute_priority(tile_map_addr);
             self.framebuffer[line_start + screen_x] = palette_color;
         }
     }
@@ -341,47 +660,64 @@ impl Ppu {
             };
 
             let color = self.get_tile_pixel(tile_addr, tile_x, tile_y);
-            let palette_color = self.apply_palette(color, self.bgp);
+            let palette_color = self.apply_palette(color, self.bgp_at(screen_x));
 
+            self.bg_line[screen_x] = color;
+            self.bg_priority_line[screen_x] = self.bg_attribute_priority(tile_map_addr);
             self.framebuffer[line_start + screen_x] = palette_color;
+            self.set_pixel_source(line_start + screen_x, PixelSource::Window);
         }
 
         self.window_line += 1;
     }
 
-    /// Render sprites for one scanline
-    fn render_sprites(&mut self, ly: usize) {
+    /// OAM scan: collect the sprites on this scanline in OAM order (max 10)
+    fn scan_oam(&self, ly: usize) -> Vec<(u8, Sprite)> {
         let sprite_height = if self.lcdc.obj_size() { 16 } else { 8 };
         let ly_i16 = ly as i16;
-
-        // Collect sprites on this scanline (max 10)
-        let mut sprites_on_line: Vec<(u8, Sprite)> = Vec::with_capacity(10);
+        let mut sprites_on_line: Vec<(u8, Sprite)> = Vec::with_capacity(self.sprite_limit);
 
         for i in 0..40 {
-            let sprite = self.get_sprite(i);
+            if sprites_on_line.len() >= self.sprite_limit {
+                break;
+            }
+            let sprite = self.oam_sprite(i);
             let sprite_y = sprite.y as i16 - 16;
 
             if ly_i16 >= sprite_y && ly_i16 < sprite_y + sprite_height as i16 {
                 sprites_on_line.push((i as u8, sprite));
-                if sprites_on_line.len() >= 10 {
-                    break;
-                }
             }
         }
 
+        sprites_on_line
+    }
+
+    /// Render sprites for one scanline
+    fn render_sprites(&mut self, ly: usize, mut sprites_on_line: Vec<(u8, Sprite)>) {
+        let sprite_height = if self.lcdc.obj_size() { 16 } else { 8 };
+        let ly_i16 = ly as i16;
+
         // Sort by X coordinate (lower X = higher priority), then by OAM index
-        sprites_on_line.sort_by(|a, b| {
-            if a.1.x == b.1.x {
-                a.0.cmp(&b.0)
-            } else {
-                a.1.x.cmp(&b.1.x)
-            }
-        });
+        // (CGB: OAM index only, which is the scan order already)
+        if !self.cgb_mode {
+            sprites_on_line.sort_by(|a, b| {
+                if a.1.x == b.1.x {
+                    a.0.cmp(&b.0)
+                } else {
+                    a.1.x.cmp(&b.1.x)
+                }
+            });
+        }
 
         let line_start = ly * SCREEN_WIDTH;
 
-        // Render sprites in reverse order (lowest priority first, so higher priority overwrites)
-        for (_, sprite) in sprites_on_line.iter().rev() {
+        // Pixels already owned by a higher priority sprite. The first opaque
+        // sprite pixel wins even if its BG priority bit then hides it behind
+        // the background - lower priority sprites never show through.
+        let mut claimed = [false; SCREEN_WIDTH];
+
+        // Render sprites in priority order
+        for &(index, ref sprite) in sprites_on_line.iter() {
             let sprite_x = sprite.x as i16 - 8;
             let sprite_y = sprite.y as i16 - 16;
 
@@ -418,10 +754,20 @@ impl Ppu {
                 }
 
                 let screen_x = screen_x as usize;
-                let bg_color = self.framebuffer[line_start + screen_x];
+                if claimed[screen_x] {
+                    continue;
+                }
+                claimed[screen_x] = true;
 
-                // Check sprite priority
-                if sprite.priority() && bg_color != 0 {
+                // Behind BG: only BG color index 0 lets the sprite through
+                // (the raw index, regardless of what BGP maps it to)
+                let behind_bg = if self.cgb_mode {
+                    self.lcdc.bg_enable()
+                        && (sprite.priority() || self.bg_priority_line[screen_x])
+                } else {
+                    sprite.priority()
+                };
+                if behind_bg && self.bg_line[screen_x] != 0 {
                     continue;
                 }
 
@@ -429,6 +775,7 @@ impl Ppu {
                 let palette_color = self.apply_palette(color, palette);
 
                 self.framebuffer[line_start + screen_x] = palette_color;
+                self.set_pixel_source(line_start + screen_x, PixelSource::Sprite(index));
             }
         }
     }
@@ -451,8 +798,8 @@ impl Ppu {
         (palette >> (color * 2)) & 0x03
     }
 
-    /// Get sprite from OAM
-    fn get_sprite(&self, index: usize) -> Sprite {
+    /// Decode sprite `index` (0-39) from OAM
+    pub fn oam_sprite(&self, index: usize) -> Sprite {
         let base = index * 4;
         Sprite {
             y: self.oam[base],
@@ -462,28 +809,51 @@ impl Ppu {
         }
     }
 
+    /// Encode a sprite into OAM entry `index` (0-39)
+    /// All flag bits are stored as-is; the CGB bank/palette bits (0-3)
+    /// are simply ignored when rendering in DMG mode.
+    pub fn set_sprite(&mut self, index: usize, sprite: &Sprite) {
+        let base = index * 4;
+        self.oam[base] = sprite.y;
+        self.oam[base + 1] = sprite.x;
+        self.oam[base + 2] = sprite.tile;
+        self.oam[base + 3] = sprite.flags;
+    }
+
+    /// VRAM bank currently selected by VBK (always 0 on DMG)
+    pub fn selected_vram_bank(&self) -> u8 {
+        self.vram_bank
+    }
+
     /// Read from VRAM
     pub fn read_vram(&self, addr: u16) -> u8 {
         // During mode 3, VRAM is not accessible
-        if self.mode == PpuMode::Drawing && self.lcdc.lcd_enable() {
+        if self.vram_blocked() {
             return 0xFF;
         }
-        self.vram[(addr & 0x1FFF) as usize]
+        if self.vram_bank == 1 {
+            self.vram1[(addr & 0x1FFF) as usize]
+        } else {
+            self.vram[(addr & 0x1FFF) as usize]
+        }
     }
 
     /// Write to VRAM
     pub fn write_vram(&mut self, addr: u16, value: u8) {
-        if self.mode == PpuMode::Drawing && self.lcdc.lcd_enable() {
+        if self.vram_blocked() {
             return;
         }
-        self.vram[(addr & 0x1FFF) as usize] = value;
+        if self.vram_bank == 1 {
+            self.vram1[(addr & 0x1FFF) as usize] = value;
+        } else {
+            self.vram[(addr & 0x1FFF) as usize] = value;
+        }
     }
 
     /// Read from OAM
     pub fn read_oam(&self, addr: u16) -> u8 {
         // During mode 2 and 3, OAM is not accessible
-        if (self.mode == PpuMode::OamScan || self.mode == PpuMode::Drawing) && self.lcdc.lcd_enable()
-        {
+        if self.oam_blocked() {
             return 0xFF;
         }
         let index = (addr & 0xFF) as usize;
@@ -496,8 +866,7 @@ impl Ppu {
 
     /// Write to OAM
     pub fn write_oam(&mut self, addr: u16, value: u8) {
-        if (self.mode == PpuMode::OamScan || self.mode == PpuMode::Drawing) && self.lcdc.lcd_enable()
-        {
+        if self.oam_blocked() {
             return;
         }
         let index = (addr & 0xFF) as usize;
@@ -506,13 +875,53 @@ impl Ppu {
         }
     }
 
+    /// Read a CGB-only PPU register (VBK, BCPS/BCPD, OCPS/OCPD)
+    /// The bus only routes these here in CGB mode
+    pub fn read_cgb_register(&self, addr: u16) -> u8 {
+        match addr {
+            0xFF4F => 0xFE | self.vram_bank,
+            0xFF68 => self.bcps | 0x40,
+            0xFF69 => self.bg_palette_ram[(self.bcps & 0x3F) as usize],
+            0xFF6A => self.ocps | 0x40,
+            0xFF6B => self.obj_palette_ram[(self.ocps & 0x3F) as usize],
+            _ => 0xFF,
+        }
+    }
+
+    /// Write a CGB-only PPU register
+    /// Palette data writes advance the index when its auto-increment bit is set
+    pub fn write_cgb_register(&mut self, addr: u16, value: u8) {
+        match addr {
+            0xFF4F => self.vram_bank = value & 0x01,
+            0xFF68 => self.bcps = value & 0xBF,
+            0xFF69 => {
+                self.bg_palette_ram[(self.bcps & 0x3F) as usize] = value;
+                if self.bcps & 0x80 != 0 {
+                    self.bcps = 0x80 | (self.bcps.wrapping_add(1) & 0x3F);
+                }
+            }
+            0xFF6A => self.ocps = value & 0xBF,
+            0xFF6B => {
+                self.obj_palette_ram[(self.ocps & 0x3F) as usize] = value;
+                if self.ocps & 0x80 != 0 {
+                    self.ocps = 0x80 | (self.ocps.wrapping_add(1) & 0x3F);
+                }
+            }
+            _ => {}
+        }
+    }
+
     /// Read PPU register
     pub fn read_register(&self, addr: u16) -> u8 {
         match addr {
             0xFF40 => self.lcdc.0,
-            0xFF41 => self.stat.0 | 0x80, // Bit 7 always 1
+            // Bit 7 always 1; with the LCD off the mode reads 0 (HBlank)
+            0xFF41 if !self.lcdc.lcd_enable() => (self.stat.0 & !0x03) | 0x80,
+            0xFF41 => self.stat.0 | 0x80,
             0xFF42 => self.scy,
             0xFF43 => self.scx,
+            // LY stays at 0 while the LCD is off
+            0xFF44 if !self.lcdc.lcd_enable() => 0,
             0xFF44 => self.ly,
             0xFF45 => self.lyc,
             0xFF47 => self.bgp,
@@ -536,13 +945,18 @@ impl Ppu {
                     self.dot = 0;
                     self.mode = PpuMode::HBlank;
                     self.stat.set_mode(0);
+                    self.stat_line = false;
                     self.window_line = 0;
                     self.window_triggered = false;
+                    self.bgp_changes.clear();
                 }
             }
             0xFF41 => {
-                // Lower 3 bits are read-only
-                self.stat.0 = (self.stat.0 & 0x07) | (value & 0xF8);
+                // Lower 3 bits are read-only, bit 7 is unused
+                self.stat.0 = (self.stat.0 & 0x07) | (value & 0x78);
+                if self.lcdc.lcd_enable() {
+                    self.update_stat_line();
+                }
             }
             0xFF42 => self.scy = value,
             0xFF43 => self.scx = value,
@@ -551,7 +965,15 @@ impl Ppu {
                 self.lyc = value;
                 self.check_lyc();
             }
-            0xFF47 => self.bgp = value,
+            0xFF47 => {
+                // Mid-scanline palette changes (raster effects) only apply
+                // to the pixels not yet output
+                if self.mode == PpuMode::Drawing && self.lcdc.lcd_enable() {
+                    let first_pixel = self.dot.saturating_sub(80 + PIXEL_OUTPUT_DELAY) as usize;
+                    self.bgp_changes.push((first_pixel.min(SCREEN_WIDTH), self.bgp));
+                }
+                self.bgp = value;
+            }
             0xFF48 => self.obp0 = value,
             0xFF49 => self.obp1 = value,
             0xFF4A => self.wy = value,
@@ -561,6 +983,122 @@ impl Ppu {
     }
 }
 
+// Scene fixtures
+//
+// A small text format for describing PPU test scenes declaratively:
+//
+//   # comment
+//   tile 1            - tile 1 (at 0x8000 + 1*16) followed by 8 rows of 8 color digits (0-3)
+//   01230123
+//   ...
+//   map               - BG tile map (0x9800) rows of tile numbers, one line per map row
+//   1 0 1
+//   0 1 0
+//   sprite 0 8 16 1 00 - OAM entry: index, screen X, screen Y, tile, flags (hex, optional)
+//
+// Only memory is programmed; registers (LCDC, palettes, scroll) are left as-is.
+impl Ppu {
+    /// Program VRAM, the BG tile map, and OAM from a scene fixture
+    pub fn load_scene_fixture(&mut self, fixture: &str) -> Result<(), String> {
+        let mut lines = fixture
+            .lines()
+            .map(|line| line.trim())
+            .filter(|line| !line.is_empty() && !line.starts_with('#'))
+            .peekable();
+
+        while let Some(line) = lines.next() {
+            let mut parts = line.split_whitespace();
+            match parts.next() {
+                Some("tile") => {
+                    let index: usize = parse_number(parts.next(), "tile index")?;
+                    if index > 0xFF {
+                        return Err(format!("Tile index out of range: {}", index));
+                    }
+                    for row in 0..8 {
+                        let pixels = lines
+                            .next()
+                            .ok_or_else(|| format!("Tile {}: missing row {}", index, row))?;
+                        self.load_tile_row(index, row, pixels)?;
+                    }
+                }
+                Some("map") => {
+                    let mut map_row = 0;
+                    while let Some(row) = lines.peek() {
+                        if !row.starts_with(|c: char| c.is_ascii_digit()) {
+                            break;
+                        }
+                        for (col, tile) in row.split_whitespace().enumerate() {
+                            let tile: u8 = parse_number(Some(tile), "map entry")?;
+                            if map_row >= 32 || col >= 32 {
+                                return Err("Tile map larger than 32x32".to_string());
+                            }
+                            self.vram[0x1800 + map_row * 32 + col] = tile;
+                        }
+                        map_row += 1;
+                        lines.next();
+                    }
+                }
+                Some("sprite") => {
+                    let index: usize = parse_number(parts.next(), "sprite index")?;
+                    let x: u8 = parse_number(parts.next(), "sprite X")?;
+                    let y: u8 = parse_number(parts.next(), "sprite Y")?;
+                    let tile: u8 = parse_number(parts.next(), "sprite tile")?;
+                    let flags = match parts.next() {
+                        Some(flags) => u8::from_str_radix(flags, 16)
+                            .map_err(|_| format!("Invalid sprite flags: {}", flags))?,
+                        None => 0,
+                    };
+                    if index >= 40 {
+                        return Err(format!("Sprite index out of range: {}", index));
+                    }
+                    let sprite = Sprite {
+                        y: y.wrapping_add(16),
+                        x: x.wrapping_add(8),
+                        tile,
+                        flags,
+                    };
+                    self.set_sprite(index, &sprite);
+                }
+                _ => return Err(format!("Unknown fixture line: {}", line)),
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Encode one row of 8 color digits into 2bpp tile data
+    fn load_tile_row(&mut self, index: usize, row: usize, pixels: &str) -> Result<(), String> {
+        let colors: Vec<u8> = pixels
+            .chars()
+            .map(|c| match c {
+                '0'..='3' => Ok(c as u8 - b'0'),
+                _ => Err(format!("Tile {}: invalid color '{}'", index, c)),
+            })
+            .collect::<Result<_, _>>()?;
+        if colors.len() != 8 {
+            return Err(format!("Tile {}: row {} must have 8 pixels", index, row));
+        }
+
+        let mut low = 0u8;
+        let mut high = 0u8;
+        for (x, color) in colors.iter().enumerate() {
+            low |= (color & 1) << (7 - x);
+            high |= (color >> 1) << (7 - x);
+        }
+
+        let addr = index * 16 + row * 2;
+        self.vram[addr] = low;
+        self.vram[addr + 1] = high;
+        Ok(())
+    }
+}
+
+/// Parse a decimal fixture field
+fn parse_number<T: std::str::FromStr>(field: Option<&str>, name: &str) -> Result<T, String> {
+    let field = field.ok_or_else(|| format!("Missing {}", name))?;
+    field.parse().map_err(|_| format!("Invalid {}: {}", name, field))
+}
+
 impl Default for Ppu {
     fn default() -> Self {
         Self::new()
@@ -588,6 +1126,34 @@ mod tests {
         assert_eq!(ppu.mode, PpuMode::HBlank);
     }
 
+    #[test]
+    fn test_dot_counter() {
+        let mut ppu = Ppu::new();
+        ppu.lcdc.0 = 0x91;
+
+        ppu.tick(79);
+        assert_eq!(ppu.dot(), 79);
+        assert_eq!(ppu.mode, PpuMode::OamScan);
+
+        ppu.tick(1);
+        assert_eq!(ppu.dot(), 80);
+        assert_eq!(ppu.mode, PpuMode::Drawing);
+
+        ppu.tick(172);
+        assert_eq!(ppu.dot(), 252);
+        assert_eq!(ppu.mode, PpuMode::HBlank);
+
+        // Dot resets at the scanline boundary
+        ppu.tick(204);
+        assert_eq!(ppu.dot(), 0);
+        assert_eq!(ppu.ly, 1);
+        assert_eq!(ppu.mode, PpuMode::OamScan);
+
+        ppu.tick(100);
+        assert_eq!(ppu.dot(), 100);
+        assert_eq!(ppu.mode, PpuMode::Drawing);
+    }
+
     #[test]
     fn test_ly_increment() {
         let mut ppu = Ppu::new();
@@ -640,6 +1206,613 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_load_scene_fixture() {
+        let mut ppu = Ppu::new();
+        ppu.bgp = 0xE4; // Identity palette
+
+        ppu.load_scene_fixture(
+            "
+            # Tile 1: diagonal stripes of all four colors
+            tile 1
+            01230123
+            12301230
+            23012301
+            30123012
+            01230123
+            12301230
+            23012301
+            30123012
+
+            map
+            1 0
+            ",
+        )
+        .unwrap();
+
+        // Render one full frame
+        ppu.tick(70224);
+
+        let expected = [0u8, 1, 2, 3];
+        for y in 0..8 {
+            for x in 0..8 {
+                let pixel = ppu.framebuffer[y * SCREEN_WIDTH + x];
+                assert_eq!(pixel, expected[(x + y) % 4], "pixel ({}, {})", x, y);
+            }
+            // Tile 0 (blank) next to it
+            assert_eq!(ppu.framebuffer[y * SCREEN_WIDTH + 8], 0);
+        }
+    }
+
+    #[test]
+    fn test_sprite_bg_priority() {
+        let mut ppu = Ppu::new();
+        ppu.lcdc = LcdControl(0x93); // LCD, BG and sprites on
+        ppu.bgp = 0xE4; // Identity palette
+        ppu.obp0 = 0xE4;
+
+        ppu.load_scene_fixture(
+            "
+            # Tile 1: BG color 1 on the left half, color 0 on the right
+            tile 1
+            11110000
+            11110000
+            11110000
+            11110000
+            11110000
+            11110000
+            11110000
+            11110000
+
+            # Tile 2: solid sprite color 3 with a transparent first column
+            tile 2
+            03333333
+            03333333
+            03333333
+            03333333
+            03333333
+            03333333
+            03333333
+            03333333
+
+            map
+            1 1
+
+            # Priority-set sprite over tile (0,0), plain sprite over tile (1,0)
+            sprite 0 0 0 2 80
+            sprite 1 8 0 2 00
+            ",
+        )
+        .unwrap();
+
+        ppu.tick(70224);
+
+        let row: Vec<u8> = ppu.framebuffer[0..16].to_vec();
+        assert_eq!(
+            row,
+            vec![
+                // Behind BG: BG colors 1-3 win, sprite only over color 0
+                1, 1, 1, 1, 3, 3, 3, 3,
+                // Above BG: sprite everywhere except its transparent column
+                1, 3, 3, 3, 3, 3, 3, 3,
+            ]
+        );
+    }
+
+    #[test]
+    fn test_sprite_placement_flip_and_palette() {
+        let mut ppu = Ppu::new();
+        ppu.lcdc = LcdControl(0x93);
+        ppu.bgp = 0xE4;
+        ppu.obp0 = 0xE4;
+        ppu.obp1 = 0x64; // 2 -> 2, 3 -> 1
+
+        ppu.load_scene_fixture(
+            "
+            # Color 3 in the top-left corner, color 2 in the bottom-right
+            tile 1
+            30000000
+            00000000
+            00000000
+            00000000
+            00000000
+            00000000
+            00000000
+            00000002
+
+            # Plain at (20, 30); X+Y flipped with OBP1 at (60, 40)
+            sprite 0 20 30 1 00
+            sprite 1 60 40 1 70
+            ",
+        )
+        .unwrap();
+
+        ppu.tick(70224);
+
+        let pixel = |x: usize, y: usize| ppu.framebuffer[y * SCREEN_WIDTH + x];
+        assert_eq!(pixel(20, 30), 3);
+        assert_eq!(pixel(27, 37), 2);
+        assert_eq!(pixel(60, 40), 2);
+        assert_eq!(pixel(67, 47), 1);
+        assert_eq!(ppu.framebuffer.iter().filter(|&&c| c != 0).count(), 4);
+    }
+
+    #[test]
+    fn test_window_layer() {
+        let mut ppu = Ppu::new();
+        ppu.lcdc = LcdControl(0xF1); // Window on, window map at 0x9C00
+        ppu.bgp = 0xE4;
+        ppu.wx = 7;
+        ppu.wy = 0;
+
+        ppu.load_scene_fixture(
+            "
+            tile 1
+            33333333
+            33333333
+            33333333
+            33333333
+            33333333
+            33333333
+            33333333
+            33333333
+            tile 2
+            11111111
+            11111111
+            11111111
+            11111111
+            11111111
+            11111111
+            11111111
+            11111111
+
+            # BG map is tile 2 everywhere in the first column; must be covered
+            map
+            2
+            2
+            2
+            ",
+        )
+        .unwrap();
+        ppu.vram[0x1C00] = 1; // Window row 0
+        ppu.vram[0x1C20] = 2; // Window row 1
+        ppu.vram[0x1C40] = 1; // Window row 2
+
+        // Lines 0-7 draw window row 0
+        ppu.tick(456 * 8);
+        // Hide the window for lines 8-15; its line counter must not advance
+        ppu.wx = 200;
+        ppu.tick(456 * 8);
+        ppu.wx = 7;
+        ppu.tick(70224 - 456 * 16);
+
+        let pixel = |x: usize, y: usize| ppu.framebuffer[y * SCREEN_WIDTH + x];
+        assert_eq!(pixel(0, 0), 3);
+        assert_eq!(pixel(8, 0), 0);
+        assert_eq!(pixel(0, 8), 1); // Background shows through
+        assert_eq!(pixel(0, 16), 1); // Window row 1, not row 2
+        assert_eq!(pixel(0, 24), 3); // Window row 2
+    }
+
+    #[test]
+    fn test_cgb_bg_attribute_priority() {
+        let mut ppu = Ppu::new();
+        ppu.set_cgb_mode(true);
+        ppu.bgp = 0xE4;
+        ppu.obp0 = 0xE4;
+
+        ppu.load_scene_fixture(
+            "
+            # Tile 1: solid BG color 1
+            tile 1
+            11111111
+            11111111
+            11111111
+            11111111
+            11111111
+            11111111
+            11111111
+            11111111
+
+            # Tile 2: solid sprite color 3
+            tile 2
+            33333333
+            33333333
+            33333333
+            33333333
+            33333333
+            33333333
+            33333333
+            33333333
+
+            map
+            1 1 1
+
+            # Non-priority sprites over both tiles
+            sprite 0 0 0 2 00
+            sprite 1 8 0 2 00
+            ",
+        )
+        .unwrap();
+        // BG-over-OBJ attribute on tile (0,0) only
+        ppu.vram1[0x1800] = 0x80;
+
+        // Master priority on: the attributed tile wins, the plain one doesn't
+        ppu.lcdc = LcdControl(0x93);
+        ppu.tick(70224);
+        assert_eq!(ppu.framebuffer[0], 1);
+        assert_eq!(ppu.framebuffer[8], 3);
+
+        // Master priority off: sprites are always on top
+        ppu.lcdc = LcdControl(0x92);
+        ppu.tick(70224);
+        assert_eq!(ppu.framebuffer[0], 3);
+        assert_eq!(ppu.framebuffer[8], 3);
+
+        // The BG is still drawn where no sprite covers it
+        assert_eq!(ppu.framebuffer[16], 1);
+    }
+
+    #[test]
+    fn test_tall_sprite_tile_masking() {
+        let mut ppu = Ppu::new();
+        ppu.lcdc = LcdControl(0x97); // LCD, BG, sprites on, 8x16 sprites
+        ppu.bgp = 0xE4;
+        ppu.obp0 = 0xE4;
+
+        ppu.load_scene_fixture(
+            "
+            # Tile 2: solid color 1 (top half of the 8x16 pair)
+            tile 2
+            11111111
+            11111111
+            11111111
+            11111111
+            11111111
+            11111111
+            11111111
+            11111111
+
+            # Tile 3: solid color 2 (bottom half)
+            tile 3
+            22222222
+            22222222
+            22222222
+            22222222
+            22222222
+            22222222
+            22222222
+            22222222
+
+            # Odd tile index: bit 0 is ignored in 8x16 mode
+            sprite 0 0 0 3 00
+            # Same tile pair, Y-flipped: halves swap
+            sprite 1 8 0 3 40
+            ",
+        )
+        .unwrap();
+
+        ppu.tick(70224);
+
+        let top = &ppu.framebuffer[0..16];
+        let bottom = &ppu.framebuffer[8 * SCREEN_WIDTH..8 * SCREEN_WIDTH + 16];
+        assert_eq!(top, &[1, 1, 1, 1, 1, 1, 1, 1, 2, 2, 2, 2, 2, 2, 2, 2]);
+        assert_eq!(bottom, &[2, 2, 2, 2, 2, 2, 2, 2, 1, 1, 1, 1, 1, 1, 1, 1]);
+    }
+
+    #[test]
+    fn test_pixel_source() {
+        let mut ppu = Ppu::new();
+        ppu.lcdc = LcdControl(0x93);
+        ppu.load_scene_fixture(
+            "
+            # Tile 1: opaque left half, transparent right half
+            tile 1
+            33330000
+            33330000
+            33330000
+            33330000
+            33330000
+            33330000
+            33330000
+            33330000
+
+            sprite 5 16 8 1 00
+            ",
+        )
+        .unwrap();
+
+        // Disabled by default
+        ppu.tick(70224);
+        assert_eq!(ppu.pixel_source(16, 8), None);
+
+        ppu.set_pixel_source_tracking(true);
+        ppu.tick(70224);
+        assert_eq!(ppu.pixel_source(16, 8), Some(PixelSource::Sprite(5)));
+        assert_eq!(ppu.pixel_source(19, 15), Some(PixelSource::Sprite(5)));
+        assert_eq!(ppu.pixel_source(20, 8), Some(PixelSource::Background)); // Transparent
+        assert_eq!(ppu.pixel_source(16, 16), Some(PixelSource::Background));
+        assert_eq!(ppu.pixel_source(0, 0), Some(PixelSource::Background));
+        assert_eq!(ppu.pixel_source(160, 0), None);
+    }
+
+    #[test]
+    fn test_sprite_priority_uses_bg_color_index() {
+        let mut ppu = Ppu::new();
+        ppu.lcdc = LcdControl(0x93);
+        ppu.bgp = 0x03; // Color 0 -> shade 3, colors 1-3 -> shade 0
+        ppu.obp0 = 0xE4;
+
+        ppu.load_scene_fixture(
+            "
+            tile 1
+            22222222
+            22222222
+            22222222
+            22222222
+            22222222
+            22222222
+            22222222
+            22222222
+
+            # Behind-BG sprite next to a regular one
+            sprite 0 0 0 1 80
+            sprite 1 4 0 1 00
+            ",
+        )
+        .unwrap();
+
+        ppu.tick(70224);
+
+        // BG is color index 0 (shown as shade 3), so the behind-BG sprite is visible
+        assert_eq!(ppu.framebuffer[0..12].to_vec(), vec![2; 12]);
+    }
+
+    #[test]
+    fn test_sprite_hidden_by_bg_masks_lower_sprites() {
+        let mut ppu = Ppu::new();
+        ppu.lcdc = LcdControl(0x93);
+        ppu.bgp = 0xE4;
+        ppu.obp0 = 0xE4;
+
+        ppu.load_scene_fixture(
+            "
+            tile 1
+            11111111
+            11111111
+            11111111
+            11111111
+            11111111
+            11111111
+            11111111
+            11111111
+
+            tile 2
+            33333333
+            33333333
+            33333333
+            33333333
+            33333333
+            33333333
+            33333333
+            33333333
+
+            map
+            1
+
+            # Behind-BG sprite wins the pixel over the above-BG one at the same X
+            sprite 0 0 0 2 80
+            sprite 1 0 0 2 00
+            ",
+        )
+        .unwrap();
+
+        ppu.tick(70224);
+
+        assert_eq!(ppu.framebuffer[0..8].to_vec(), vec![1; 8]);
+    }
+
+    #[test]
+    fn test_set_sprite_round_trip() {
+        let mut ppu = Ppu::new();
+        let sprite = Sprite {
+            y: 0x20,
+            x: 0x18,
+            tile: 0x42,
+            flags: 0xAB, // Priority, X flip, VRAM bank 1, CGB palette 3
+        };
+
+        ppu.set_sprite(39, &sprite);
+
+        assert_eq!(&ppu.oam[156..160], &[0x20, 0x18, 0x42, 0xAB]);
+        let decoded = ppu.oam_sprite(39);
+        assert_eq!(decoded, sprite);
+        assert!(decoded.priority());
+        assert!(!decoded.y_flip());
+        assert!(decoded.x_flip());
+        assert!(!decoded.palette());
+        assert_eq!(decoded.vram_bank(), 1);
+        assert_eq!(decoded.cgb_palette(), 3);
+    }
+
+    #[test]
+    fn test_render_scaled() {
+        let mut ppu = Ppu::new();
+        for (i, pixel) in ppu.framebuffer.iter_mut().enumerate() {
+            *pixel = (i % 7 % 4) as u8;
+        }
+        let native = ppu.framebuffer;
+
+        let scaled = ppu.render_scaled(2);
+        assert_eq!(scaled.len(), 320 * 288);
+        for y in 0..SCREEN_HEIGHT {
+            for x in 0..SCREEN_WIDTH {
+                let pixel = native[y * SCREEN_WIDTH + x];
+                for (dx, dy) in [(0, 0), (1, 0), (0, 1), (1, 1)] {
+                    assert_eq!(scaled[(2 * y + dy) * 320 + 2 * x + dx], pixel, "({}, {})", x, y);
+                }
+            }
+        }
+        assert_eq!(ppu.framebuffer, native);
+        assert_eq!(ppu.render_scaled(1), native.to_vec());
+    }
+
+    #[test]
+    fn test_frame_hash() {
+        let render = || {
+            let mut ppu = Ppu::new();
+            ppu.bgp = 0xE4;
+            ppu.load_scene_fixture(
+                "
+                tile 1
+                01230123
+                12301230
+                23012301
+                30123012
+                01230123
+                12301230
+                23012301
+                30123012
+
+                map
+                1 0 1
+                0 1 0
+                ",
+            )
+            .unwrap();
+            ppu.tick(70224);
+            ppu
+        };
+
+        let first = render();
+        let second = render();
+        assert_eq!(first.frame_hash(), second.frame_hash());
+
+        // A blank screen hashes differently
+        assert_ne!(first.frame_hash(), Ppu::new().frame_hash());
+    }
+
+    #[test]
+    fn test_sprites_on_current_line() {
+        let mut ppu = Ppu::new();
+        ppu.lcdc = LcdControl(0x93);
+
+        // 12 sprites covering scanline 0, one elsewhere
+        for i in 0..12 {
+            let sprite = Sprite { y: 16, x: 8 + i as u8 * 8, tile: 0, flags: 0 };
+            ppu.set_sprite(i, &sprite);
+        }
+        ppu.set_sprite(12, &Sprite { y: 100, x: 8, tile: 0, flags: 0 });
+
+        // Render scanline 0 (OAM scan + drawing)
+        ppu.tick(80 + 172);
+        assert_eq!(ppu.ly, 0);
+        assert_eq!(ppu.sprites_on_current_line(), SPRITES_PER_LINE);
+
+        // Raising the limit selects all of them on the next frame
+        ppu.set_sprite_limit(40);
+        ppu.tick(70224);
+        assert_eq!(ppu.ly, 0);
+        assert_eq!(ppu.sprites_on_current_line(), 12);
+    }
+
+    #[test]
+    fn test_renderer_receives_scanlines() {
+        use std::cell::RefCell;
+        use std::rc::Rc;
+
+        /// Records (ly, first pixel) per line and counts frames
+        struct MockRenderer {
+            lines: Rc<RefCell<Vec<(u8, u8)>>>,
+            frames: Rc<RefCell<usize>>,
+        }
+
+        impl Renderer for MockRenderer {
+            fn push_scanline(&mut self, ly: u8, pixels: &[u8]) {
+                assert_eq!(pixels.len(), SCREEN_WIDTH);
+                self.lines.borrow_mut().push((ly, pixels[0]));
+            }
+
+            fn end_frame(&mut self) {
+                *self.frames.borrow_mut() += 1;
+            }
+        }
+
+        let lines = Rc::new(RefCell::new(Vec::new()));
+        let frames = Rc::new(RefCell::new(0));
+        let mut ppu = Ppu::new();
+        ppu.bgp = 0xE4;
+        ppu.load_scene_fixture("tile 0\n30000000\n30000000\n30000000\n30000000\n30000000\n30000000\n30000000\n30000000").unwrap();
+        ppu.set_renderer(Box::new(MockRenderer {
+            lines: Rc::clone(&lines),
+            frames: Rc::clone(&frames),
+        }));
+
+        ppu.tick(70224);
+        assert_eq!(*frames.borrow(), 1);
+        let received = lines.borrow().clone();
+        assert_eq!(received.len(), SCREEN_HEIGHT);
+        for (i, &(ly, pixel)) in received.iter().enumerate() {
+            assert_eq!(ly as usize, i);
+            assert_eq!(pixel, ppu.framebuffer[i * SCREEN_WIDTH]);
+            assert_eq!(pixel, 3);
+        }
+
+        ppu.tick(70224);
+        assert_eq!(*frames.borrow(), 2);
+        assert_eq!(lines.borrow().len(), 2 * SCREEN_HEIGHT);
+    }
+
+    #[test]
+    fn test_mid_scanline_bgp_write() {
+        let mut ppu = Ppu::new();
+        ppu.bgp = 0xE4; // Color 1 -> shade 1
+        ppu.load_scene_fixture("tile 0\n11111111\n11111111\n11111111\n11111111\n11111111\n11111111\n11111111\n11111111").unwrap();
+
+        // Write BGP when pixel 80 is about to be output
+        ppu.force_mode(PpuMode::Drawing, 5, 80);
+        ppu.tick(PIXEL_OUTPUT_DELAY + 80);
+        ppu.write_register(0xFF47, 0xEC); // Color 1 -> shade 3
+        ppu.tick(2 * DOTS_PER_LINE);
+
+        let line = &ppu.framebuffer[5 * SCREEN_WIDTH..6 * SCREEN_WIDTH];
+        assert!(line[..80].iter().all(|&shade| shade == 1));
+        assert!(line[80..].iter().all(|&shade| shade == 3));
+
+        // The next line uses the new palette throughout
+        let next = &ppu.framebuffer[6 * SCREEN_WIDTH..7 * SCREEN_WIDTH];
+        assert!(next.iter().all(|&shade| shade == 3));
+    }
+
+    #[test]
+    fn test_force_mode_blocks_vram() {
+        let mut ppu = Ppu::new();
+        ppu.vram[0x10] = 0x42;
+
+        ppu.force_mode(PpuMode::Drawing, 42, 100);
+        assert_eq!(ppu.ly, 42);
+        assert_eq!(ppu.dot(), 100);
+        assert_eq!(ppu.stat.mode(), 3);
+        assert_eq!(ppu.read_vram(0x10), 0xFF);
+        assert_eq!(ppu.read_oam(0x00), 0xFF);
+
+        ppu.force_mode(PpuMode::HBlank, 42, 300);
+        assert_eq!(ppu.read_vram(0x10), 0x42);
+
+        // Ticking continues from the forced position
+        ppu.tick(456 - 300);
+        assert_eq!(ppu.ly, 43);
+        assert_eq!(ppu.dot(), 0);
+    }
+
+    #[test]
+    fn test_scene_fixture_errors() {
+        let mut ppu = Ppu::new();
+        assert!(ppu.load_scene_fixture("tile 1\n0123").is_err());
+        assert!(ppu.load_scene_fixture("tile 1\n01234567").is_err());
+        assert!(ppu.load_scene_fixture("bogus").is_err());
+    }
+
     #[test]
     fn test_palette() {
         let ppu = Ppu::new();
@@ -660,4 +1833,88 @@ mod tests {
         assert_eq!(ppu.apply_palette(2, 0xE4), 2);
         assert_eq!(ppu.apply_palette(3, 0xE4), 3);
     }
+
+    #[test]
+    fn test_scanline_callback() {
+        use std::cell::RefCell;
+        use std::rc::Rc;
+
+        let mut ppu = Ppu::new();
+        let seen = Rc::new(RefCell::new(Vec::new()));
+        let log = Rc::clone(&seen);
+        ppu.set_scanline_callback(Box::new(move |ly, registers: &LcdRegisters| {
+            log.borrow_mut().push((ly, registers.scx, registers.ly));
+        }));
+
+        // Raster effect: SCX = 3 * line, set during the previous line
+        for line in 1..=10u8 {
+            ppu.scx = line * 3;
+            ppu.tick(DOTS_PER_LINE);
+        }
+
+        let seen = seen.borrow();
+        assert_eq!(seen.len(), 10);
+        for (i, &(ly, scx, snapshot_ly)) in seen.iter().enumerate() {
+            let line = i as u8 + 1;
+            assert_eq!(ly, line);
+            assert_eq!(snapshot_ly, line);
+            assert_eq!(scx, line * 3);
+        }
+    }
+
+    #[test]
+    fn test_ly_and_mode_read_zero_with_lcd_off() {
+        let mut ppu = Ppu::new();
+        ppu.tick(5 * DOTS_PER_LINE + 100);
+        assert_eq!(ppu.read_register(0xFF44), 5);
+        assert_eq!(ppu.read_register(0xFF41) & 0x03, PpuMode::Drawing as u8);
+
+        ppu.write_register(0xFF40, 0x11);
+        assert_eq!(ppu.read_register(0xFF44), 0);
+        assert_eq!(ppu.read_register(0xFF41) & 0x03, 0);
+
+        // Still 0 even if the internal state says otherwise
+        ppu.force_mode(PpuMode::OamScan, 42, 0);
+        assert_eq!(ppu.read_register(0xFF44), 0);
+        assert_eq!(ppu.read_register(0xFF41) & 0x03, 0);
+
+        // Time doesn't move LY while off
+        ppu.force_mode(PpuMode::HBlank, 0, 0);
+        ppu.tick(10 * DOTS_PER_LINE);
+        assert_eq!(ppu.read_register(0xFF44), 0);
+    }
+
+    #[test]
+    fn test_stat_blocking() {
+        let count_stat_interrupts = |ppu: &mut Ppu, dots: u32| {
+            (0..dots)
+                .filter(|_| {
+                    ppu.tick(1);
+                    ppu.stat_interrupt
+                })
+                .count()
+        };
+
+        // HBlank and LY=LYC on the next line: the line stays high from
+        // HBlank through LY's change, so only the HBlank edge counts
+        let mut ppu = Ppu::new();
+        ppu.lcdc.0 = 0x91;
+        ppu.lyc = 1;
+        ppu.stat.0 = 0x48; // LYC and HBlank selects
+        assert_eq!(count_stat_interrupts(&mut ppu, DOTS_PER_LINE), 1);
+
+        // Line 1: coincidence holds all line, so HBlank doesn't fire either
+        assert_eq!(count_stat_interrupts(&mut ppu, DOTS_PER_LINE), 0);
+
+        // Line 2: coincidence drops at the line start, HBlank fires again
+        assert_eq!(count_stat_interrupts(&mut ppu, DOTS_PER_LINE), 1);
+
+        // OAM and HBlank selects: HBlank -> OAM scan keeps the line high,
+        // so only the HBlank edges request interrupts
+        let mut ppu = Ppu::new();
+        ppu.lcdc.0 = 0x91;
+        ppu.stat.0 = 0x28;
+        ppu.tick(1);
+        assert_eq!(count_stat_interrupts(&mut ppu, 4 * DOTS_PER_LINE), 4);
+    }
 }
@@ -2,6 +2,8 @@
 //
 // LCDC (0xFF40) - LCD Control
 // STAT (0xFF41) - LCD Status
+//
+// LcdRegisters is a plain snapshot of all LCD registers (0xFF40-0xFF4B)
 
 /// LCD Control Register (0xFF40)
 /// Bit 7: LCD Enable (0=Off, 1=On)
@@ -113,6 +115,22 @@ impl LcdStatus {
     }
 }
 
+/// Snapshot of the LCD registers (0xFF40-0xFF4B, without DMA)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LcdRegisters {
+    pub lcdc: u8,
+    pub stat: u8,
+    pub scy: u8,
+    pub scx: u8,
+    pub ly: u8,
+    pub lyc: u8,
+    pub bgp: u8,
+    pub obp0: u8,
+    pub obp1: u8,
+    pub wy: u8,
+    pub wx: u8,
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -0,0 +1,18 @@
+// Renderer
+//
+// The PPU produces the picture one scanline at a time (at the end of mode 3).
+// A Renderer receives each finished line as it is produced, so a front-end
+// can stream the picture or apply per-scanline effects instead of waiting
+// for the complete framebuffer.
+//
+// The PPU always keeps its own framebuffer up to date; an attached renderer
+// gets the same lines in addition.
+
+/// Receiver for scanlines produced by the PPU
+pub trait Renderer {
+    /// A visible scanline (0-143) finished; `pixels` holds 160 shades (0-3)
+    fn push_scanline(&mut self, ly: u8, pixels: &[u8]);
+
+    /// All visible scanlines of a frame were pushed (the PPU entered V-Blank)
+    fn end_frame(&mut self) {}
+}
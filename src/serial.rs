@@ -0,0 +1,369 @@
+// Serial Data Transfer (Link Cable)
+//
+// Registers:
+//   SB (0xFF01): Serial transfer data - byte to send / byte received
+//   SC (0xFF02): Serial transfer control
+//                Bit 7: Transfer start (1 = requested or in progress)
+//                Bit 1: Clock speed (CGB only, 0 = normal, 1 = fast)
+//                Bit 0: Shift clock (0 = external, 1 = internal)
+//
+// A transfer shifts SB out MSB-first while shifting the partner's bits in.
+// With the internal clock, one bit moves every 512 T-cycles (8192 Hz),
+// so a full byte takes 4096 cycles. When all 8 bits are done, SC bit 7 is
+// cleared and the Serial interrupt is requested.
+//
+// With no link partner connected, the incoming bits all read as 1,
+// so SB holds 0xFF after a transfer. In loopback mode the output is wired
+// back to the input, so SB holds the byte that was sent.
+//
+// With the external clock (SC bit 0 = 0) the link partner drives the shifts:
+// each clock pulse moves one bit, so nothing happens until pulses arrive.
+//
+// Test ROMs (e.g. Blargg) print their results through serial, so every
+// transfer started with the internal clock also appends the outgoing byte to
+// an output buffer. Externally clocked transfers are not captured.
+// The buffer is unbounded by default; with a cap set, the oldest bytes are
+// dropped so long headless runs can't grow it without limit.
+
+/// T-cycles per bit with the internal clock (8192 Hz)
+pub const CYCLES_PER_BIT: u32 = 512;
+
+/// How transfers are timed
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SerialMode {
+    /// Transfers complete as soon as they start (fast, good for test ROMs)
+    Instant,
+    /// Transfers take 8 bit periods like real hardware
+    Timed,
+}
+
+/// Decoded SC (0xFF02) configuration
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SerialControl {
+    /// Bit 7: a transfer is requested or in progress
+    pub transfer_start: bool,
+    /// Bit 0: this side drives the clock (link master)
+    pub internal_clock: bool,
+    /// Bit 1: CGB fast clock, as written (see `Bus::serial_control` for the DMG view)
+    pub fast_clock: bool,
+}
+
+/// Serial port state
+pub struct Serial {
+    /// SB - Serial transfer data (0xFF01)
+    sb: u8,
+    /// SC - Serial transfer control (0xFF02)
+    sc: u8,
+    /// Transfer timing
+    mode: SerialMode,
+    /// Feed outgoing bits back in (SB reads back the sent byte)
+    loopback: bool,
+    /// Bits left to shift in the current transfer (timed mode)
+    bits_remaining: u8,
+    /// Cycles accumulated toward the next bit shift (timed mode)
+    bit_cycles: u32,
+    /// Bytes sent by the program (for test ROMs)
+    pub output: Vec<u8>,
+    /// Maximum bytes kept in `output` (None = unbounded)
+    output_cap: Option<usize>,
+    /// Bytes ever appended to `output`, including ones dropped by the cap
+    output_total: usize,
+    /// Interrupt request flag
+    pub interrupt_requested: bool,
+}
+
+impl Serial {
+    pub fn new() -> Self {
+        Self {
+            sb: 0,
+            sc: 0,
+            mode: SerialMode::Instant,
+            loopback: false,
+            bits_remaining: 0,
+            bit_cycles: 0,
+            output: Vec::new(),
+            output_cap: None,
+            output_total: 0,
+            interrupt_requested: false,
+        }
+    }
+
+    /// Get the transfer timing mode
+    pub fn mode(&self) -> SerialMode {
+        self.mode
+    }
+
+    /// Set the transfer timing mode
+    pub fn set_mode(&mut self, mode: SerialMode) {
+        self.mode = mode;
+    }
+
+    /// Check if loopback is enabled
+    pub fn loopback(&self) -> bool {
+        self.loopback
+    }
+
+    /// Enable or disable loopback (output wired to input)
+    pub fn set_loopback(&mut self, enabled: bool) {
+        self.loopback = enabled;
+    }
+
+    /// Limit the output buffer to the most recent `bytes` bytes
+    pub fn set_output_cap(&mut self, bytes: usize) {
+        self.output_cap = Some(bytes);
+        self.trim_output();
+    }
+
+    /// Remove the output buffer limit
+    pub fn clear_output_cap(&mut self) {
+        self.output_cap = None;
+    }
+
+    /// Number of bytes ever sent, including ones dropped by the cap
+    pub fn output_total(&self) -> usize {
+        self.output_total
+    }
+
+    /// Drop the oldest output bytes beyond the cap
+    fn trim_output(&mut self) {
+        if let Some(cap) = self.output_cap {
+            if self.output.len() > cap {
+                let excess = self.output.len() - cap;
+                self.output.drain(..excess);
+            }
+        }
+    }
+
+    /// Check if a transfer is in progress (SC bit 7)
+    pub fn transfer_in_progress(&self) -> bool {
+        self.sc & 0x80 != 0
+    }
+
+    /// Decode the raw SC register
+    pub fn control(&self) -> SerialControl {
+        SerialControl {
+            transfer_start: self.sc & 0x80 != 0,
+            internal_clock: self.sc & 0x01 != 0,
+            fast_clock: self.sc & 0x02 != 0,
+        }
+    }
+
+    /// Read SB (0xFF01)
+    pub fn read_sb(&self) -> u8 {
+        self.sb
+    }
+
+    /// Write SB (0xFF01)
+    pub fn write_sb(&mut self, value: u8) {
+        self.sb = value;
+    }
+
+    /// Read SC (0xFF02) - unused bits return 1
+    /// Bit 1 (fast clock) is only readable on CGB.
+    pub fn read_sc(&self, cgb: bool) -> u8 {
+        self.sc | if cgb { 0x7C } else { 0x7E }
+    }
+
+    /// Write SC (0xFF02)
+    /// When bits 7 and 0 are set (e.g. 0x81, or 0x83 for a CGB fast transfer),
+    /// an internally clocked transfer is initiated; other bits don't matter
+    pub fn write_sc(&mut self, value: u8) {
+        self.sc = value;
+        if value & 0x80 == 0 {
+            // Clearing the start flag aborts a transfer in progress
+            self.bits_remaining = 0;
+            self.bit_cycles = 0;
+        } else if value & 0x81 == 0x80 {
+            // Externally clocked: wait for the partner's 8 pulses
+            self.bits_remaining = 8;
+        } else if value & 0x81 == 0x81 {
+            // Transfer requested - capture the byte from SB
+            self.output.push(self.sb);
+            self.output_total += 1;
+            self.trim_output();
+
+            match self.mode {
+                SerialMode::Instant => {
+                    if !self.loopback {
+                        self.sb = 0xFF;
+                    }
+                    self.complete_transfer();
+                }
+                SerialMode::Timed => {
+                    self.bits_remaining = 8;
+                    self.bit_cycles = 0;
+                }
+            }
+        }
+    }
+
+    /// Finish the transfer: clear the start flag and request the interrupt
+    fn complete_transfer(&mut self) {
+        self.sc &= 0x7F;
+        self.bits_remaining = 0;
+        self.interrupt_requested = true;
+    }
+
+    /// Shift one bit on an external clock edge (slave mode)
+    /// Returns the received byte once all 8 bits have been shifted in
+    pub fn external_clock_pulse(&mut self, incoming_bit: u8) -> Option<u8> {
+        if !self.transfer_in_progress() || self.sc & 0x01 != 0 || self.bits_remaining == 0 {
+            return None;
+        }
+
+        self.sb = (self.sb << 1) | (incoming_bit & 0x01);
+        self.bits_remaining -= 1;
+
+        if self.bits_remaining == 0 {
+            self.complete_transfer();
+            Some(self.sb)
+        } else {
+            None
+        }
+    }
+
+    /// Update serial state for elapsed cycles
+    pub fn tick(&mut self, cycles: u32) {
+        // Only the internal clock advances with time
+        if self.bits_remaining == 0 || self.sc & 0x01 == 0 {
+            return;
+        }
+
+        self.bit_cycles += cycles;
+        while self.bit_cycles >= CYCLES_PER_BIT && self.bits_remaining > 0 {
+            self.bit_cycles -= CYCLES_PER_BIT;
+
+            // Shift out the MSB, shift in the received bit
+            // (1 with no partner, the outgoing bit in loopback)
+            let received = if self.loopback { self.sb >> 7 } else { 1 };
+            self.sb = (self.sb << 1) | received;
+            self.bits_remaining -= 1;
+
+            if self.bits_remaining == 0 {
+                self.complete_transfer();
+            }
+        }
+    }
+
+    /// Take the interrupt request (clears the flag)
+    pub fn take_interrupt(&mut self) -> bool {
+        let requested = self.interrupt_requested;
+        self.interrupt_requested = false;
+        requested
+    }
+}
+
+impl Default for Serial {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_instant_transfer() {
+        let mut serial = Serial::new();
+
+        serial.write_sb(b'A');
+        serial.write_sc(0x81);
+
+        assert_eq!(serial.output, vec![b'A']);
+        assert!(!serial.transfer_in_progress());
+        assert_eq!(serial.read_sb(), 0xFF);
+        assert!(serial.take_interrupt());
+    }
+
+    #[test]
+    fn test_transfer_start_ignores_other_bits() {
+        for sc in [0x83, 0xC1, 0xFF] {
+            let mut serial = Serial::new();
+            serial.write_sb(b'Z');
+            serial.write_sc(sc);
+            assert_eq!(serial.output, vec![b'Z'], "SC={:02X}", sc);
+            assert!(serial.take_interrupt());
+        }
+
+        // Bit 0 clear: externally clocked, nothing captured yet
+        let mut serial = Serial::new();
+        serial.write_sb(b'Z');
+        serial.write_sc(0x82);
+        assert!(serial.output.is_empty());
+    }
+
+    #[test]
+    fn test_output_cap() {
+        let mut serial = Serial::new();
+        serial.set_output_cap(4);
+        for byte in b"abcdefgh" {
+            serial.write_sb(*byte);
+            serial.write_sc(0x81);
+            assert!(serial.output.len() <= 4);
+        }
+        assert_eq!(serial.output, b"efgh");
+
+        serial.clear_output_cap();
+        serial.write_sb(b'i');
+        serial.write_sc(0x81);
+        assert_eq!(serial.output, b"efghi");
+    }
+
+    #[test]
+    fn test_timed_transfer() {
+        let mut serial = Serial::new();
+        serial.set_mode(SerialMode::Timed);
+
+        serial.write_sb(b'A');
+        serial.write_sc(0x81);
+        assert_eq!(serial.output, vec![b'A']);
+
+        // Still in progress one cycle before the 8th bit
+        serial.tick(8 * CYCLES_PER_BIT - 1);
+        assert!(serial.transfer_in_progress());
+        assert!(!serial.interrupt_requested);
+
+        serial.tick(1);
+        assert!(!serial.transfer_in_progress());
+        assert_eq!(serial.read_sb(), 0xFF);
+        assert!(serial.take_interrupt());
+    }
+
+    #[test]
+    fn test_clearing_start_aborts_transfer() {
+        let mut serial = Serial::new();
+        serial.set_mode(SerialMode::Timed);
+        serial.write_sb(b'A');
+        serial.write_sc(0x81);
+        serial.tick(4 * CYCLES_PER_BIT);
+
+        serial.write_sc(0x01);
+        serial.tick(8 * CYCLES_PER_BIT);
+        assert!(!serial.transfer_in_progress());
+        assert!(!serial.take_interrupt());
+
+        // A new transfer takes the full 8 bits again
+        serial.write_sc(0x81);
+        serial.tick(8 * CYCLES_PER_BIT - 1);
+        assert!(!serial.take_interrupt());
+        serial.tick(1);
+        assert!(serial.take_interrupt());
+    }
+
+    #[test]
+    fn test_loopback_transfer() {
+        for mode in [SerialMode::Instant, SerialMode::Timed] {
+            let mut serial = Serial::new();
+            serial.set_mode(mode);
+            serial.set_loopback(true);
+
+            serial.write_sb(0x5A);
+            serial.write_sc(0x81);
+            serial.tick(8 * CYCLES_PER_BIT);
+
+            assert!(!serial.transfer_in_progress());
+            assert_eq!(serial.read_sb(), 0x5A, "{:?}", mode);
+        }
+    }
+}
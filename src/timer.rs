@@ -22,6 +22,19 @@
 // Internal counter:
 // The timer uses a 16-bit internal counter. DIV is the upper 8 bits.
 // TIMA increments based on specific bits of this counter.
+//
+// Initial counter:
+// With a boot ROM the counter starts at 0 on power-on. When the boot ROM is
+// skipped, it starts where the boot ROM would have left it at 0x0100, which
+// depends on the model (the boot ROMs take different times):
+//   DMG: 0xABCC (DIV = 0xAB)
+//   CGB: 0x1EA0 (DIV = 0x1E)
+//
+// TIMA increments on a falling edge of the selected bit. Bit n falls every
+// time the counter reaches a multiple of 2^(n+1), so the number of
+// increments over an interval can be computed without stepping each cycle.
+
+use crate::model::Model;
 
 /// Timer state
 pub struct Timer {
@@ -39,9 +52,16 @@ pub struct Timer {
 }
 
 impl Timer {
+    /// Create a DMG timer in its post-boot state
     pub fn new() -> Self {
+        Self::with_model(Model::Dmg, false)
+    }
+
+    /// Create a timer for the given model
+    /// `boot_rom`: a boot ROM will run from power-on (otherwise post-boot state)
+    pub fn with_model(model: Model, boot_rom: bool) -> Self {
         Self {
-            internal_counter: 0xABCC, // Post-boot value
+            internal_counter: Self::initial_counter(model, boot_rom),
             tima: 0,
             tma: 0,
             tac: 0,
@@ -49,6 +69,15 @@ impl Timer {
         }
     }
 
+    /// Internal counter value at startup
+    pub fn initial_counter(model: Model, boot_rom: bool) -> u16 {
+        match (boot_rom, model) {
+            (true, _) => 0x0000,
+            (false, Model::Dmg) => 0xABCC,
+            (false, Model::Cgb) => 0x1EA0,
+        }
+    }
+
     /// Get DIV register (upper 8 bits of internal counter)
     pub fn div(&self) -> u8 {
         (self.internal_counter >> 8) as u8
@@ -72,16 +101,20 @@ impl Timer {
         (self.tac & 0x04) != 0
     }
 
-    /// Get the bit of internal counter that controls TIMA increments
-    fn get_timer_bit(&self) -> bool {
-        let bit_pos = match self.tac & 0x03 {
+    /// Get the position of the internal counter bit selected by TAC
+    fn timer_bit_position(&self) -> u32 {
+        match self.tac & 0x03 {
             0 => 9,  // 4096 Hz (bit 9)
             1 => 3,  // 262144 Hz (bit 3)
             2 => 5,  // 65536 Hz (bit 5)
             3 => 7,  // 16384 Hz (bit 7)
             _ => unreachable!(),
-        };
-        (self.internal_counter & (1 << bit_pos)) != 0
+        }
+    }
+
+    /// Get the bit of internal counter that controls TIMA increments
+    fn get_timer_bit(&self) -> bool {
+        (self.internal_counter & (1 << self.timer_bit_position())) != 0
     }
 
     /// Increment TIMA, handling overflow
@@ -97,17 +130,20 @@ impl Timer {
 
     /// Update timer state for elapsed cycles
     pub fn tick(&mut self, cycles: u32) {
-        for _ in 0..cycles {
-            let old_bit = self.get_timer_bit() && self.timer_enabled();
-
-            self.internal_counter = self.internal_counter.wrapping_add(1);
+        let start = self.internal_counter as u64;
+        let end = start + cycles as u64;
+        self.internal_counter = end as u16;
 
-            let new_bit = self.get_timer_bit() && self.timer_enabled();
+        if !self.timer_enabled() {
+            return;
+        }
 
-            // Falling edge detection: bit goes from 1 to 0
-            if old_bit && !new_bit {
-                self.increment_tima();
-            }
+        // Falling edges = multiples of the bit's period crossed in (start, end]
+        // (the 16-bit wrap-around is a multiple of every period)
+        let period = 2u64 << self.timer_bit_position();
+        let falling_edges = end / period - start / period;
+        for _ in 0..falling_edges {
+            self.increment_tima();
         }
     }
 
@@ -117,7 +153,9 @@ impl Timer {
         self.tac = value;
         let new_bit = self.get_timer_bit() && self.timer_enabled();
 
-        // Changing TAC can trigger TIMA increment
+        // Changing TAC can trigger TIMA increment (the enable is ANDed with
+        // the selected bit, so disabling while the bit is high is a falling
+        // edge; re-enabling while it is high is a rising edge and does nothing)
         if old_bit && !new_bit {
             self.increment_tima();
         }
@@ -189,6 +227,52 @@ mod tests {
         assert!(timer.interrupt_requested);
     }
 
+    /// Reference implementation: step the counter one cycle at a time
+    fn tick_per_cycle(timer: &mut Timer, cycles: u32) {
+        for _ in 0..cycles {
+            let old_bit = timer.get_timer_bit() && timer.timer_enabled();
+            timer.internal_counter = timer.internal_counter.wrapping_add(1);
+            let new_bit = timer.get_timer_bit() && timer.timer_enabled();
+            if old_bit && !new_bit {
+                timer.increment_tima();
+            }
+        }
+    }
+
+    #[test]
+    fn test_batched_tick_matches_per_cycle() {
+        // Small deterministic LCG so the test needs no dependencies
+        let mut seed: u32 = 0x1234_5678;
+        let mut next = move || {
+            seed = seed.wrapping_mul(1_664_525).wrapping_add(1_013_904_223);
+            seed >> 8
+        };
+
+        for _ in 0..200 {
+            let mut fast = Timer::new();
+            fast.internal_counter = next() as u16;
+            fast.tima = next() as u8;
+            fast.tma = next() as u8;
+            fast.tac = (next() & 0x07) as u8;
+
+            let mut slow = Timer::new();
+            slow.internal_counter = fast.internal_counter;
+            slow.tima = fast.tima;
+            slow.tma = fast.tma;
+            slow.tac = fast.tac;
+
+            for _ in 0..5 {
+                let cycles = next() % 20_000;
+                fast.tick(cycles);
+                tick_per_cycle(&mut slow, cycles);
+
+                assert_eq!(fast.internal_counter, slow.internal_counter);
+                assert_eq!(fast.tima, slow.tima, "TAC={:02X} cycles={}", fast.tac, cycles);
+                assert_eq!(fast.take_interrupt(), slow.take_interrupt());
+            }
+        }
+    }
+
     #[test]
     fn test_timer_frequency() {
         // Test clock select 01 (262144 Hz = every 16 cycles)
@@ -203,4 +287,39 @@ mod tests {
         timer.tick(16);
         assert_eq!(timer.tima, 2);
     }
+
+    #[test]
+    fn test_tac_disable_reenable_with_bit_high() {
+        let mut timer = Timer::new();
+        timer.internal_counter = 0x0008; // Bit 3 high
+        timer.tac = 0x05; // Enabled, 262144 Hz (bit 3)
+
+        // Disabling while the selected bit is high is a falling edge
+        timer.write_tac(0x01);
+        assert_eq!(timer.tima, 1);
+
+        // Re-enabling while it is still high must not increment
+        timer.write_tac(0x05);
+        assert_eq!(timer.tima, 1);
+
+        // Counting resumes normally: next falling edge at 0x0010
+        timer.tick(7);
+        assert_eq!(timer.tima, 1);
+        timer.tick(1);
+        assert_eq!(timer.tima, 2);
+        timer.tick(16);
+        assert_eq!(timer.tima, 3);
+    }
+
+    #[test]
+    fn test_initial_div_per_model() {
+        assert_eq!(Timer::with_model(Model::Dmg, false).div(), 0xAB);
+        assert_eq!(Timer::with_model(Model::Cgb, false).div(), 0x1E);
+        assert_eq!(Timer::new().internal_counter, 0xABCC);
+
+        for model in [Model::Dmg, Model::Cgb] {
+            let timer = Timer::with_model(model, true);
+            assert_eq!(timer.internal_counter, 0, "{:?}", model);
+        }
+    }
 }
@@ -0,0 +1,213 @@
+// Reference Trace Comparison
+//
+// The standard way to pin down CPU bugs is to log the CPU state before every
+// instruction and diff the log against one produced by a known-good emulator.
+// This module uses the gameboy-doctor log format, one line per instruction:
+//
+//   A:01 F:B0 B:00 C:13 D:00 E:D8 H:01 L:4D SP:FFFE PC:0100 PCMEM:00,C3,13,02
+//
+// PCMEM holds the 4 bytes at PC, which usually covers the whole instruction.
+//
+// Lines are only produced when the next step will execute an instruction:
+// HALT idling and interrupt dispatch do not emit a line (same as gameboy-doctor).
+
+use std::fmt;
+use std::fs;
+use std::path::Path;
+
+use crate::emulator::Emulator;
+
+/// Field names of a trace line, in order
+const FIELDS: [&str; 11] = ["A", "F", "B", "C", "D", "E", "H", "L", "SP", "PC", "PCMEM"];
+
+/// First divergence between the emulator and a reference trace
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TraceMismatch {
+    /// Line number in the reference trace (1-based)
+    pub line: usize,
+    /// PC of the instruction about to execute when the divergence was found
+    pub pc: u16,
+    /// Name of the first differing field (e.g. "A", "SP", "PCMEM")
+    pub field: String,
+    /// Value from the reference trace
+    pub expected: String,
+    /// Value produced by the emulator
+    pub actual: String,
+}
+
+impl fmt::Display for TraceMismatch {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "trace mismatch at line {} (PC=0x{:04X}): {} expected {}, got {}",
+            self.line, self.pc, self.field, self.expected, self.actual
+        )
+    }
+}
+
+/// Format the current CPU state as a gameboy-doctor trace line
+pub fn trace_line(emu: &Emulator) -> String {
+    let regs = &emu.cpu.regs;
+    let pc = regs.pc;
+    format!(
+        "A:{:02X} F:{:02X} B:{:02X} C:{:02X} D:{:02X} E:{:02X} H:{:02X} L:{:02X} SP:{:04X} PC:{:04X} PCMEM:{:02X},{:02X},{:02X},{:02X}",
+        regs.a,
+        regs.f.to_byte(),
+        regs.b,
+        regs.c,
+        regs.d,
+        regs.e,
+        regs.h,
+        regs.l,
+        regs.sp,
+        pc,
+        emu.bus.read(pc),
+        emu.bus.read(pc.wrapping_add(1)),
+        emu.bus.read(pc.wrapping_add(2)),
+        emu.bus.read(pc.wrapping_add(3)),
+    )
+}
+
+/// Check whether the next step will execute an instruction
+/// (as opposed to idling in HALT or dispatching an interrupt)
+fn executes_instruction(emu: &Emulator) -> bool {
+    let pending = emu.bus.read(0xFFFF) & emu.bus.read(0xFF0F) & 0x1F;
+    if emu.cpu.ime && pending != 0 {
+        return false;
+    }
+    // A pending interrupt wakes HALT even with IME off
+    !emu.cpu.halted || pending != 0
+}
+
+/// Step until the next instruction is about to execute, giving up after max_steps
+fn advance_to_instruction(emu: &mut Emulator, max_steps: usize) -> bool {
+    for _ in 0..max_steps {
+        if executes_instruction(emu) {
+            return true;
+        }
+        emu.step();
+    }
+    executes_instruction(emu)
+}
+
+/// Split a trace line into (field, value) pairs
+fn parse_fields(line: &str) -> Vec<(&str, &str)> {
+    line.split_whitespace()
+        .filter_map(|part| part.split_once(':'))
+        .collect()
+}
+
+/// Compare two trace lines field by field
+/// Returns (field, expected, actual) for the first difference
+fn diff_lines(expected: &str, actual: &str) -> Option<(String, String, String)> {
+    let expected_fields = parse_fields(expected);
+    let actual_fields = parse_fields(actual);
+
+    for name in FIELDS {
+        let exp = expected_fields.iter().find(|(k, _)| *k == name).map(|(_, v)| *v);
+        let act = actual_fields.iter().find(|(k, _)| *k == name).map(|(_, v)| *v);
+
+        // Fields missing from the reference are not compared
+        // (some tools omit PCMEM)
+        if let Some(exp) = exp {
+            let act = act.unwrap_or("");
+            if !exp.eq_ignore_ascii_case(act) {
+                return Some((name.to_string(), exp.to_string(), act.to_string()));
+            }
+        }
+    }
+    None
+}
+
+/// Run the emulator against a reference trace, stopping at the first divergence
+///
+/// Each non-empty line of `reference` is compared against the emulator state
+/// before the corresponding instruction executes. Returns the number of lines
+/// that matched, or the first mismatch.
+pub fn compare_trace(emu: &mut Emulator, reference: &str) -> Result<usize, TraceMismatch> {
+    let mut matched = 0;
+
+    for (index, expected) in reference.lines().enumerate() {
+        let expected = expected.trim();
+        if expected.is_empty() {
+            continue;
+        }
+
+        // Skip HALT idling and interrupt dispatch (bounded so a stuck CPU can't hang)
+        advance_to_instruction(emu, 1_000_000);
+
+        let actual = trace_line(emu);
+        if let Some((field, expected, actual)) = diff_lines(expected, &actual) {
+            return Err(TraceMismatch {
+                line: index + 1,
+                pc: emu.cpu.regs.pc,
+                field,
+                expected,
+                actual,
+            });
+        }
+
+        emu.step();
+        matched += 1;
+    }
+
+    Ok(matched)
+}
+
+/// Run the emulator against a reference trace file (gameboy-doctor format)
+pub fn compare_trace_file<P: AsRef<Path>>(emu: &mut Emulator, path: P) -> Result<usize, String> {
+    let reference = fs::read_to_string(&path)
+        .map_err(|e| format!("Failed to read trace file: {}", e))?;
+    compare_trace(emu, &reference).map_err(|m| m.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn create_test_emulator() -> Emulator {
+        let mut rom = vec![0u8; 0x8000];
+        let program: &[u8] = &[
+            0x3E, 0x42,       // LD A, 0x42
+            0x06, 0x10,       // LD B, 0x10
+            0x80,             // ADD A, B
+            0x76,             // HALT
+        ];
+        rom[0x0100..0x0100 + program.len()].copy_from_slice(program);
+        Emulator::with_rom(&rom)
+    }
+
+    const EXPECTED_TRACE: &str = "\
+A:01 F:B0 B:00 C:13 D:00 E:D8 H:01 L:4D SP:FFFE PC:0100 PCMEM:3E,42,06,10
+A:42 F:B0 B:00 C:13 D:00 E:D8 H:01 L:4D SP:FFFE PC:0102 PCMEM:06,10,80,76
+A:42 F:B0 B:10 C:13 D:00 E:D8 H:01 L:4D SP:FFFE PC:0104 PCMEM:80,76,00,00
+A:52 F:00 B:10 C:13 D:00 E:D8 H:01 L:4D SP:FFFE PC:0105 PCMEM:76,00,00,00
+";
+
+    #[test]
+    fn test_trace_line_format() {
+        let emu = create_test_emulator();
+        assert_eq!(trace_line(&emu), EXPECTED_TRACE.lines().next().unwrap());
+    }
+
+    #[test]
+    fn test_trace_matches_reference() {
+        let mut emu = create_test_emulator();
+        assert_eq!(compare_trace(&mut emu, EXPECTED_TRACE), Ok(4));
+    }
+
+    #[test]
+    fn test_trace_mismatch_is_pinpointed() {
+        // Deliberately wrong A after ADD A, B (0x53 instead of 0x52)
+        let reference = EXPECTED_TRACE.replace("A:52", "A:53");
+
+        let mut emu = create_test_emulator();
+        let mismatch = compare_trace(&mut emu, &reference).unwrap_err();
+
+        assert_eq!(mismatch.line, 4);
+        assert_eq!(mismatch.pc, 0x0105);
+        assert_eq!(mismatch.field, "A");
+        assert_eq!(mismatch.expected, "53");
+        assert_eq!(mismatch.actual, "52");
+    }
+}
@@ -1,5 +1,6 @@
 // Integration tests for the Game Boy emulator
 
+use rust_gb_emu::cartridge::{compute_header_checksum, Cartridge};
 use rust_gb_emu::emulator::Emulator;
 
 /// Create a ROM with a test program
@@ -14,6 +15,30 @@ fn create_test_rom(program: &[u8]) -> Vec<u8> {
     rom
 }
 
+/// Size of one switchable ROM bank
+const BANK_SIZE: usize = 0x4000;
+
+/// Create an MBC1 ROM with code in several banks
+/// Bank 0's program starts at the entry point (0x0100); every other bank's
+/// program starts at the beginning of the bank (0x4000 when mapped).
+/// The bank count is padded to a power of two, and the header's cartridge
+/// type, ROM size and checksum are set to match.
+fn build_banked_rom(banks: &[&[u8]]) -> Vec<u8> {
+    let bank_count = banks.len().max(2).next_power_of_two();
+    let mut rom = vec![0u8; bank_count * BANK_SIZE];
+
+    for (bank, program) in banks.iter().enumerate() {
+        let start = if bank == 0 { 0x0100 } else { bank * BANK_SIZE };
+        rom[start..start + program.len()].copy_from_slice(program);
+    }
+
+    rom[0x0147] = 0x01; // MBC1
+    rom[0x0148] = (bank_count / 2).trailing_zeros() as u8; // 32KB << n
+    rom[0x014D] = compute_header_checksum(&rom);
+
+    rom
+}
+
 #[test]
 fn test_serial_hello_world() {
     // Program that outputs "Hello" via serial
@@ -240,3 +265,34 @@ fn test_swap() {
 
     assert_eq!(emu.cpu.regs.a, 0xBA);
 }
+
+#[test]
+fn test_banked_rom_trampoline() {
+    let bank0: &[u8] = &[
+        0x3E, 0x02,       // LD A, 2
+        0xEA, 0x00, 0x20, // LD (0x2000), A -> select ROM bank 2
+        0xC3, 0x00, 0x40, // JP 0x4000
+    ];
+    let bank1: &[u8] = &[
+        0x3E, 0x11,       // LD A, 0x11     (must not run)
+        0x76,             // HALT
+    ];
+    let bank2: &[u8] = &[
+        0x3E, 0x22,       // LD A, 0x22
+        0xEA, 0x00, 0xC0, // LD (0xC000), A
+        0x76,             // HALT
+    ];
+
+    let rom = build_banked_rom(&[bank0, bank1, bank2]);
+    assert_eq!(rom.len(), 4 * BANK_SIZE);
+    let cart = Cartridge::from_bytes(rom).unwrap();
+    assert_eq!(cart.info.rom_size, 4 * BANK_SIZE);
+    assert!(cart.info.checksum_valid);
+
+    let mut emu = Emulator::new(&cart);
+    emu.run_until_halt(1000);
+
+    assert_eq!(emu.cpu.regs.a, 0x22);
+    assert_eq!(emu.bus.read(0xC000), 0x22);
+    assert_eq!(emu.bus.current_rom_bank(), 2);
+}